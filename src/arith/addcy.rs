@@ -74,7 +74,44 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     lv: &ArithCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let in0 = lv.in0;
+    let in1 = lv.in1;
+    let out = lv.out;
+    let aux = lv.aux;
+
+    // Eval addition.
+    eval_add_circuit(cb, cc, lv.op.f_add, in0, in1, out, aux);
+
+    // Eval subtraction.
+    eval_sub_circuit(cb, cc, lv.op.f_sub, in0, in1, out, aux);
+
+    // Eval unsigned less than.
+    eval_ltu_circuit(cb, cc, lv.op.f_ltu, in0, in1, out, aux);
+
+    // Eval unsigned greater than or equal to.
+    let one = cb.one_extension();
+    let not_out = cb.sub_extension(one, out);
+    eval_ltu_circuit(cb, cc, lv.op.f_geu, in0, in1, not_out, aux);
+
+    // Eval signed less than and signed greater than or equal to.
+    let f_lts = lv.op.f_lts;
+    let f_ges = lv.op.f_ges;
+    let f_signed = cb.add_extension(f_lts, f_ges);
+    let in0_bias = lv.in0_bias;
+    let in1_bias = lv.in1_bias;
+    let sign_bit = cb.constant_extension(F::Extension::from_canonical_u32(SIGN_BIT));
+
+    // in0 + 2^31 == in0_bias
+    eval_add_circuit(cb, cc, f_signed, in0, sign_bit, in0_bias, lv.in0_aux);
+
+    // in1 + 2^31 == in1_bias
+    eval_add_circuit(cb, cc, f_signed, in1, sign_bit, in1_bias, lv.in1_aux);
+
+    // in0_bias <u in1_bias == out
+    eval_ltu_circuit(cb, cc, f_lts, in0_bias, in1_bias, out, aux);
+
+    // in0_bias <u in1_bias == 1 - out
+    eval_ltu_circuit(cb, cc, f_ges, in0_bias, in1_bias, not_out, aux);
 }
 
 /// Constrains `x + y == z + cy*2^32` if `filter != 0`.
@@ -125,7 +162,34 @@ fn eval_addcy_circuit<F: RichField + Extendable<D>, const D: usize>(
 ) {
     let base = F::from_canonical_u64(1u64 << REG_BITS);
     let base_inv = F::from_canonical_u64(GOLDILOCKS_INVERSE_REG_SIZE);
-    //TODO
+    debug_assert!(base * base_inv == F::ONE);
+
+    // diff in {0, base}
+    let sum = cb.add_extension(x, y);
+    let diff = cb.sub_extension(sum, z);
+    let diff_sub_base = cb.add_const_extension(diff, -base);
+    let t = cb.mul_extension(diff, diff_sub_base);
+    let cs = cb.mul_extension(filter, t);
+    if transition {
+        cc.constraint_transition(cb, cs);
+    } else {
+        cc.constraint(cb, cs);
+    }
+
+    // did_cy in {0, 1}
+    let did_cy = cb.mul_const_extension(base_inv, diff);
+    let t = cb.mul_sub_extension(cy, cy, cy);
+    let cs = cb.mul_extension(filter, t);
+    cc.constraint(cb, cs);
+
+    // did_cy matches cy
+    let t = cb.sub_extension(did_cy, cy);
+    let cs = cb.mul_extension(filter, t);
+    if transition {
+        cc.constraint_transition(cb, cs);
+    } else {
+        cc.constraint(cb, cs);
+    }
 }
 
 /// `left + right == out`
@@ -141,6 +205,20 @@ pub(crate) fn eval_add<P: PackedField>(
     eval_addcy(cc, filter, left, right, out, overflow, false)
 }
 
+/// `left + right == out`
+pub(crate) fn eval_add_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+    filter: ExtensionTarget<D>,
+    left: ExtensionTarget<D>,
+    right: ExtensionTarget<D>,
+    out: ExtensionTarget<D>,
+    overflow: ExtensionTarget<D>,
+) {
+    // constrain left + right == out + overflow * 2^32
+    eval_addcy_circuit(cb, cc, filter, left, right, out, overflow, false)
+}
+
 /// `left + right == out`
 pub(crate) fn eval_add_transition<P: PackedField>(
     cc: &mut ConstraintConsumer<P>,
@@ -154,6 +232,20 @@ pub(crate) fn eval_add_transition<P: PackedField>(
     eval_addcy(cc, filter, left, right, out, overflow, true)
 }
 
+/// `left + right == out`
+pub(crate) fn eval_add_transition_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+    filter: ExtensionTarget<D>,
+    left: ExtensionTarget<D>,
+    right: ExtensionTarget<D>,
+    out: ExtensionTarget<D>,
+    overflow: ExtensionTarget<D>,
+) {
+    // constrain left + right == out + overflow * 2^32
+    eval_addcy_circuit(cb, cc, filter, left, right, out, overflow, true)
+}
+
 /// `left - right == out`
 pub(crate) fn eval_sub<P: PackedField>(
     cc: &mut ConstraintConsumer<P>,
@@ -167,6 +259,20 @@ pub(crate) fn eval_sub<P: PackedField>(
     eval_addcy(cc, filter, right, out, left, overflow, false)
 }
 
+/// `left - right == out`
+pub(crate) fn eval_sub_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+    filter: ExtensionTarget<D>,
+    left: ExtensionTarget<D>,
+    right: ExtensionTarget<D>,
+    out: ExtensionTarget<D>,
+    overflow: ExtensionTarget<D>,
+) {
+    // constrain right + out == left + overflow * 2^32
+    eval_addcy_circuit(cb, cc, filter, right, out, left, overflow, false)
+}
+
 /// `left <u right == out` (unsigned).
 pub(crate) fn eval_ltu<P: PackedField>(
     cc: &mut ConstraintConsumer<P>,
@@ -180,6 +286,20 @@ pub(crate) fn eval_ltu<P: PackedField>(
     eval_addcy(cc, filter, right, diff, left, out, false)
 }
 
+/// `left <u right == out` (unsigned).
+pub(crate) fn eval_ltu_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+    filter: ExtensionTarget<D>,
+    left: ExtensionTarget<D>,
+    right: ExtensionTarget<D>,
+    out: ExtensionTarget<D>,
+    diff: ExtensionTarget<D>,
+) {
+    // constrain right + diff == left + out * 2^32
+    eval_addcy_circuit(cb, cc, filter, right, diff, left, out, false)
+}
+
 #[cfg(test)]
 mod tests {
     use core::borrow::BorrowMut;
@@ -225,7 +345,7 @@ mod tests {
         let left = rng.gen();
         let right = rng.gen();
         let op = ArithOp::new(Op::ADD, left, right);
-        let lv = op.into_row();
+        let lv = op.into_row(&mut Default::default(), 0);
 
         let mut cc = constraint_consumer();
         eval(&lv, &mut cc);
@@ -244,7 +364,7 @@ mod tests {
         let left: u32 = rng.gen();
         let right: u32 = rng.gen();
         let op = ArithOp::new(Op::SUB, left, right);
-        let lv = op.into_row();
+        let lv = op.into_row(&mut Default::default(), 0);
 
         let mut cc = constraint_consumer();
         eval(&lv, &mut cc);
@@ -263,7 +383,7 @@ mod tests {
         let left: u32 = rng.gen();
         let right: u32 = rng.gen();
         let op = ArithOp::new(Op::LTU, left, right);
-        let lv = op.into_row();
+        let lv = op.into_row(&mut Default::default(), 0);
 
         let mut cc = constraint_consumer();
         eval(&lv, &mut cc);
@@ -281,7 +401,7 @@ mod tests {
         let left: u32 = rng.gen();
         let right: u32 = rng.gen();
         let op = ArithOp::new(Op::GEU, left, right);
-        let lv = op.into_row();
+        let lv = op.into_row(&mut Default::default(), 0);
 
         let mut cc = constraint_consumer();
         eval(&lv, &mut cc);
@@ -299,7 +419,7 @@ mod tests {
         let left: i32 = rng.gen();
         let right: i32 = rng.gen();
         let op = ArithOp::new(Op::LTS, left as u32, right as u32);
-        let lv = op.into_row();
+        let lv = op.into_row(&mut Default::default(), 0);
 
         let mut cc = constraint_consumer();
         eval(&lv, &mut cc);
@@ -317,7 +437,7 @@ mod tests {
         let left: i32 = rng.gen();
         let right: i32 = rng.gen();
         let op = ArithOp::new(Op::GES, left as u32, right as u32);
-        let lv = op.into_row();
+        let lv = op.into_row(&mut Default::default(), 0);
 
         let mut cc = constraint_consumer();
         eval(&lv, &mut cc);
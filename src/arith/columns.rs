@@ -3,12 +3,17 @@ use core::ops::{Deref, DerefMut, Index, IndexMut};
 
 use rizzo_derive::{DerefColumns, Columns};
 
+use crate::bytes::columns::RangeCheck;
+use crate::bytes::BYTES_WORD;
+
 /// The value of each struct field is the index of the corresponding column.
 pub(crate) const ARITH_COL_MAP: ArithCols<usize> = make_col_map();
 /// The value of each struct field is the index of the corresponding column.
 pub(crate) const OP_COL_MAP: OpCols<usize> = make_op_col_map();
 /// The number of field elements in `ArithCols`.
 pub(crate) const N_ARITH_COLS: usize = core::mem::size_of::<ArithCols<u8>>();
+/// The number of word columns range checked in each row.
+pub(crate) const N_CHECKED_WORDS: usize = 6;
 /// The number of field elements in `OpCols`.
 pub(crate) const N_OP_COLS: usize = core::mem::size_of::<OpCols<u8>>();
 
@@ -52,12 +57,40 @@ pub(crate) struct ArithCols<T> {
     pub in0_aux: T,
     /// Auxiliary column used for signed less than.
     pub in1_aux: T,
+    /// LE bytes of `in0`.
+    pub in0_bytes: [T; BYTES_WORD],
+    /// LE bytes of `in1`.
+    pub in1_bytes: [T; BYTES_WORD],
+    /// LE bytes of `out`.
+    pub out_bytes: [T; BYTES_WORD],
+    /// LE bytes of `aux`. The difference computed by comparisons must be a
+    /// word for the result to be meaningful.
+    pub aux_bytes: [T; BYTES_WORD],
+    /// LE bytes of `in0_bias`.
+    pub in0_bias_bytes: [T; BYTES_WORD],
+    /// LE bytes of `in1_bias`.
+    pub in1_bias_bytes: [T; BYTES_WORD],
+    /// Range check columns.
+    pub range_check: RangeCheck<T>,
 }
 
 impl<T: Copy> ArithCols<T> {
     pub(crate) fn to_vec(&self) -> Vec<T> {
         Borrow::<[T; N_ARITH_COLS]>::borrow(self).to_vec()
     }
+
+    /// The word columns whose values are range checked, each paired with the
+    /// columns holding its LE bytes.
+    pub(crate) fn checked_words(&self) -> [(T, [T; BYTES_WORD]); N_CHECKED_WORDS] {
+        [
+            (self.in0, self.in0_bytes),
+            (self.in1, self.in1_bytes),
+            (self.out, self.out_bytes),
+            (self.aux, self.aux_bytes),
+            (self.in0_bias, self.in0_bias_bytes),
+            (self.in1_bias, self.in1_bias_bytes),
+        ]
+    }
 }
 
 const fn make_col_map() -> ArithCols<usize> {
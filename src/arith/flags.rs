@@ -20,5 +20,11 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     lv: &ArithCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    for flag in *lv.op {
+        let cs = cb.mul_sub_extension(flag, flag, flag);
+        cc.constraint(cb, cs);
+    }
+    let flag_sum = cb.add_many_extension(*lv.op);
+    let cs = cb.mul_sub_extension(flag_sum, flag_sum, flag_sum);
+    cc.constraint(cb, cs);
 }
@@ -4,4 +4,6 @@ pub mod flags;
 pub mod stark;
 pub mod trace;
 
-pub(crate) use addcy::{eval_add, eval_add_transition};
+pub(crate) use addcy::{
+    eval_add, eval_add_circuit, eval_add_transition, eval_add_transition_circuit,
+};
@@ -10,13 +10,13 @@ use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use starky::cross_table_lookup::TableWithColumns;
 use starky::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
-use starky::lookup::{Column, Filter};
+use starky::lookup::{Column, Filter, Lookup};
 use starky::stark::Stark;
 
 use crate::arith::columns::{ArithCols, ARITH_COL_MAP, N_ARITH_COLS};
 use crate::arith::{addcy, flags};
 use crate::stark::Table;
-use crate::util::fst;
+use crate::util::{felt_from_le_bytes, felt_from_le_bytes_circuit, fst};
 use crate::vm::opcode::Opcode;
 
 /// Operation flags and the corresponding opcode.
@@ -43,14 +43,33 @@ pub(crate) fn ctl_looked<F: Field>() -> TableWithColumns<F> {
     TableWithColumns::new(Table::Arith as usize, cols, filter)
 }
 
+/// Columns whose values are range checked to be bytes.
+fn range_checked_cols<F: Field>() -> Vec<Column<F>> {
+    let bytes = ARITH_COL_MAP.checked_words().map(|(_, bytes)| bytes);
+    Column::singles(bytes.iter().flatten()).collect()
+}
+
 #[derive(Debug, Clone, Copy, Default)]
-pub(crate) struct ArithStark<F, const D: usize> {
+pub struct ArithStark<F, const D: usize> {
     _unused: PhantomData<F>,
 }
 
 fn eval_all<P: PackedField>(lv: &ArithCols<P>, nv: &ArithCols<P>, cc: &mut ConstraintConsumer<P>) {
     flags::eval(lv, cc);
     addcy::eval(lv, cc);
+
+    // words are built from their range checked bytes
+    for (word, bytes) in lv.checked_words() {
+        cc.constraint(word - felt_from_le_bytes(bytes));
+    }
+
+    // range check
+    let count = lv.range_check.count;
+    let count_next = nv.range_check.count;
+    let delta = count_next - count;
+    cc.constraint_first_row(count);
+    cc.constraint_transition(delta * (delta - P::ONES));
+    cc.constraint_last_row(count - P::Scalar::from_canonical_u8(u8::MAX));
 }
 
 fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
@@ -61,6 +80,23 @@ fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
 ) {
     flags::eval_circuit(cb, lv, cc);
     addcy::eval_circuit(cb, lv, cc);
+
+    // words are built from their range checked bytes
+    for (word, bytes) in lv.checked_words() {
+        let from_bytes = felt_from_le_bytes_circuit(cb, bytes);
+        let cs = cb.sub_extension(word, from_bytes);
+        cc.constraint(cb, cs);
+    }
+
+    // range check
+    let count = lv.range_check.count;
+    let count_next = nv.range_check.count;
+    let delta = cb.sub_extension(count_next, count);
+    cc.constraint_first_row(cb, count);
+    let cs = cb.mul_sub_extension(delta, delta, delta);
+    cc.constraint_transition(cb, cs);
+    let cs = cb.add_const_extension(count, -F::from_canonical_u8(u8::MAX));
+    cc.constraint_last_row(cb, cs);
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for ArithStark<F, D> {
@@ -105,6 +141,17 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for ArithStark<F,
         3
     }
 
+    fn lookups(&self) -> Vec<Lookup<F>> {
+        let columns = range_checked_cols();
+        let n_cols = columns.len();
+        vec![Lookup {
+            columns,
+            table_column: Column::single(ARITH_COL_MAP.range_check.count),
+            frequencies_column: Column::single(ARITH_COL_MAP.range_check.freq),
+            filter_columns: vec![Default::default(); n_cols],
+        }]
+    }
+
     fn requires_ctls(&self) -> bool {
         true
     }
@@ -134,11 +181,11 @@ mod tests {
         test_stark_low_degree(stark).unwrap();
     }
 
-    // #[test]
-    // fn test_stark_circuit() {
-    //     let stark: S = Default::default();
-    //     test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
-    // }
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
 
     #[test]
     fn test_gen_eval() {
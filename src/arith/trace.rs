@@ -1,12 +1,14 @@
-use core::cmp::max;
-use core::iter::repeat;
+use core::cmp::{max, min};
 
+use hashbrown::HashMap;
 use plonky2::field::polynomial::PolynomialValues;
 use plonky2::field::types::Field;
 use plonky2::util::transpose;
 
 use crate::arith::addcy::SIGN_BIT;
-use crate::arith::columns::{ArithCols, OpCols, OP_COL_MAP};
+use crate::arith::columns::{ArithCols, OpCols, ARITH_COL_MAP, N_CHECKED_WORDS, OP_COL_MAP};
+use crate::bytes::columns::RangeCheck;
+use crate::bytes::BYTES_WORD;
 
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy)]
@@ -52,81 +54,116 @@ impl ArithOp {
         Self { op, in0, in1 }
     }
 
-    fn apply_to_row<F: Field>(self, lv: &mut ArithCols<F>) {
+    /// Fills in the sign carries of signed comparisons and returns the values
+    /// of `out`, `aux`, `in0_bias` and `in1_bias`.
+    fn apply_to_row<F: Field>(self, lv: &mut ArithCols<F>) -> [u32; 4] {
         match self.op {
             Op::ADD => {
                 let (res, cy) = self.in0.overflowing_add(self.in1);
-                lv.aux = F::from_bool(cy);
-                lv.out = F::from_canonical_u32(res);
+                [res, cy.into(), 0, 0]
             }
             Op::SUB => {
                 let (diff, cy) = self.in0.overflowing_sub(self.in1);
-                lv.aux = F::from_bool(cy);
-                lv.out = F::from_canonical_u32(diff);
+                [diff, cy.into(), 0, 0]
             }
             Op::LTU => {
                 let (diff, lt) = self.in0.overflowing_sub(self.in1);
-                lv.aux = F::from_canonical_u32(diff);
-                lv.out = F::from_bool(lt);
+                [lt.into(), diff, 0, 0]
             }
             Op::GEU => {
                 let (diff, lt) = self.in0.overflowing_sub(self.in1);
-                lv.aux = F::from_canonical_u32(diff);
-                lv.out = F::from_bool(!lt);
+                [(!lt).into(), diff, 0, 0]
             }
             Op::LTS => {
                 let (bias0, cy0) = self.in0.overflowing_add(SIGN_BIT);
                 let (bias1, cy1) = self.in1.overflowing_add(SIGN_BIT);
                 let (diff, lt) = bias0.overflowing_sub(bias1);
 
-                lv.in0_bias = F::from_canonical_u32(bias0);
-                lv.in1_bias = F::from_canonical_u32(bias1);
                 lv.in0_aux = F::from_bool(cy0);
                 lv.in1_aux = F::from_bool(cy1);
-                lv.aux = F::from_canonical_u32(diff);
-                lv.out = F::from_bool(lt);
+                [lt.into(), diff, bias0, bias1]
             }
             Op::GES => {
                 let (bias0, cy0) = self.in0.overflowing_add(SIGN_BIT);
                 let (bias1, cy1) = self.in1.overflowing_add(SIGN_BIT);
                 let (diff, lt) = bias0.overflowing_sub(bias1);
 
-                lv.in0_bias = F::from_canonical_u32(bias0);
-                lv.in1_bias = F::from_canonical_u32(bias1);
                 lv.in0_aux = F::from_bool(cy0);
                 lv.in1_aux = F::from_bool(cy1);
-                lv.aux = F::from_canonical_u32(diff);
-                lv.out = F::from_bool(!lt);
+                [(!lt).into(), diff, bias0, bias1]
             }
         }
     }
 
-    pub(in crate::arith) fn into_row<F: Field>(self) -> ArithCols<F> {
+    pub(in crate::arith) fn into_row<F: Field>(
+        self,
+        map: &mut HashMap<u8, usize>,
+        index: usize,
+    ) -> ArithCols<F> {
         let mut row = ArithCols {
             op: self.op.to_op_cols(),
-            in0: F::from_canonical_u32(self.in0),
-            in1: F::from_canonical_u32(self.in1),
+            range_check: RangeCheck {
+                count: rc_count(index),
+                ..Default::default()
+            },
             ..Default::default()
         };
-        self.apply_to_row(&mut row);
+        let [out, aux, in0_bias, in1_bias] = self.apply_to_row(&mut row);
+
+        // write the range checked words and record their bytes
+        let words = [self.in0, self.in1, out, aux, in0_bias, in1_bias];
+        for ((word, bytes), val) in ARITH_COL_MAP.checked_words().into_iter().zip(words) {
+            row[word] = F::from_canonical_u32(val);
+            for (col, byte) in bytes.into_iter().zip(val.to_le_bytes()) {
+                row[col] = F::from_canonical_u8(byte);
+                *map.entry(byte).or_insert(0) += 1;
+            }
+        }
         row
     }
 }
 
 pub(crate) fn gen_trace<F: Field>(ops: Vec<ArithOp>, min_rows: usize) -> Vec<PolynomialValues<F>> {
     let trace = gen_trace_rows(ops, min_rows);
-    let trace_rows: Vec<_> = trace.map(|col| col.to_vec()).collect();
+    let trace_rows: Vec<_> = trace.iter().map(ArithCols::to_vec).collect();
     let trace_cols = transpose(&trace_rows);
     trace_cols.into_iter().map(PolynomialValues::new).collect()
 }
 
-fn gen_trace_rows<F: Field>(
-    ops: Vec<ArithOp>,
-    min_rows: usize,
-) -> impl Iterator<Item = ArithCols<F>> {
-    let n_rows = max(ops.len(), min_rows).next_power_of_two();
-    ops.into_iter()
-        .map(ArithOp::into_row)
-        .chain(repeat(ArithCols::default()))
-        .take(n_rows)
+fn gen_trace_rows<F: Field>(ops: Vec<ArithOp>, min_rows: usize) -> Vec<ArithCols<F>> {
+    let n_ops = ops.len();
+    let n_rows = max(max(n_ops, u8::MAX.into()), min_rows).next_power_of_two();
+
+    let mut rc_freq = HashMap::default();
+    let mut rows: Vec<ArithCols<F>> = ops
+        .into_iter()
+        .enumerate()
+        .map(|(i, op)| op.into_row(&mut rc_freq, i))
+        .chain((n_ops..n_rows).map(padding_row))
+        .collect();
+
+    // account for padding rows in range check frequencies
+    let n_checked = N_CHECKED_WORDS * BYTES_WORD;
+    let pad_freq = rc_freq.entry(0).or_insert(0);
+    *pad_freq += n_checked * (n_rows - n_ops);
+
+    // write range check frequencies column
+    for (val, freq) in rc_freq {
+        rows[val as usize].range_check.freq = F::from_canonical_usize(freq);
+    }
+    rows
+}
+
+fn padding_row<F: Field>(index: usize) -> ArithCols<F> {
+    ArithCols {
+        range_check: RangeCheck {
+            count: rc_count(index),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn rc_count<F: Field>(index: usize) -> F {
+    F::from_canonical_usize(min(index, u8::MAX.into()))
 }
@@ -31,5 +31,24 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     lv: &BitCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    // flags in {0, 1}
+    for flag in *lv.op {
+        let cs = cb.mul_sub_extension(flag, flag, flag);
+        cc.constraint(cb, cs);
+    }
+
+    // at most one op flag is set
+    let flag_sum = cb.add_many_extension(*lv.op);
+    let cs = cb.mul_sub_extension(flag_sum, flag_sum, flag_sum);
+    cc.constraint(cb, cs);
+
+    // input bit values in {0, 1}
+    for bit in lv.in0 {
+        let cs = cb.mul_sub_extension(bit, bit, bit);
+        cc.constraint(cb, cs);
+    }
+    for bit in lv.in1 {
+        let cs = cb.mul_sub_extension(bit, bit, bit);
+        cc.constraint(cb, cs);
+    }
 }
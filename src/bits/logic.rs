@@ -8,7 +8,7 @@ use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
 use crate::bits::columns::BitCols;
-use crate::util::felt_from_le_bits;
+use crate::util::{felt_from_le_bits, felt_from_le_bits_circuit};
 
 /// Constraints for AND, OR, and XOR from [zk_evm].
 ///
@@ -49,5 +49,35 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     lv: &BitCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let f_and = lv.op.f_and;
+    let f_xor = lv.op.f_xor;
+    let f_or = lv.op.f_or;
+
+    let sum_coeff = cb.add_extension(f_or, f_xor);
+    let and_coeff = cb.sub_extension(f_and, f_or);
+    let and_coeff = cb.mul_const_add_extension(-F::TWO, f_xor, and_coeff);
+    let f_logic = cb.add_many_extension([f_and, f_xor, f_or]);
+
+    // `in0 & in1` reconstructed as a single field element.
+    let zero = cb.zero_extension();
+    let x_and_y = izip!(lv.in0, lv.in1, F::TWO.powers()).fold(zero, |acc, (x_bit, y_bit, base)| {
+        cb.arithmetic_extension(base, F::ONE, x_bit, y_bit, acc)
+    });
+
+    // Ensure `lv.and` contains the correct result.
+    let t = cb.sub_extension(lv.and, x_and_y);
+    let cs = cb.mul_extension(f_logic, t);
+    cc.constraint(cb, cs);
+
+    // in0 and in1 reconstructed as field elements.
+    let x = felt_from_le_bits_circuit(cb, lv.in0);
+    let y = felt_from_le_bits_circuit(cb, lv.in1);
+
+    // Output constraint for AND, OR, and XOR.
+    let x_add_y = cb.add_extension(x, y);
+    let t = cb.mul_extension(and_coeff, lv.and);
+    let x_op_y = cb.mul_add_extension(sum_coeff, x_add_y, t);
+    let t = cb.sub_extension(lv.out, x_op_y);
+    let cs = cb.mul_extension(f_logic, t);
+    cc.constraint(cb, cs);
 }
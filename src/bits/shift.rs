@@ -76,6 +76,54 @@ fn sra_ext<P: PackedField>(bits: &[P; WORD_BITS], shift_amt: &[P; WORD_BITS]) ->
         .sum()
 }
 
+/// Circuit version of [`sll`].
+fn sll_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    bits: &[ExtensionTarget<D>; WORD_BITS],
+    shift_amt: &[ExtensionTarget<D>; WORD_BITS],
+) -> ExtensionTarget<D> {
+    let mut acc = cb.zero_extension();
+    for (n, &f_n) in shift_amt.iter().enumerate() {
+        let terms = bits.iter().take(WORD_BITS - n).zip(F::TWO.powers().skip(n));
+        for (&bit, base) in terms {
+            acc = cb.arithmetic_extension(base, F::ONE, f_n, bit, acc);
+        }
+    }
+    acc
+}
+
+/// Circuit version of [`srl`].
+fn srl_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    bits: &[ExtensionTarget<D>; WORD_BITS],
+    shift_amt: &[ExtensionTarget<D>; WORD_BITS],
+) -> ExtensionTarget<D> {
+    let mut acc = cb.zero_extension();
+    for (n, &f_n) in shift_amt.iter().enumerate() {
+        let terms = bits.iter().skip(n).zip(F::TWO.powers());
+        for (&bit, base) in terms {
+            acc = cb.arithmetic_extension(base, F::ONE, f_n, bit, acc);
+        }
+    }
+    acc
+}
+
+/// Circuit version of [`sra_ext`].
+fn sra_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    bits: &[ExtensionTarget<D>; WORD_BITS],
+    shift_amt: &[ExtensionTarget<D>; WORD_BITS],
+) -> ExtensionTarget<D> {
+    // The bases multiplying `f_n * ext_bit` are summed into a single constant.
+    let ext_bit = *bits.last().unwrap();
+    let mut acc = cb.zero_extension();
+    for (n, &f_n) in shift_amt.iter().enumerate() {
+        let base: F = F::TWO.powers().skip(WORD_BITS - n).take(n).sum();
+        acc = cb.arithmetic_extension(base, F::ONE, f_n, ext_bit, acc);
+    }
+    acc
+}
+
 pub(crate) fn eval<P: PackedField>(lv: &BitCols<P>, cc: &mut ConstraintConsumer<P>) {
     let f_sll = lv.op.f_sll;
     let f_srl = lv.op.f_srl;
@@ -101,7 +149,29 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     lv: &BitCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let f_sll = lv.op.f_sll;
+    let f_srl = lv.op.f_srl;
+    let f_sra = lv.op.f_sra;
+    let out = lv.out;
+
+    // SLL
+    let sll_out = sll_circuit(cb, &lv.in0, &lv.in1);
+    let t = cb.sub_extension(out, sll_out);
+    let cs = cb.mul_extension(f_sll, t);
+    cc.constraint(cb, cs);
+
+    // SRL
+    let srl_out = srl_circuit(cb, &lv.in0, &lv.in1);
+    let t = cb.sub_extension(out, srl_out);
+    let cs = cb.mul_extension(f_srl, t);
+    cc.constraint(cb, cs);
+
+    // SRA
+    let sra_ext = sra_ext_circuit(cb, &lv.in0, &lv.in1);
+    let sra_out = cb.add_extension(srl_out, sra_ext);
+    let t = cb.sub_extension(out, sra_out);
+    let cs = cb.mul_extension(f_sra, t);
+    cc.constraint(cb, cs);
 }
 
 #[cfg(test)]
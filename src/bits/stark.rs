@@ -74,11 +74,13 @@ fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &BitCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    flags::eval_circuit(cb, lv, cc);
+    logic::eval_circuit(cb, lv, cc);
+    shift::eval_circuit(cb, lv, cc);
 }
 
 #[derive(Debug, Clone, Copy, Default)]
-pub(crate) struct BitStark<F, const D: usize> {
+pub struct BitStark<F, const D: usize> {
     _unused: PhantomData<F>,
 }
 
@@ -152,11 +154,11 @@ mod tests {
         test_stark_low_degree(stark).unwrap();
     }
 
-    // #[test]
-    // fn test_stark_circuit() {
-    //     let stark: S = Default::default();
-    //     test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
-    // }
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
 
     #[test]
     fn test_gen_eval() {
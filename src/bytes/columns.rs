@@ -40,6 +40,11 @@ pub(crate) struct ByteCols<T> {
     pub len_idx: [T; BYTES_WORD],
     /// The LE byte decomposition of the value being packed or unpacked.
     pub bytes: [T; BYTES_WORD],
+    /// The LE byte decomposition of `adr_virt`.
+    pub adr_bytes: [T; BYTES_WORD],
+    /// The LE byte decomposition of `adr_virt + length - 1`, the address of
+    /// the last byte accessed.
+    pub end_bytes: [T; BYTES_WORD],
     /// The LE bit decomposition of the most significant byte of `bytes`.
     pub high_bits: [T; BITS_U8],
     /// Range check columns.
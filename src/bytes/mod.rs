@@ -4,3 +4,20 @@ pub mod trace;
 
 pub(crate) const BYTES_WORD: usize = 4;
 pub(crate) const BYTES_HALF: usize = 2;
+
+/// How halfword and word loads and stores treat addresses which are not a
+/// multiple of the access size. The machine and the proof must use the same
+/// policy.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Alignment {
+    /// Misaligned accesses are allowed. An access of `len` bytes at `adr` is
+    /// split into the byte accesses at `adr..adr + len`, which may span two
+    /// words. Accesses wrapping around the end of the address space trap,
+    /// and can't be proven: the bytes table range checks the addresses of
+    /// the first and last bytes accessed.
+    #[default]
+    Permissive,
+    /// Misaligned accesses trap. The CPU proves that `adr % len == 0` through
+    /// the bits table.
+    Strict,
+}
@@ -1,5 +1,4 @@
 use core::cmp::{max, min};
-use core::iter::repeat;
 
 use hashbrown::HashMap;
 use plonky2::field::polynomial::PolynomialValues;
@@ -9,13 +8,21 @@ use plonky2::util::transpose;
 use crate::bytes::columns::{ByteCols, RangeCheck};
 use crate::bytes::BYTES_WORD;
 
+/// The number of range checked columns: the bytes of the value and of the
+/// addresses of the first and last bytes.
+const N_RANGE_CHECKED: usize = 3 * BYTES_WORD;
+
 #[derive(Clone, Debug)]
 pub(crate) struct ByteOp {
     pub rw: bool,
     pub signed: bool,
     pub adr_virt: u32,
     pub time: u32,
+    /// The bytes read or written, in big-endian order.
     pub bytes: Vec<u8>,
+    /// For writes, the value of the source register. Only the low
+    /// `bytes.len()` bytes of `src` are written to memory. Ignored for reads.
+    pub src: u32,
 }
 
 impl ByteOp {
@@ -52,21 +59,23 @@ impl ByteOp {
         // deconstruct the most significant byte
         row.high_bits = crate::util::u8_to_le_bits(high_byte);
 
-        // write (maybe sign extended) little-endian bytes to row
-        row.bytes = self
-            .bytes
-            .into_iter()
-            .rev()
-            .chain(repeat(ext_byte))
-            .take(BYTES_WORD)
-            .map(|b| {
-                let freq = map.entry(b).or_insert(0);
-                *freq += 1;
-                F::from_canonical_u8(b)
-            })
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
+        // bytes beyond the length are the extension byte for reads, and the
+        // remaining bytes of the source register for writes
+        let ext_bytes = if self.rw {
+            debug_assert!(self.bytes.iter().rev().eq(&self.src.to_le_bytes()[..len]));
+            self.src.to_le_bytes()[len..].to_vec()
+        } else {
+            vec![ext_byte; BYTES_WORD - len]
+        };
+
+        // decompose the addresses of the first and last bytes
+        let end = self.adr_virt + len as u32 - 1;
+        row.adr_bytes = range_check_bytes(self.adr_virt.to_le_bytes(), map);
+        row.end_bytes = range_check_bytes(end.to_le_bytes(), map);
+
+        // write little-endian bytes to row
+        let bytes: Vec<u8> = self.bytes.into_iter().rev().chain(ext_bytes).collect();
+        row.bytes = range_check_bytes(bytes.try_into().unwrap(), map);
         row
     }
 }
@@ -94,7 +103,7 @@ fn gen_trace_rows<F: Field>(ops: Vec<ByteOp>, min_rows: usize) -> Vec<ByteCols<F
 
     // account for padding rows in range check frequencies
     let pad_freq = rc_freq.entry(0).or_insert(0);
-    *pad_freq += BYTES_WORD * n_rows.saturating_sub(n_ops);
+    *pad_freq += N_RANGE_CHECKED * n_rows.saturating_sub(n_ops);
 
     // write range check frequencies column
     for (val, freq) in rc_freq {
@@ -103,6 +112,18 @@ fn gen_trace_rows<F: Field>(ops: Vec<ByteOp>, min_rows: usize) -> Vec<ByteCols<F
     rows
 }
 
+/// Converts `bytes` to field elements, counting their range check
+/// frequencies in `map`.
+fn range_check_bytes<F: Field>(
+    bytes: [u8; BYTES_WORD],
+    map: &mut HashMap<u8, usize>,
+) -> [F; BYTES_WORD] {
+    bytes.map(|b| {
+        *map.entry(b).or_insert(0) += 1;
+        F::from_canonical_u8(b)
+    })
+}
+
 fn padding_row<F: Field>(index: usize) -> ByteCols<F> {
     ByteCols {
         range_check: RangeCheck {
@@ -12,11 +12,15 @@ pub(crate) fn eval<P: PackedField>(
     nv: &CpuCols<P>,
     cc: &mut ConstraintConsumer<P>,
 ) {
-    let f_arith = lv.op.f_arith;
+    let f_arith = lv.op.f_arith + lv.op.f_muldiv + lv.op.f_shift + lv.op.f_bits;
     let f_imm = lv.f_imm;
 
     // rd = rs1 + rs2
     // rd = rs1 + imm
+    // rd = rs1 * rs2
+    // rd = rs1 << rs2
+    // rd = rs1 & imm
+    cc.constraint(lv.op.f_muldiv * f_imm);
     let ch_rs1 = lv.rs1_channel();
     cc.constraint(f_arith * (P::ONES - ch_rs1.f_on));
     cc.constraint(f_arith * ch_rs1.f_rw);
@@ -44,5 +48,57 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &CpuCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let one = cb.one_extension();
+    let f_arith =
+        cb.add_many_extension([lv.op.f_arith, lv.op.f_muldiv, lv.op.f_shift, lv.op.f_bits]);
+    let f_imm = lv.f_imm;
+
+    // rd = rs1 + rs2
+    // rd = rs1 + imm
+    // rd = rs1 * rs2
+    // rd = rs1 << rs2
+    // rd = rs1 & imm
+    let cs = cb.mul_extension(lv.op.f_muldiv, f_imm);
+    cc.constraint(cb, cs);
+    let ch_rs1 = lv.rs1_channel();
+    let t = cb.sub_extension(one, ch_rs1.f_on);
+    let cs = cb.mul_extension(f_arith, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_arith, ch_rs1.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_arith, ch_rs1.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs1, ch_rs1.adr_virt);
+    let cs = cb.mul_extension(f_arith, t);
+    cc.constraint(cb, cs);
+
+    let ch_rs2 = lv.rs2_channel();
+    let use_rs2 = cb.sub_extension(one, f_imm);
+    let f_use_rs2 = cb.mul_extension(f_arith, use_rs2);
+    let t = cb.mul_extension(f_imm, ch_rs2.f_on);
+    let cs = cb.mul_extension(f_arith, t);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, ch_rs2.f_on);
+    let cs = cb.mul_extension(f_use_rs2, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_use_rs2, ch_rs2.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_use_rs2, ch_rs2.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs2, ch_rs2.adr_virt);
+    let cs = cb.mul_extension(f_use_rs2, t);
+    cc.constraint(cb, cs);
+
+    let ch_rd = lv.rd_channel();
+    let t = cb.sub_extension(one, ch_rd.f_on);
+    let cs = cb.mul_extension(f_arith, t);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, ch_rd.f_rw);
+    let cs = cb.mul_extension(f_arith, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_arith, ch_rd.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rd, ch_rd.adr_virt);
+    let cs = cb.mul_extension(f_arith, t);
+    cc.constraint(cb, cs);
 }
@@ -6,7 +6,7 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
-use crate::arith::eval_add_transition;
+use crate::arith::{eval_add_transition, eval_add_transition_circuit};
 use crate::cpu::columns::CpuCols;
 use crate::cpu::control_flow::INSTRUCTION_BYTES;
 
@@ -66,12 +66,14 @@ pub(crate) fn eval<P: PackedField>(
     // if bne and branching, rs1_val != rs2_val
     cc.constraint(f_bne * (diff * diff_pinv - f_take_branch));
 
-    // if branching, pc += imm
-    eval_add_transition(cc, f_take_branch, lv.pc, lv.imm, nv.pc, lv.f_aux0);
-
-    // if not branching, pc += 4
+    // pc_offset is imm if branching, and 4 otherwise
     let ix_bytes: P = P::Scalar::from_canonical_usize(INSTRUCTION_BYTES).into();
-    eval_add_transition(cc, f_not_take_branch, lv.pc, ix_bytes, nv.pc, lv.f_aux0);
+    let pc_offset = blv.pc_offset;
+    cc.constraint(f_branch * f_take_branch * (pc_offset - lv.imm));
+    cc.constraint(f_branch * f_not_take_branch * (pc_offset - ix_bytes));
+
+    // pc += pc_offset
+    eval_add_transition(cc, f_branch, lv.pc, pc_offset, nv.pc, lv.pc_carry);
 }
 
 pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
@@ -80,5 +82,97 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &CpuCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let one = cb.one_extension();
+    let f_beq = lv.op.f_beq;
+    let f_bne = lv.op.f_bne;
+    let f_branch = cb.add_many_extension([
+        f_beq,
+        f_bne,
+        lv.op.f_bltu,
+        lv.op.f_bgeu,
+        lv.op.f_blt,
+        lv.op.f_bge,
+    ]);
+
+    let blv = lv.shared.branch();
+    let f_take_branch = blv.f_take_branch;
+    let f_not_take_branch = cb.sub_extension(one, f_take_branch);
+
+    // at most one branch flag set
+    let cs = cb.mul_sub_extension(f_branch, f_branch, f_branch);
+    cc.constraint(cb, cs);
+
+    // f_take_branch in {0, 1}
+    let t = cb.mul_sub_extension(f_take_branch, f_take_branch, f_take_branch);
+    let cs = cb.mul_extension(f_branch, t);
+    cc.constraint(cb, cs);
+
+    // disable the rd memory channel
+    let ch_rd = lv.rd_channel();
+    let cs = cb.mul_extension(f_branch, ch_rd.f_on);
+    cc.constraint(cb, cs);
+
+    // read rs1
+    let ch_rs1 = lv.rs1_channel();
+    let t = cb.sub_extension(one, ch_rs1.f_on);
+    let cs = cb.mul_extension(f_branch, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_branch, ch_rs1.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_branch, ch_rs1.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs1, ch_rs1.adr_virt);
+    let cs = cb.mul_extension(f_branch, t);
+    cc.constraint(cb, cs);
+    let rs1_val = ch_rs1.val;
+
+    // read rs2
+    let ch_rs2 = lv.rs2_channel();
+    let t = cb.sub_extension(one, ch_rs2.f_on);
+    let cs = cb.mul_extension(f_branch, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_branch, ch_rs2.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_branch, ch_rs2.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs2, ch_rs2.adr_virt);
+    let cs = cb.mul_extension(f_branch, t);
+    cc.constraint(cb, cs);
+    let rs2_val = ch_rs2.val;
+
+    let diff = cb.sub_extension(rs1_val, rs2_val);
+    let diff_pinv = blv.diff_pinv;
+
+    // if beq and branching, rs1_val == rs2_val
+    let t = cb.mul_extension(f_take_branch, diff);
+    let cs = cb.mul_extension(f_beq, t);
+    cc.constraint(cb, cs);
+    // if beq and not branching, rs1_val != rs2_val
+    let t = cb.mul_sub_extension(diff, diff_pinv, f_not_take_branch);
+    let cs = cb.mul_extension(f_beq, t);
+    cc.constraint(cb, cs);
+
+    // if bne and not branching, rs1_val == rs2_val
+    let t = cb.mul_extension(f_not_take_branch, diff);
+    let cs = cb.mul_extension(f_bne, t);
+    cc.constraint(cb, cs);
+    // if bne and branching, rs1_val != rs2_val
+    let t = cb.mul_sub_extension(diff, diff_pinv, f_take_branch);
+    let cs = cb.mul_extension(f_bne, t);
+    cc.constraint(cb, cs);
+
+    // pc_offset is imm if branching, and 4 otherwise
+    let ix_bytes = F::from_canonical_usize(INSTRUCTION_BYTES);
+    let pc_offset = blv.pc_offset;
+    let t = cb.sub_extension(pc_offset, lv.imm);
+    let t = cb.mul_extension(f_take_branch, t);
+    let cs = cb.mul_extension(f_branch, t);
+    cc.constraint(cb, cs);
+    let t = cb.add_const_extension(pc_offset, -ix_bytes);
+    let t = cb.mul_extension(f_not_take_branch, t);
+    let cs = cb.mul_extension(f_branch, t);
+    cc.constraint(cb, cs);
+
+    // pc += pc_offset
+    eval_add_transition_circuit(cb, cc, f_branch, lv.pc, pc_offset, nv.pc, lv.pc_carry);
 }
@@ -5,25 +5,39 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
-use crate::cpu::columns::CpuCols;
+use crate::cpu::columns::{CpuCols, CpuPublicInputs, CPU_COL_MAP};
 
 pub(crate) fn eval<P: PackedField>(
     lv: &CpuCols<P>,
     nv: &CpuCols<P>,
+    pis: &CpuPublicInputs<P::Scalar>,
     cc: &mut ConstraintConsumer<P>,
 ) {
     // The clock starts at zero.
     cc.constraint_first_row(lv.clock);
     // Each row increments the clock by one.
     cc.constraint_transition(nv.clock - lv.clock - P::ONES);
+
+    // The clock of the first halted row is the number of instructions
+    // executed. Since the clock is strictly increasing, this also ensures
+    // that execution does not resume once halted.
+    let is_op: P = CPU_COL_MAP.op.iter().map(|&i| lv[i]).sum();
+    let is_op_next: P = CPU_COL_MAP.op.iter().map(|&i| nv[i]).sum();
+    let halt = P::ONES - is_op;
+    let halt_next = P::ONES - is_op_next;
+    cc.constraint_first_row(halt * (lv.clock - pis.clock_end));
+    cc.constraint_transition(is_op * halt_next * (nv.clock - pis.clock_end));
 }
 
 pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     cb: &mut CircuitBuilder<F, D>,
     lv: &CpuCols<ExtensionTarget<D>>,
     nv: &CpuCols<ExtensionTarget<D>>,
+    pis: &CpuPublicInputs<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
+    let one = cb.one_extension();
+
     // The clock starts at zero.
     cc.constraint_first_row(cb, lv.clock);
 
@@ -31,4 +45,19 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     let new_clock = cb.add_const_extension(lv.clock, F::ONE);
     let cs = cb.sub_extension(nv.clock, new_clock);
     cc.constraint_transition(cb, cs);
+
+    // The clock of the first halted row is the number of instructions
+    // executed.
+    let is_op = cb.add_many_extension(CPU_COL_MAP.op.iter().map(|&i| lv[i]));
+    let is_op_next = cb.add_many_extension(CPU_COL_MAP.op.iter().map(|&i| nv[i]));
+    let halt = cb.sub_extension(one, is_op);
+    let halt_next = cb.sub_extension(one, is_op_next);
+
+    let t = cb.sub_extension(lv.clock, pis.clock_end);
+    let cs = cb.mul_extension(halt, t);
+    cc.constraint_first_row(cb, cs);
+
+    let t = cb.sub_extension(nv.clock, pis.clock_end);
+    let cs = cb.mul_many_extension([is_op, halt_next, t]);
+    cc.constraint_transition(cb, cs);
 }
@@ -1,14 +1,23 @@
 use core::borrow::{Borrow, BorrowMut};
 use core::ops::{Deref, DerefMut, Index, IndexMut};
 
-use static_assertions::const_assert;
+use plonky2::hash::hash_types::NUM_HASH_OUT_ELTS;
 use rizzo_derive::{Columns, DerefColumns};
+use static_assertions::const_assert;
 
 mod shared;
 use shared::SharedCols;
 
 /// The total number of memory channels.
 pub(crate) const N_MEM_CHANNELS: usize = 3;
+/// The index of the memory channel associated with register `rs1`.
+pub(crate) const RS1_CHANNEL: usize = 0;
+/// The index of the memory channel associated with register `rs2`.
+pub(crate) const RS2_CHANNEL: usize = 1;
+/// The index of the memory channel associated with register `rd`. The channel
+/// index determines the order of accesses within a cycle, so `rd` comes last
+/// to ensure that `rs1` and `rs2` are read before `rd` is written.
+pub(crate) const RD_CHANNEL: usize = 2;
 /// The number of field elements in a `MemChannel`.
 pub(crate) const N_MEM_CHANNEL_COLS: usize = core::mem::size_of::<MemChannel<u8>>();
 
@@ -28,15 +37,31 @@ pub(crate) struct MemChannel<T> {
     pub val: T,
 }
 
+/// The number of bits of a register index.
+pub(crate) const N_REG_BITS: usize = 5;
+
+/// The bits of the register indices of an instruction, in little-endian
+/// order.
+#[repr(C)]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(crate) struct RegBits<T> {
+    pub rs1: [T; N_REG_BITS],
+    pub rs2: [T; N_REG_BITS],
+    pub rd: [T; N_REG_BITS],
+}
+
 /// The number of field elements in `OpCols`.
 pub(crate) const N_OP_COLS: usize = core::mem::size_of::<OpCols<u8>>();
 
-/// Flag columns for the operation to perform.
+/// Flag columns for the operation to perform. The flags of an instruction are
+/// fixed by the program table, see [`ProgramRom`](crate::program::trace::ProgramRom).
 #[repr(C)]
-#[derive(DerefColumns, Clone, Eq, PartialEq, Debug)]
+#[derive(DerefColumns, Clone, Eq, PartialEq, Debug, Default)]
 pub(crate) struct OpCols<T> {
     pub f_arith: T,
     pub f_bits: T,
+    pub f_shift: T,
+    pub f_muldiv: T,
     pub f_lw: T,
     pub f_lh: T,
     pub f_lb: T,
@@ -45,6 +70,10 @@ pub(crate) struct OpCols<T> {
     pub f_sw: T,
     pub f_sh: T,
     pub f_sb: T,
+    pub f_lui: T,
+    pub f_auipc: T,
+    pub f_ecall: T,
+    pub f_ebreak: T,
     pub f_jal: T,
     pub f_jalr: T,
     pub f_beq: T,
@@ -78,14 +107,18 @@ pub(crate) struct CpuCols<T: Copy> {
     pub rs2: T,
     /// Destination register `rd`.
     pub rd: T,
+    /// The bits of `rs1`, `rs2` and `rd`, range checking them.
+    pub reg_bits: RegBits<T>,
     /// The immediate value.
     pub imm: T,
     /// 1 if the immediate value should be used.
     pub f_imm: T,
-    /// Auxiliary column.
-    pub f_aux0: T,
-    /// Auxiliary column.
-    pub f_aux1: T,
+    /// The carry of the pc transition.
+    pub pc_carry: T,
+    /// The position in the input stream of the next byte to read.
+    pub input_pos: T,
+    /// The position in the output stream of the next byte to write.
+    pub output_pos: T,
     /// Memory channels.
     pub membus: [MemChannel<T>; N_MEM_CHANNELS],
     /// Columns dependent on the operation.
@@ -93,22 +126,79 @@ pub(crate) struct CpuCols<T: Copy> {
 }
 
 impl<T: Copy> CpuCols<T> {
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        Borrow::<[T; N_CPU_COLS]>::borrow(self).to_vec()
+    }
+
     /// Returns the memory channel associated with register `rd`.
     pub(crate) fn rd_channel(&self) -> &MemChannel<T> {
-        const_assert!(N_MEM_CHANNELS > 0);
-        &self.membus[0]
+        const_assert!(RD_CHANNEL < N_MEM_CHANNELS);
+        &self.membus[RD_CHANNEL]
     }
 
     /// Returns the memory channel associated with register `rs1`.
     pub(crate) fn rs1_channel(&self) -> &MemChannel<T> {
-        const_assert!(N_MEM_CHANNELS > 1);
-        &self.membus[1]
+        const_assert!(RS1_CHANNEL < N_MEM_CHANNELS);
+        &self.membus[RS1_CHANNEL]
     }
 
     /// Returns the memory channel associated with register `rs2`.
     pub(crate) fn rs2_channel(&self) -> &MemChannel<T> {
-        const_assert!(N_MEM_CHANNELS > 2);
-        &self.membus[2]
+        const_assert!(RS2_CHANNEL < N_MEM_CHANNELS);
+        &self.membus[RS2_CHANNEL]
+    }
+}
+
+/// The number of field elements in `CpuPublicInputs`.
+pub(crate) const N_CPU_PUBLIC_INPUTS: usize = core::mem::size_of::<CpuPublicInputs<u8>>();
+
+/// Public inputs of the cpu stark.
+#[repr(C)]
+#[derive(Columns, Clone, Debug)]
+pub(crate) struct CpuPublicInputs<T: Copy> {
+    /// A hash of the program. The verifier checks that the program table
+    /// holds the instructions it identifies.
+    pub program_hash: [T; NUM_HASH_OUT_ELTS],
+    /// A hash of the initial memory image. The verifier checks that the
+    /// meminit table holds the bytes it identifies.
+    pub image_hash: [T; NUM_HASH_OUT_ELTS],
+    /// A hash of the bytes read from the input stream.
+    pub input_hash: [T; NUM_HASH_OUT_ELTS],
+    /// A hash of the bytes written to the output stream.
+    pub output_hash: [T; NUM_HASH_OUT_ELTS],
+    /// A hash of the final memory. The verifier checks that the memfinal
+    /// table holds the values it identifies.
+    pub final_hash: [T; NUM_HASH_OUT_ELTS],
+    /// The Merkle root of the registers and main memory in which execution
+    /// starts.
+    pub root_start: [T; NUM_HASH_OUT_ELTS],
+    /// The Merkle root of the registers and main memory in which execution
+    /// stops.
+    pub root_end: [T; NUM_HASH_OUT_ELTS],
+    /// The program counter of the first instruction.
+    pub pc_start: T,
+    /// The program counter at which execution halted.
+    pub pc_end: T,
+    /// The position in the input stream of the first byte read.
+    pub input_start: T,
+    /// The position in the input stream following the last byte read.
+    pub input_end: T,
+    /// The position in the output stream of the first byte written.
+    pub output_start: T,
+    /// The position in the output stream following the last byte written.
+    pub output_end: T,
+    /// The number of instructions executed before halting.
+    pub clock_end: T,
+    /// 1 if execution halted with HALT, 0 if this is a segment which
+    /// stopped after its last instruction.
+    pub halted: T,
+    /// The exit code passed to HALT.
+    pub exit_code: T,
+}
+
+impl<T: Copy> CpuPublicInputs<T> {
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        Borrow::<[T; N_CPU_PUBLIC_INPUTS]>::borrow(self).to_vec()
     }
 }
 
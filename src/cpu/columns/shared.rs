@@ -1,12 +1,39 @@
 use core::borrow::{Borrow, BorrowMut};
 use core::fmt::{Debug, Formatter};
+use core::mem::size_of;
 
-pub(crate) const N_SHARED_COLS: usize = core::mem::size_of::<SharedCols<u8>>();
+use static_assertions::{const_assert, const_assert_eq};
 
-/// Columns intended to be shared, but currently only used by branching ops.
+pub(crate) const N_SHARED_COLS: usize = size_of::<SharedCols<u8>>();
+
+// every variant starts at the first shared column and fits in the union, and
+// the union is no wider than its widest variant
+const_assert!(size_of::<BranchCols<u8>>() <= N_SHARED_COLS);
+const_assert!(size_of::<JumpCols<u8>>() <= N_SHARED_COLS);
+const_assert!(size_of::<MemIoCols<u8>>() <= N_SHARED_COLS);
+const_assert!(size_of::<ShiftCols<u8>>() <= N_SHARED_COLS);
+const_assert_eq!(size_of::<SyscallCols<u8>>(), N_SHARED_COLS);
+
+/// The number of columns the variants of `SharedCols` would take up if each
+/// had its own columns.
+const N_UNSHARED_COLS: usize = size_of::<BranchCols<u8>>()
+    + size_of::<JumpCols<u8>>()
+    + size_of::<MemIoCols<u8>>()
+    + size_of::<ShiftCols<u8>>()
+    + size_of::<SyscallCols<u8>>();
+
+// sharing saves columns
+const_assert!(N_SHARED_COLS < N_UNSHARED_COLS);
+
+/// Columns shared by mutually exclusive operations.
+#[repr(C)]
 #[derive(Clone, Copy)]
 pub(crate) union SharedCols<T: Copy> {
     branch: BranchCols<T>,
+    jump: JumpCols<T>,
+    memio: MemIoCols<T>,
+    shift: ShiftCols<T>,
+    syscall: SyscallCols<T>,
 }
 
 impl<T: Copy> SharedCols<T> {
@@ -16,12 +43,85 @@ impl<T: Copy> SharedCols<T> {
     pub(crate) fn branch_mut(&mut self) -> &mut BranchCols<T> {
         unsafe { &mut self.branch }
     }
+    pub(crate) fn jump(&self) -> &JumpCols<T> {
+        unsafe { &self.jump }
+    }
+    pub(crate) fn jump_mut(&mut self) -> &mut JumpCols<T> {
+        unsafe { &mut self.jump }
+    }
+    pub(crate) fn memio(&self) -> &MemIoCols<T> {
+        unsafe { &self.memio }
+    }
+    pub(crate) fn memio_mut(&mut self) -> &mut MemIoCols<T> {
+        unsafe { &mut self.memio }
+    }
+    pub(crate) fn shift(&self) -> &ShiftCols<T> {
+        unsafe { &self.shift }
+    }
+    pub(crate) fn shift_mut(&mut self) -> &mut ShiftCols<T> {
+        unsafe { &mut self.shift }
+    }
+    pub(crate) fn syscall(&self) -> &SyscallCols<T> {
+        unsafe { &self.syscall }
+    }
+    pub(crate) fn syscall_mut(&mut self) -> &mut SyscallCols<T> {
+        unsafe { &mut self.syscall }
+    }
 }
 
+#[repr(C)]
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct BranchCols<T> {
     pub f_take_branch: T,
     pub diff_pinv: T,
+    /// Amount added to the PC: `imm` if the branch is taken, 4 otherwise.
+    pub pc_offset: T,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct JumpCols<T> {
+    /// The carry of the return address `rd = pc + 4`.
+    pub rd_carry: T,
+    /// The target `rs1 + imm` of JALR, before its lsb is cleared.
+    pub target: T,
+    /// The least significant bit of `target`, which JALR clears. It is
+    /// checked by the bits table.
+    pub target_lsb: T,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MemIoCols<T> {
+    /// The carry of the load/store address `rs1 + imm`.
+    pub adr_carry: T,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ShiftCols<T> {
+    /// The shift amount, i.e. the second operand masked to its low 5 bits.
+    pub shamt: T,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SyscallCols<T> {
+    /// 1 if the syscall number in `a7` is HALT.
+    pub f_halt: T,
+    /// 1 if the syscall number in `a7` is READ.
+    pub f_read: T,
+    /// 1 if the syscall number in `a7` is WRITE.
+    pub f_write: T,
+    /// The number of bytes transferred: the length in `a2` for READ and
+    /// WRITE, 0 for HALT.
+    pub n_bytes: T,
+    /// The inverse of `n_bytes`, if nonzero.
+    pub n_bytes_inv: T,
+    /// 1 if at least one byte is transferred.
+    pub f_xfer: T,
+    /// The position in its stream of the first transferred byte.
+    pub pos: T,
 }
 
 impl<T: Copy + Debug> Debug for SharedCols<T> {
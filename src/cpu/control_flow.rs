@@ -6,12 +6,14 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
-use crate::arith::eval_add_transition;
-use crate::cpu::columns::{CpuCols, CPU_COL_MAP};
+use crate::arith::{eval_add_transition, eval_add_transition_circuit};
+use crate::cpu::columns::{CpuCols, CpuPublicInputs, CPU_COL_MAP};
 
-const INC_PC_OPS: [usize; 10] = [
+const INC_PC_OPS: [usize; 16] = [
     CPU_COL_MAP.op.f_arith,
     CPU_COL_MAP.op.f_bits,
+    CPU_COL_MAP.op.f_shift,
+    CPU_COL_MAP.op.f_muldiv,
     CPU_COL_MAP.op.f_lw,
     CPU_COL_MAP.op.f_lh,
     CPU_COL_MAP.op.f_lb,
@@ -20,31 +22,101 @@ const INC_PC_OPS: [usize; 10] = [
     CPU_COL_MAP.op.f_sw,
     CPU_COL_MAP.op.f_sh,
     CPU_COL_MAP.op.f_sb,
+    CPU_COL_MAP.op.f_lui,
+    CPU_COL_MAP.op.f_auipc,
+    CPU_COL_MAP.op.f_ecall,
+    CPU_COL_MAP.op.f_ebreak,
 ];
 pub(crate) const INSTRUCTION_BYTES: usize = 4;
 
 pub(crate) fn eval<P: PackedField>(
     lv: &CpuCols<P>,
     nv: &CpuCols<P>,
+    pis: &CpuPublicInputs<P::Scalar>,
     cc: &mut ConstraintConsumer<P>,
 ) {
     let is_op: P = CPU_COL_MAP.op.iter().map(|&i| lv[i]).sum();
     let is_op_next: P = CPU_COL_MAP.op.iter().map(|&i| nv[i]).sum();
+    let halt = P::ONES - is_op;
     let halt_next = P::ONES - is_op_next;
 
-    cc.constraint_transition(is_op * (is_op_next + halt_next - P::ONES));
+    // Execution starts with an instruction. If `halted` is set, it stops
+    // exactly after a HALT syscall. Otherwise this is a segment which stops
+    // after any instruction, and HALT is never called. Once halted, it never
+    // resumes.
+    let f_halt = lv.op.f_ecall * lv.shared.syscall().f_halt;
+    cc.constraint_first_row(halt);
+    cc.constraint_transition(is_op * halt_next * pis.halted - f_halt);
+    cc.constraint_transition(halt * is_op_next);
+
+    // Halted rows don't access memory.
+    for ch in &lv.membus {
+        cc.constraint(halt * ch.f_on);
+    }
+
+    // The exit code is the argument of HALT, read from a0.
+    cc.constraint(f_halt * (lv.rs2_channel().val - pis.exit_code));
 
     let f_inc_pc: P = INC_PC_OPS.iter().map(|&i| lv[i]).sum();
     let ix_bytes: P = P::Scalar::from_canonical_usize(INSTRUCTION_BYTES).into();
-    eval_add_transition(cc, f_inc_pc, lv.pc, ix_bytes, nv.pc, lv.f_aux0);
-    // cc.constraint_transition(inc_pc * (nv.pc - lv.pc - P::Scalar::from_canonical_u8(4)));
+    eval_add_transition(cc, f_inc_pc, lv.pc, ix_bytes, nv.pc, lv.pc_carry);
+
+    // Execution starts at `pc_start`, and the trace ends in a halted row
+    // whose pc is `pc_end`. Halted rows keep the pc fixed.
+    cc.constraint_first_row(lv.pc - pis.pc_start);
+    cc.constraint_last_row(is_op);
+    cc.constraint_last_row(lv.pc - pis.pc_end);
+    cc.constraint_transition(halt * (nv.pc - lv.pc));
 }
 
 pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     cb: &mut CircuitBuilder<F, D>,
     lv: &CpuCols<ExtensionTarget<D>>,
     nv: &CpuCols<ExtensionTarget<D>>,
+    pis: &CpuPublicInputs<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let one = cb.one_extension();
+    let is_op = cb.add_many_extension(CPU_COL_MAP.op.iter().map(|&i| lv[i]));
+    let is_op_next = cb.add_many_extension(CPU_COL_MAP.op.iter().map(|&i| nv[i]));
+    let halt = cb.sub_extension(one, is_op);
+    let halt_next = cb.sub_extension(one, is_op_next);
+
+    // Execution starts with an instruction. If `halted` is set, it stops
+    // exactly after a HALT syscall. Otherwise this is a segment which stops
+    // after any instruction, and HALT is never called. Once halted, it never
+    // resumes.
+    let f_halt = cb.mul_extension(lv.op.f_ecall, lv.shared.syscall().f_halt);
+    cc.constraint_first_row(cb, halt);
+    let stop = cb.mul_extension(is_op, halt_next);
+    let cs = cb.mul_sub_extension(stop, pis.halted, f_halt);
+    cc.constraint_transition(cb, cs);
+    let cs = cb.mul_extension(halt, is_op_next);
+    cc.constraint_transition(cb, cs);
+
+    // Halted rows don't access memory.
+    for ch in &lv.membus {
+        let cs = cb.mul_extension(halt, ch.f_on);
+        cc.constraint(cb, cs);
+    }
+
+    // The exit code is the argument of HALT, read from a0.
+    let t = cb.sub_extension(lv.rs2_channel().val, pis.exit_code);
+    let cs = cb.mul_extension(f_halt, t);
+    cc.constraint(cb, cs);
+
+    let f_inc_pc = cb.add_many_extension(INC_PC_OPS.iter().map(|&i| lv[i]));
+    let ix_bytes = cb.constant_extension(F::Extension::from_canonical_usize(INSTRUCTION_BYTES));
+    eval_add_transition_circuit(cb, cc, f_inc_pc, lv.pc, ix_bytes, nv.pc, lv.pc_carry);
+
+    // Execution starts at `pc_start`, and the trace ends in a halted row
+    // whose pc is `pc_end`. Halted rows keep the pc fixed.
+    let cs = cb.sub_extension(lv.pc, pis.pc_start);
+    cc.constraint_first_row(cb, cs);
+    cc.constraint_last_row(cb, is_op);
+    let cs = cb.sub_extension(lv.pc, pis.pc_end);
+    cc.constraint_last_row(cb, cs);
+    let t = cb.sub_extension(nv.pc, lv.pc);
+    let cs = cb.mul_extension(halt, t);
+    cc.constraint_transition(cb, cs);
 }
@@ -13,8 +13,7 @@ pub(crate) fn eval<P: PackedField>(
     cc: &mut ConstraintConsumer<P>,
 ) {
     cc.constraint(lv.f_imm * (lv.f_imm - P::ONES));
-    cc.constraint(lv.f_aux0 * (lv.f_aux0 - P::ONES));
-    cc.constraint(lv.f_aux1 * (lv.f_aux1 - P::ONES));
+    cc.constraint(lv.pc_carry * (lv.pc_carry - P::ONES));
 
     for flag in *lv.op {
         cc.constraint(flag * (flag - P::ONES));
@@ -29,5 +28,16 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &CpuCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let cs = cb.mul_sub_extension(lv.f_imm, lv.f_imm, lv.f_imm);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_sub_extension(lv.pc_carry, lv.pc_carry, lv.pc_carry);
+    cc.constraint(cb, cs);
+
+    for flag in *lv.op {
+        let cs = cb.mul_sub_extension(flag, flag, flag);
+        cc.constraint(cb, cs);
+    }
+    let flag_sum = cb.add_many_extension(*lv.op);
+    let cs = cb.mul_sub_extension(flag_sum, flag_sum, flag_sum);
+    cc.constraint(cb, cs);
 }
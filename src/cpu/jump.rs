@@ -6,7 +6,7 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
-use crate::arith::{eval_add, eval_add_transition};
+use crate::arith::{eval_add, eval_add_circuit, eval_add_transition, eval_add_transition_circuit};
 use crate::cpu::columns::CpuCols;
 use crate::cpu::control_flow::INSTRUCTION_BYTES;
 
@@ -20,15 +20,19 @@ pub(crate) fn eval<P: PackedField>(
     let f_jump = f_jal + f_jalr;
 
     // jal sets PC = PC + imm
-    eval_add_transition(cc, f_jal, lv.pc, lv.imm, nv.pc, lv.f_aux0);
+    eval_add_transition(cc, f_jal, lv.pc, lv.imm, nv.pc, lv.pc_carry);
 
-    // jalr sets PC = rs1 + imm
+    // jalr sets PC = (rs1 + imm) & !1
     let ch_rs1 = lv.rs1_channel();
     cc.constraint(f_jalr * (P::ONES - ch_rs1.f_on));
     cc.constraint(f_jalr * ch_rs1.f_rw);
     cc.constraint(f_jalr * ch_rs1.adr_seg);
     cc.constraint(f_jalr * (lv.rs1 - ch_rs1.adr_virt));
-    eval_add_transition(cc, f_jalr, lv.pc, ch_rs1.val, nv.pc, lv.f_aux0);
+    // target_lsb = target & 1 is looked up in the bits table, which also
+    // range checks target
+    let jump = lv.shared.jump();
+    eval_add(cc, f_jalr, ch_rs1.val, lv.imm, jump.target, lv.pc_carry);
+    cc.constraint_transition(f_jalr * (nv.pc + jump.target_lsb - jump.target));
 
     // jal disables the rs1 memory channel
     cc.constraint(f_jal * ch_rs1.f_on);
@@ -40,11 +44,12 @@ pub(crate) fn eval<P: PackedField>(
     cc.constraint(f_jump * ch_rd.adr_seg);
     cc.constraint(f_jump * (lv.rd - ch_rd.adr_virt));
     let ix_bytes: P = P::Scalar::from_canonical_usize(INSTRUCTION_BYTES).into();
-    eval_add(cc, f_jump, lv.pc, ix_bytes, ch_rd.val, lv.f_aux1);
+    let rd_carry = lv.shared.jump().rd_carry;
+    eval_add(cc, f_jump, lv.pc, ix_bytes, ch_rd.val, rd_carry);
 
     // both jal and jalr disable the rs2 memory channel
     let ch_rs2 = lv.rs2_channel();
-    cc.constraint(f_jump * (P::ONES - ch_rs2.f_on));
+    cc.constraint(f_jump * ch_rs2.f_on);
 }
 
 pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
@@ -53,5 +58,56 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &CpuCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let one = cb.one_extension();
+    let f_jal = lv.op.f_jal;
+    let f_jalr = lv.op.f_jalr;
+    let f_jump = cb.add_extension(f_jal, f_jalr);
+
+    // jal sets PC = PC + imm
+    eval_add_transition_circuit(cb, cc, f_jal, lv.pc, lv.imm, nv.pc, lv.pc_carry);
+
+    // jalr sets PC = (rs1 + imm) & !1
+    let ch_rs1 = lv.rs1_channel();
+    let t = cb.sub_extension(one, ch_rs1.f_on);
+    let cs = cb.mul_extension(f_jalr, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_jalr, ch_rs1.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_jalr, ch_rs1.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs1, ch_rs1.adr_virt);
+    let cs = cb.mul_extension(f_jalr, t);
+    cc.constraint(cb, cs);
+    let jump = lv.shared.jump();
+    eval_add_circuit(cb, cc, f_jalr, ch_rs1.val, lv.imm, jump.target, lv.pc_carry);
+    let t = cb.add_extension(nv.pc, jump.target_lsb);
+    let t = cb.sub_extension(t, jump.target);
+    let cs = cb.mul_extension(f_jalr, t);
+    cc.constraint_transition(cb, cs);
+
+    // jal disables the rs1 memory channel
+    let cs = cb.mul_extension(f_jal, ch_rs1.f_on);
+    cc.constraint(cb, cs);
+
+    // both jal and jalr set rd = PC + 4
+    let ch_rd = lv.rd_channel();
+    let t = cb.sub_extension(one, ch_rd.f_on);
+    let cs = cb.mul_extension(f_jump, t);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, ch_rd.f_rw);
+    let cs = cb.mul_extension(f_jump, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_jump, ch_rd.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rd, ch_rd.adr_virt);
+    let cs = cb.mul_extension(f_jump, t);
+    cc.constraint(cb, cs);
+    let ix_bytes = cb.constant_extension(F::Extension::from_canonical_usize(INSTRUCTION_BYTES));
+    let rd_carry = lv.shared.jump().rd_carry;
+    eval_add_circuit(cb, cc, f_jump, lv.pc, ix_bytes, ch_rd.val, rd_carry);
+
+    // both jal and jalr disable the rs2 memory channel
+    let ch_rs2 = lv.rs2_channel();
+    let cs = cb.mul_extension(f_jump, ch_rs2.f_on);
+    cc.constraint(cb, cs);
 }
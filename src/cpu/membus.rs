@@ -26,5 +26,13 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &CpuCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    for chan in &lv.membus {
+        let cs = cb.mul_sub_extension(chan.f_on, chan.f_on, chan.f_on);
+        cc.constraint(cb, cs);
+        let cs = cb.mul_sub_extension(chan.f_rw, chan.f_rw, chan.f_rw);
+        cc.constraint(cb, cs);
+        // adr_seg must be either 0 (register) or 1 (main memory)
+        let cs = cb.mul_sub_extension(chan.adr_seg, chan.adr_seg, chan.adr_seg);
+        cc.constraint(cb, cs);
+    }
 }
@@ -5,7 +5,7 @@ use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
-use crate::arith::eval_add;
+use crate::arith::{eval_add, eval_add_circuit};
 use crate::cpu::columns::CpuCols;
 
 fn eval_load<P: PackedField>(lv: &CpuCols<P>, nv: &CpuCols<P>, cc: &mut ConstraintConsumer<P>) {
@@ -29,7 +29,8 @@ fn eval_load<P: PackedField>(lv: &CpuCols<P>, nv: &CpuCols<P>, cc: &mut Constrai
     // disable rs2 channel, but use the adr_virt column to store rs1 + imm
     let ch_rs2 = lv.rs2_channel();
     cc.constraint(f_load * ch_rs2.f_on);
-    eval_add(cc, f_load, ch_rs1.val, lv.imm, ch_rs2.adr_virt, lv.f_aux1);
+    let adr_carry = lv.shared.memio().adr_carry;
+    eval_add(cc, f_load, ch_rs1.val, lv.imm, ch_rs2.adr_virt, adr_carry);
 }
 
 fn eval_store<P: PackedField>(lv: &CpuCols<P>, nv: &CpuCols<P>, cc: &mut ConstraintConsumer<P>) {
@@ -53,7 +54,114 @@ fn eval_store<P: PackedField>(lv: &CpuCols<P>, nv: &CpuCols<P>, cc: &mut Constra
     // disable rd channel, but use the adr_virt column to store rs1 + imm
     let ch_rd = lv.rd_channel();
     cc.constraint(f_store * ch_rd.f_on);
-    eval_add(cc, f_store, ch_rs1.val, lv.imm, ch_rd.adr_virt, lv.f_aux1);
+    let adr_carry = lv.shared.memio().adr_carry;
+    eval_add(cc, f_store, ch_rs1.val, lv.imm, ch_rd.adr_virt, adr_carry);
+}
+
+fn eval_load_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &CpuCols<ExtensionTarget<D>>,
+    nv: &CpuCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    // rd = M[rs1+imm]
+    let one = cb.one_extension();
+    let f_load =
+        cb.add_many_extension([lv.op.f_lw, lv.op.f_lh, lv.op.f_lhu, lv.op.f_lb, lv.op.f_lbu]);
+
+    // read rs1
+    let ch_rs1 = lv.rs1_channel();
+    let t = cb.sub_extension(one, ch_rs1.f_on);
+    let cs = cb.mul_extension(f_load, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_load, ch_rs1.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_load, ch_rs1.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs1, ch_rs1.adr_virt);
+    let cs = cb.mul_extension(f_load, t);
+    cc.constraint(cb, cs);
+
+    // write loaded value to rd
+    let ch_rd = lv.rd_channel();
+    let t = cb.sub_extension(one, ch_rd.f_on);
+    let cs = cb.mul_extension(f_load, t);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, ch_rd.f_rw);
+    let cs = cb.mul_extension(f_load, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_load, ch_rd.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rd, ch_rd.adr_virt);
+    let cs = cb.mul_extension(f_load, t);
+    cc.constraint(cb, cs);
+
+    // disable rs2 channel, but use the adr_virt column to store rs1 + imm
+    let ch_rs2 = lv.rs2_channel();
+    let cs = cb.mul_extension(f_load, ch_rs2.f_on);
+    cc.constraint(cb, cs);
+    let adr_carry = lv.shared.memio().adr_carry;
+    eval_add_circuit(
+        cb,
+        cc,
+        f_load,
+        ch_rs1.val,
+        lv.imm,
+        ch_rs2.adr_virt,
+        adr_carry,
+    );
+}
+
+fn eval_store_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &CpuCols<ExtensionTarget<D>>,
+    nv: &CpuCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    // M[rs1+imm] = rs2
+    let one = cb.one_extension();
+    let f_store = cb.add_many_extension([lv.op.f_sw, lv.op.f_sh, lv.op.f_sb]);
+
+    // read rs1
+    let ch_rs1 = lv.rs1_channel();
+    let t = cb.sub_extension(one, ch_rs1.f_on);
+    let cs = cb.mul_extension(f_store, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_store, ch_rs1.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_store, ch_rs1.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs1, ch_rs1.adr_virt);
+    let cs = cb.mul_extension(f_store, t);
+    cc.constraint(cb, cs);
+
+    // read rs2
+    let ch_rs2 = lv.rs2_channel();
+    let t = cb.sub_extension(one, ch_rs2.f_on);
+    let cs = cb.mul_extension(f_store, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_store, ch_rs2.f_rw);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_store, ch_rs2.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rs2, ch_rs2.adr_virt);
+    let cs = cb.mul_extension(f_store, t);
+    cc.constraint(cb, cs);
+
+    // disable rd channel, but use the adr_virt column to store rs1 + imm
+    let ch_rd = lv.rd_channel();
+    let cs = cb.mul_extension(f_store, ch_rd.f_on);
+    cc.constraint(cb, cs);
+    let adr_carry = lv.shared.memio().adr_carry;
+    eval_add_circuit(
+        cb,
+        cc,
+        f_store,
+        ch_rs1.val,
+        lv.imm,
+        ch_rd.adr_virt,
+        adr_carry,
+    );
 }
 
 pub(crate) fn eval<P: PackedField>(
@@ -71,5 +179,6 @@ pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     nv: &CpuCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    eval_load_circuit(cb, lv, nv, cc);
+    eval_store_circuit(cb, lv, nv, cc);
 }
@@ -8,4 +8,8 @@ pub mod jump;
 pub mod membus;
 pub mod memio;
 pub mod reg;
+pub mod shift;
 pub mod stark;
+pub mod syscall;
+pub mod trace;
+pub mod upper;
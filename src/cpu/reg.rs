@@ -4,23 +4,45 @@ use plonky2::hash::hash_types::RichField;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use static_assertions::const_assert_eq;
 
-use crate::cpu::columns::CpuCols;
+use crate::cpu::columns::{CpuCols, N_REG_BITS};
+use crate::util::{felt_from_le_bits, felt_from_le_bits_circuit};
+use crate::vm::machine::N_REGS;
 
-//TODO: constrain rs1, rs2, rd in 0..32
+const_assert_eq!(1 << N_REG_BITS, N_REGS);
+
+/// Range checks `rs1`, `rs2` and `rd` to 0..32 by decomposing them into bits.
+/// Otherwise, any field element would be accepted as a register index, and
+/// the memory table would treat it as a separate register.
 pub(crate) fn eval<P: PackedField>(
     lv: &CpuCols<P>,
-    nv: &CpuCols<P>,
+    _nv: &CpuCols<P>,
     cc: &mut ConstraintConsumer<P>,
 ) {
-    //TODO
+    let bits = &lv.reg_bits;
+    for (reg, bits) in [(lv.rs1, bits.rs1), (lv.rs2, bits.rs2), (lv.rd, bits.rd)] {
+        for bit in bits {
+            cc.constraint(bit * (bit - P::ONES));
+        }
+        cc.constraint(reg - felt_from_le_bits(bits));
+    }
 }
 
 pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
     cb: &mut CircuitBuilder<F, D>,
     lv: &CpuCols<ExtensionTarget<D>>,
-    nv: &CpuCols<ExtensionTarget<D>>,
+    _nv: &CpuCols<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    //TODO
+    let bits = &lv.reg_bits;
+    for (reg, bits) in [(lv.rs1, bits.rs1), (lv.rs2, bits.rs2), (lv.rd, bits.rd)] {
+        for bit in bits {
+            let cs = cb.mul_sub_extension(bit, bit, bit);
+            cc.constraint(cb, cs);
+        }
+        let t = felt_from_le_bits_circuit(cb, bits);
+        let cs = cb.sub_extension(reg, t);
+        cc.constraint(cb, cs);
+    }
 }
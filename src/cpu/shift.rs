@@ -0,0 +1,35 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+
+use crate::cpu::columns::CpuCols;
+
+/// Shifts only use the low 5 bits of their second operand.
+pub(crate) const SHAMT_MASK: u32 = 0x1f;
+
+pub(crate) fn eval<P: PackedField>(
+    lv: &CpuCols<P>,
+    _nv: &CpuCols<P>,
+    cc: &mut ConstraintConsumer<P>,
+) {
+    // The shift amount of SLLI, SRLI and SRAI is the decoded `imm`, which
+    // always fits in 5 bits. For SLL, SRL and SRA, `rs2 & 0x1f` is checked by
+    // the bits table.
+    let shamt = lv.shared.shift().shamt;
+    cc.constraint(lv.op.f_shift * lv.f_imm * (shamt - lv.imm));
+}
+
+pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &CpuCols<ExtensionTarget<D>>,
+    _nv: &CpuCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let shamt = lv.shared.shift().shamt;
+    let t = cb.sub_extension(shamt, lv.imm);
+    let cs = cb.mul_many_extension([lv.op.f_shift, lv.f_imm, t]);
+    cc.constraint(cb, cs);
+}
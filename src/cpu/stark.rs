@@ -14,16 +14,23 @@ use starky::lookup::{Column, Filter};
 use starky::stark::Stark;
 
 use crate::bytes::{BYTES_HALF, BYTES_WORD};
-use crate::cpu::columns::{CpuCols, CPU_COL_MAP, N_CPU_COLS, N_MEM_CHANNELS};
-use crate::cpu::{arith, branch, clock, control_flow, flags, jump, membus, memio, reg};
+use crate::cpu::columns::{
+    CpuCols, CpuPublicInputs, CPU_COL_MAP, N_CPU_COLS, N_CPU_PUBLIC_INPUTS, N_MEM_CHANNELS,
+};
+use crate::cpu::shift::SHAMT_MASK;
+use crate::cpu::upper::UPPER_IMM_SHIFT;
+use crate::cpu::{
+    arith, branch, clock, control_flow, flags, jump, membus, memio, reg, shift, syscall, upper,
+};
 use crate::stark::Table;
 use crate::util::fst;
 use crate::vm::opcode::Opcode;
 
-/// Create the timestamp column for memory lookups.
+/// Create the timestamp column for memory lookups. Clock 0 starts after the
+/// initial memory image, which is written at time 0.
 fn mem_timestamp<F: Field>(channel: usize) -> Column<F> {
     let n = F::from_canonical_usize(N_MEM_CHANNELS);
-    let ch = F::from_canonical_usize(channel);
+    let ch = F::from_canonical_usize(N_MEM_CHANNELS + channel);
     Column::linear_combination_with_constant([(CPU_COL_MAP.clock, n)], ch)
 }
 
@@ -105,6 +112,100 @@ pub(crate) fn ctl_looking_bits_imm<F: Field>() -> TableWithColumns<F> {
     TableWithColumns::new(Table::Cpu as usize, cols, filter)
 }
 
+/// Looking table cpu->bits, computing `rd = rs1 << shamt` or `rd = rs1 >> shamt`.
+pub(crate) fn ctl_looking_shift<F: Field>() -> TableWithColumns<F> {
+    let cols = Column::singles([
+        CPU_COL_MAP.opcode,
+        CPU_COL_MAP.rs1_channel().val,
+        CPU_COL_MAP.shared.shift().shamt,
+        CPU_COL_MAP.rd_channel().val,
+    ])
+    .collect();
+
+    let filter = Filter::new_simple(Column::single(CPU_COL_MAP.op.f_shift));
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
+/// Looking table cpu->bits, computing `shamt = rs2 & 0x1f` for shifts by a
+/// register.
+pub(crate) fn ctl_looking_shift_mask<F: Field>() -> TableWithColumns<F> {
+    let cols = vec![
+        Column::constant(F::from_canonical_u8(Opcode::AND as u8)),
+        Column::single(CPU_COL_MAP.rs2_channel().val),
+        Column::constant(F::from_canonical_u32(SHAMT_MASK)),
+        Column::single(CPU_COL_MAP.shared.shift().shamt),
+    ];
+
+    let f_not_imm =
+        Column::linear_combination_with_constant(vec![(CPU_COL_MAP.f_imm, F::NEG_ONE)], F::ONE);
+    let f_shift = Column::single(CPU_COL_MAP.op.f_shift);
+    let filter = Filter::new(vec![(f_not_imm, f_shift)], vec![]);
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
+/// Looking table cpu->bits, computing `target_lsb = target & 1` for JALR.
+pub(crate) fn ctl_looking_jalr_lsb<F: Field>() -> TableWithColumns<F> {
+    let jump = CPU_COL_MAP.shared.jump();
+    let cols = vec![
+        Column::constant(F::from_canonical_u8(Opcode::AND as u8)),
+        Column::single(jump.target),
+        Column::constant(F::ONE),
+        Column::single(jump.target_lsb),
+    ];
+
+    let filter = Filter::new_simple(Column::single(CPU_COL_MAP.op.f_jalr));
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
+/// Looking table cpu->bits, checking `adr_virt & (len - 1) == 0` for the
+/// accesses of `len` bytes at `adr_virt` made by `ops`.
+fn ctl_looking_align<F: Field, const N: usize>(
+    adr_virt: usize,
+    ops: [(usize, usize); N],
+) -> TableWithColumns<F> {
+    let mask = ops.map(|(f, len)| (f, F::from_canonical_usize(len - 1)));
+    let cols = vec![
+        Column::constant(F::from_canonical_u8(Opcode::AND as u8)),
+        Column::single(adr_virt),
+        Column::linear_combination(mask),
+        Column::constant(F::ZERO),
+    ];
+
+    let filter = Filter::new_simple(Column::sum(ops.map(fst)));
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
+/// Looking table cpu->bits, checking the alignment of halfword and word loads
+/// under [`Alignment::Strict`](crate::bytes::Alignment::Strict).
+pub(crate) fn ctl_looking_load_align<F: Field>() -> TableWithColumns<F> {
+    let ops = [
+        (CPU_COL_MAP.op.f_lh, BYTES_HALF),
+        (CPU_COL_MAP.op.f_lhu, BYTES_HALF),
+        (CPU_COL_MAP.op.f_lw, BYTES_WORD),
+    ];
+    // rs1 + imm is stored in rs2_channel.adr_virt
+    ctl_looking_align(CPU_COL_MAP.rs2_channel().adr_virt, ops)
+}
+
+/// Looking table cpu->bits, checking the alignment of halfword and word
+/// stores under [`Alignment::Strict`](crate::bytes::Alignment::Strict).
+pub(crate) fn ctl_looking_store_align<F: Field>() -> TableWithColumns<F> {
+    let ops = [
+        (CPU_COL_MAP.op.f_sh, BYTES_HALF),
+        (CPU_COL_MAP.op.f_sw, BYTES_WORD),
+    ];
+    // rs1 + imm is stored in rd_channel.adr_virt
+    ctl_looking_align(CPU_COL_MAP.rd_channel().adr_virt, ops)
+}
+
+/// Looking table cpu->muldiv.
+pub(crate) fn ctl_looking_muldiv<F: Field>() -> TableWithColumns<F> {
+    let cols = ctl_binop_reg();
+
+    let filter = Filter::new_simple(Column::single(CPU_COL_MAP.op.f_muldiv));
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
 /// Looking table cpu->bytes
 pub(crate) fn ctl_looking_read_bytes<F: Field>() -> TableWithColumns<F> {
     let load_ops = [
@@ -122,8 +223,7 @@ pub(crate) fn ctl_looking_read_bytes<F: Field>() -> TableWithColumns<F> {
     // rs1 + imm is stored in rs2_channel.adr_virt
     let adr_virt = Column::single(CPU_COL_MAP.rs2_channel().adr_virt);
     let len = Column::linear_combination(load_ops);
-    let n_channels = F::from_canonical_usize(N_MEM_CHANNELS);
-    let time = Column::linear_combination([(CPU_COL_MAP.clock, n_channels)]);
+    let time = mem_timestamp(0);
     let val = Column::single(CPU_COL_MAP.rd_channel().val);
 
     let cols = vec![f_rw, f_signed, adr_virt, len, val, time];
@@ -145,8 +245,7 @@ pub(crate) fn ctl_looking_write_bytes<F: Field>() -> TableWithColumns<F> {
     // rs1 + imm is stored in rd_channel.adr_virt
     let adr_virt = Column::single(CPU_COL_MAP.rd_channel().adr_virt);
     let len = Column::linear_combination(store_ops);
-    let n_channels = F::from_canonical_usize(N_MEM_CHANNELS);
-    let time = Column::linear_combination([(CPU_COL_MAP.clock, n_channels)]);
+    let time = mem_timestamp(0);
     let val = Column::single(CPU_COL_MAP.rs2_channel().val);
 
     let cols = vec![f_rw, f_signed, adr_virt, len, val, time];
@@ -174,17 +273,69 @@ pub(crate) fn ctl_looking_branch<F: Field>() -> TableWithColumns<F> {
     TableWithColumns::new(Table::Cpu as usize, cols, filter)
 }
 
+/// Looking table cpu->arith, computing `rd = pc + (imm << 12)` for AUIPC.
+pub(crate) fn ctl_looking_auipc<F: Field>() -> TableWithColumns<F> {
+    let opcode = Column::constant(F::from_canonical_u8(Opcode::ADD as u8));
+    let pc = Column::single(CPU_COL_MAP.pc);
+    let shift = F::from_canonical_u32(1 << UPPER_IMM_SHIFT);
+    let imm = Column::linear_combination([(CPU_COL_MAP.imm, shift)]);
+    let rd_val = Column::single(CPU_COL_MAP.rd_channel().val);
+
+    let cols = vec![opcode, pc, imm, rd_val];
+    let filter = Filter::new_simple(Column::single(CPU_COL_MAP.op.f_auipc));
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
+/// Looking table cpu->program, fetching the executed instruction.
+pub(crate) fn ctl_looking_program<F: Field>() -> TableWithColumns<F> {
+    let cols = Column::singles([
+        CPU_COL_MAP.pc,
+        CPU_COL_MAP.opcode,
+        CPU_COL_MAP.rs1,
+        CPU_COL_MAP.rs2,
+        CPU_COL_MAP.rd,
+        CPU_COL_MAP.imm,
+        CPU_COL_MAP.f_imm,
+    ])
+    .chain(Column::singles(CPU_COL_MAP.op.iter()))
+    .collect();
+
+    // the op flags are looked up along with the instruction, so they can't
+    // disagree with its opcode
+    let filter = Filter::new_simple(Column::sum(CPU_COL_MAP.op.iter()));
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
+/// Looking table cpu->io, transferring the bytes of a READ or WRITE syscall.
+pub(crate) fn ctl_looking_io<F: Field>() -> TableWithColumns<F> {
+    let sys = CPU_COL_MAP.shared.syscall();
+    let cols = Column::singles([sys.f_write, CPU_COL_MAP.clock, sys.n_bytes, sys.pos]).collect();
+
+    let f_ecall = Column::single(CPU_COL_MAP.op.f_ecall);
+    let f_xfer = Column::single(sys.f_xfer);
+    let filter = Filter::new(vec![(f_ecall, f_xfer)], vec![]);
+    TableWithColumns::new(Table::Cpu as usize, cols, filter)
+}
+
 /// Evaluate all CPU constraints.
-fn eval_all<P: PackedField>(lv: &CpuCols<P>, nv: &CpuCols<P>, cc: &mut ConstraintConsumer<P>) {
-    clock::eval(lv, nv, cc);
-    control_flow::eval(lv, nv, cc);
+fn eval_all<P: PackedField>(
+    lv: &CpuCols<P>,
+    nv: &CpuCols<P>,
+    pis: &CpuPublicInputs<P::Scalar>,
+    cc: &mut ConstraintConsumer<P>,
+) {
+    clock::eval(lv, nv, pis, cc);
+    control_flow::eval(lv, nv, pis, cc);
     membus::eval(lv, nv, cc);
     memio::eval(lv, nv, cc);
     jump::eval(lv, nv, cc);
     branch::eval(lv, nv, cc);
     flags::eval(lv, nv, cc);
     arith::eval(lv, nv, cc);
+    upper::eval(lv, nv, cc);
     reg::eval(lv, nv, cc);
+    shift::eval(lv, nv, cc);
+    syscall::eval(lv, nv, pis, cc);
 }
 
 /// Evaluate all CPU constraints.
@@ -192,17 +343,21 @@ fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
     cb: &mut CircuitBuilder<F, D>,
     lv: &CpuCols<ExtensionTarget<D>>,
     nv: &CpuCols<ExtensionTarget<D>>,
+    pis: &CpuPublicInputs<ExtensionTarget<D>>,
     cc: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    clock::eval_circuit(cb, lv, nv, cc);
-    control_flow::eval_circuit(cb, lv, nv, cc);
+    clock::eval_circuit(cb, lv, nv, pis, cc);
+    control_flow::eval_circuit(cb, lv, nv, pis, cc);
     membus::eval_circuit(cb, lv, nv, cc);
     memio::eval_circuit(cb, lv, nv, cc);
     jump::eval_circuit(cb, lv, nv, cc);
     branch::eval_circuit(cb, lv, nv, cc);
     flags::eval_circuit(cb, lv, nv, cc);
     arith::eval_circuit(cb, lv, nv, cc);
+    upper::eval_circuit(cb, lv, nv, cc);
     reg::eval_circuit(cb, lv, nv, cc);
+    shift::eval_circuit(cb, lv, nv, cc);
+    syscall::eval_circuit(cb, lv, nv, pis, cc);
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -211,12 +366,14 @@ pub struct CpuStark<F, const D: usize> {
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuStark<F, D> {
-    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, N_CPU_COLS, 0>
+    type EvaluationFrame<FE, P, const D2: usize>
+        = StarkFrame<P, P::Scalar, N_CPU_COLS, N_CPU_PUBLIC_INPUTS>
     where
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>;
 
-    type EvaluationFrameTarget = StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, N_CPU_COLS, 0>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, N_CPU_COLS, N_CPU_PUBLIC_INPUTS>;
 
     fn eval_packed_generic<FE, P, const D2: usize>(
         &self,
@@ -230,7 +387,9 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuStark<F, D
         let local: &CpuCols<P> = local.borrow();
         let next: &[P; N_CPU_COLS] = frame.get_next_values().try_into().unwrap();
         let next: &CpuCols<P> = next.borrow();
-        eval_all(local, next, cc)
+        let pis: &[P::Scalar; N_CPU_PUBLIC_INPUTS] = frame.get_public_inputs().try_into().unwrap();
+        let pis: &CpuPublicInputs<P::Scalar> = pis.borrow();
+        eval_all(local, next, pis, cc)
     }
 
     fn eval_ext_circuit(
@@ -243,7 +402,10 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuStark<F, D
         let local: &CpuCols<ExtensionTarget<D>> = local.borrow();
         let next: &[ExtensionTarget<D>; N_CPU_COLS] = frame.get_next_values().try_into().unwrap();
         let next: &CpuCols<ExtensionTarget<D>> = next.borrow();
-        eval_all_circuit(cb, local, next, cc);
+        let pis: &[ExtensionTarget<D>; N_CPU_PUBLIC_INPUTS] =
+            frame.get_public_inputs().try_into().unwrap();
+        let pis: &CpuPublicInputs<ExtensionTarget<D>> = pis.borrow();
+        eval_all_circuit(cb, local, next, pis, cc);
     }
 
     fn constraint_degree(&self) -> usize {
@@ -257,10 +419,16 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuStark<F, D
 
 #[cfg(test)]
 mod tests {
+    use plonky2::field::types::Field;
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use starky::constraint_consumer::ConstraintConsumer;
     use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
 
-    use super::CpuStark;
+    use super::{eval_all, CpuStark};
+    use crate::cpu::columns::{CpuCols, CpuPublicInputs, RD_CHANNEL, RS1_CHANNEL, RS2_CHANNEL};
+    use crate::cpu::trace::gen_trace_rows;
+    use crate::vm::asm::assemble;
+    use crate::vm::Machine;
 
     const D: usize = 2;
     type C = PoseidonGoldilocksConfig;
@@ -273,9 +441,266 @@ mod tests {
         test_stark_low_degree(stark).unwrap();
     }
 
-    // #[test]
-    // fn test_stark_circuit() {
-    //     let stark: S = Default::default();
-    //     test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
-    // }
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
+
+    /// Returns true if every CPU constraint holds on `rows`.
+    fn constraints_hold(rows: &[CpuCols<F>], pis: &CpuPublicInputs<F>) -> bool {
+        let n = rows.len();
+        (0..n).all(|i| {
+            let is_first = F::from_bool(i == 0);
+            let is_last = F::from_bool(i == n - 1);
+            let alpha = F::from_canonical_u64(0x0123_4567_89ab_cdef);
+            let mut cc = ConstraintConsumer::new(vec![alpha], F::ONE - is_last, is_first, is_last);
+            eval_all(&rows[i], &rows[(i + 1) % n], pis, &mut cc);
+            cc.accumulators().into_iter().all(|acc| acc == F::ZERO)
+        })
+    }
+
+    #[test]
+    fn test_halt() {
+        let program = assemble(
+            "
+                li a0, 5
+                addi a0, a0, 1
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let pis = log.public_values::<F>().cpu_inputs();
+        let n_steps = log.cpu.len();
+        let rows = gen_trace_rows::<F>(log.cpu, 8);
+        assert!(constraints_hold(&rows, &pis));
+
+        // the exit code is a0 at the time of HALT
+        let mut bad_pis = pis.clone();
+        bad_pis.exit_code = F::from_canonical_u32(5);
+        assert!(!constraints_hold(&rows, &bad_pis));
+
+        // execution can't stop before HALT, skipping the remaining work
+        let mut bad_rows = rows.clone();
+        bad_rows[1].op.iter_mut().for_each(|f| *f = F::ZERO);
+        assert!(!constraints_hold(&bad_rows, &pis));
+
+        // nor resume after it
+        let mut bad_rows = rows.clone();
+        bad_rows[n_steps + 1].op.f_ebreak = F::ONE;
+        assert!(!constraints_hold(&bad_rows, &pis));
+
+        // halted rows don't access memory
+        let mut bad_rows = rows;
+        bad_rows[n_steps].membus[RS1_CHANNEL].f_on = F::ONE;
+        assert!(!constraints_hold(&bad_rows, &pis));
+    }
+
+    #[test]
+    fn test_segment_halt() {
+        let program = assemble(
+            "
+                li a0, 5
+                addi a0, a0, 1
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let logs = Machine::from_words(0, &program)
+            .run_segments(2, 100)
+            .unwrap();
+        assert_eq!(logs.len(), 2);
+
+        // the first segment stops after its last instruction without HALT
+        let [first, last] = <[_; 2]>::try_from(logs).unwrap();
+        let pis = first.public_values::<F>().cpu_inputs();
+        assert_eq!(pis.halted, F::ZERO);
+        let rows = gen_trace_rows::<F>(first.cpu, 4);
+        assert!(constraints_hold(&rows, &pis));
+        let mut bad_pis = pis;
+        bad_pis.halted = F::ONE;
+        assert!(!constraints_hold(&rows, &bad_pis));
+
+        // the last segment must not hide its HALT
+        let pis = last.public_values::<F>().cpu_inputs();
+        assert_eq!(pis.halted, F::ONE);
+        let rows = gen_trace_rows::<F>(last.cpu, 4);
+        assert!(constraints_hold(&rows, &pis));
+        let mut bad_pis = pis;
+        bad_pis.halted = F::ZERO;
+        assert!(!constraints_hold(&rows, &bad_pis));
+    }
+
+    #[test]
+    fn test_reg_range() {
+        let program = assemble(
+            "
+                li a0, 5
+                addi a0, a0, 1
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let pis = log.public_values::<F>().cpu_inputs();
+        let rows = gen_trace_rows::<F>(log.cpu, 8);
+        assert!(constraints_hold(&rows, &pis));
+
+        // rs1 = 32 is out of range, even if it matches the accessed register
+        let mut bad_rows = rows.clone();
+        bad_rows[1].rs1 = F::from_canonical_u8(32);
+        bad_rows[1].membus[RS1_CHANNEL].adr_virt = F::from_canonical_u8(32);
+        assert!(!constraints_hold(&bad_rows, &pis));
+
+        // bits must be binary, so they can't sum to 32 either
+        bad_rows[1].reg_bits.rs1 = [0, 0, 0, 0, 2].map(F::from_canonical_u8);
+        assert!(!constraints_hold(&bad_rows, &pis));
+
+        // likewise for rs2 and rd
+        let mut bad_rows = rows.clone();
+        bad_rows[1].rs2 = F::NEG_ONE;
+        assert!(!constraints_hold(&bad_rows, &pis));
+        let mut bad_rows = rows;
+        bad_rows[1].rd = F::from_canonical_u8(42);
+        bad_rows[1].membus[RD_CHANNEL].adr_virt = F::from_canonical_u8(42);
+        assert!(!constraints_hold(&bad_rows, &pis));
+    }
+
+    #[test]
+    fn test_shift_bits_channels() {
+        let program = assemble(
+            "
+                li t0, 6
+                slli t1, t0, 2
+                and t2, t0, t1
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let pis = log.public_values::<F>().cpu_inputs();
+        let rows = gen_trace_rows::<F>(log.cpu, 8);
+        assert!(constraints_hold(&rows, &pis));
+
+        // shifts and bitwise operations read their operands and write rd
+        // through the register channels, like arithmetic
+        for i in [1, 2] {
+            let mut bad_rows = rows.clone();
+            bad_rows[i].membus[RD_CHANNEL].f_on = F::ZERO;
+            assert!(!constraints_hold(&bad_rows, &pis));
+            let mut bad_rows = rows.clone();
+            bad_rows[i].membus[RS1_CHANNEL].adr_virt += F::ONE;
+            assert!(!constraints_hold(&bad_rows, &pis));
+        }
+
+        // with an immediate, rs2 isn't read
+        let mut bad_rows = rows.clone();
+        bad_rows[1].membus[RS2_CHANNEL].f_on = F::ONE;
+        assert!(!constraints_hold(&bad_rows, &pis));
+
+        // without one, it is
+        let mut bad_rows = rows;
+        bad_rows[2].membus[RS2_CHANNEL].f_on = F::ZERO;
+        assert!(!constraints_hold(&bad_rows, &pis));
+    }
+
+    #[test]
+    fn test_branch() {
+        let program = assemble(
+            "
+                li t0, 1
+                beq t0, x0, skip
+                bne t0, x0, skip
+                li a0, 1
+            skip:
+                jal ra, end
+                li a0, 2
+            end:
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let pis = log.public_values::<F>().cpu_inputs();
+        let rows = gen_trace_rows::<F>(log.cpu, 8);
+        assert!(constraints_hold(&rows, &pis));
+
+        // the untaken beq moves on to the next instruction, the taken bne
+        // jumps over `li a0, 1`
+        assert_eq!(rows[2].pc, F::from_canonical_u8(8));
+        assert_eq!(rows[3].pc, F::from_canonical_u8(16));
+
+        // a branch can't be taken against its condition, nor its offset
+        // swapped for another
+        let mut bad_rows = rows.clone();
+        bad_rows[1].shared.branch_mut().f_take_branch = F::ONE;
+        assert!(!constraints_hold(&bad_rows, &pis));
+        let mut bad_rows = rows;
+        bad_rows[2].shared.branch_mut().pc_offset = F::from_canonical_u8(4);
+        assert!(!constraints_hold(&bad_rows, &pis));
+    }
+
+    #[test]
+    fn test_jump_rs2() {
+        let program = assemble(
+            "
+                jal ra, next
+            next:
+                li t0, 12
+                jalr ra, 0(t0)
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let pis = log.public_values::<F>().cpu_inputs();
+        let rows = gen_trace_rows::<F>(log.cpu, 8);
+        assert!(constraints_hold(&rows, &pis));
+
+        // neither jal nor jalr reads rs2
+        for i in [0, 2] {
+            let mut bad_rows = rows.clone();
+            bad_rows[i].membus[RS2_CHANNEL].f_on = F::ONE;
+            assert!(!constraints_hold(&bad_rows, &pis));
+        }
+    }
+
+    #[test]
+    fn test_jalr() {
+        let program = assemble(
+            "
+                li t0, 7
+                jalr ra, 2(t0)
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let pis = log.public_values::<F>().cpu_inputs();
+        let rows = gen_trace_rows::<F>(log.cpu, 8);
+        assert!(constraints_hold(&rows, &pis));
+
+        // the target is 7 + 2 with its lsb cleared, not pc + rs1
+        assert_eq!(rows[2].pc, F::from_canonical_u8(8));
+
+        // the odd bit must be accounted for by the aux bit
+        let mut bad_rows = rows.clone();
+        bad_rows[1].shared.jump_mut().target_lsb = F::ZERO;
+        assert!(!constraints_hold(&bad_rows, &pis));
+
+        // and the target must be rs1 + imm
+        let mut bad_rows = rows;
+        let jump = bad_rows[1].shared.jump_mut();
+        jump.target = F::from_canonical_u8(8);
+        jump.target_lsb = F::ZERO;
+        assert!(!constraints_hold(&bad_rows, &pis));
+    }
 }
@@ -0,0 +1,218 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+
+use crate::cpu::columns::{CpuCols, CpuPublicInputs};
+
+/// Register `a0`, holding the exit code of HALT and the file descriptor of
+/// READ and WRITE.
+pub(crate) const REG_A0: u8 = 10;
+/// Register `a1`, holding the buffer address of READ and WRITE.
+pub(crate) const REG_A1: u8 = 11;
+/// Register `a2`, holding the buffer length of READ and WRITE.
+pub(crate) const REG_A2: u8 = 12;
+/// Register `a7`, holding the syscall number.
+pub(crate) const REG_A7: u8 = 17;
+
+/// The file descriptor of the input stream.
+pub(crate) const FD_STDIN: u32 = 0;
+/// The file descriptor of the output stream.
+pub(crate) const FD_STDOUT: u32 = 1;
+
+/// The syscalls made through ECALL, numbered as in the Linux RISC-V ABI.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Syscall {
+    /// `READ(fd, buf, len)` copies the next `len` bytes of the input stream
+    /// to `buf`.
+    Read = 63,
+    /// `WRITE(fd, buf, len)` appends the `len` bytes at `buf` to the output
+    /// stream.
+    Write = 64,
+    /// `HALT(exit_code)` stops execution.
+    Halt = 93,
+}
+
+impl Syscall {
+    /// Returns the syscall with number `n`, if any.
+    pub(crate) fn from_u32(n: u32) -> Option<Self> {
+        match n {
+            63 => Some(Self::Read),
+            64 => Some(Self::Write),
+            93 => Some(Self::Halt),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn eval<P: PackedField>(
+    lv: &CpuCols<P>,
+    nv: &CpuCols<P>,
+    pis: &CpuPublicInputs<P::Scalar>,
+    cc: &mut ConstraintConsumer<P>,
+) {
+    let f_ecall = lv.op.f_ecall;
+    let sys = lv.shared.syscall();
+
+    // ecall reads a7, a0 and a2. It doesn't write any register, so the rd
+    // channel is used for the third read. The buffer address in a1 is read
+    // by the io table.
+    let args = [
+        (lv.rs1_channel(), REG_A7),
+        (lv.rs2_channel(), REG_A0),
+        (lv.rd_channel(), REG_A2),
+    ];
+    for (ch, reg) in args {
+        cc.constraint(f_ecall * (P::ONES - ch.f_on));
+        cc.constraint(f_ecall * ch.f_rw);
+        cc.constraint(f_ecall * ch.adr_seg);
+        cc.constraint(f_ecall * (ch.adr_virt - P::Scalar::from_canonical_u8(reg)));
+    }
+
+    // a7 selects exactly one syscall
+    for f in [sys.f_halt, sys.f_read, sys.f_write] {
+        cc.constraint(f_ecall * f * (f - P::ONES));
+    }
+    cc.constraint(f_ecall * (sys.f_halt + sys.f_read + sys.f_write - P::ONES));
+    let syscall = sys.f_halt * P::Scalar::from_canonical_u32(Syscall::Halt as u32)
+        + sys.f_read * P::Scalar::from_canonical_u32(Syscall::Read as u32)
+        + sys.f_write * P::Scalar::from_canonical_u32(Syscall::Write as u32);
+    cc.constraint(f_ecall * (lv.rs1_channel().val - syscall));
+
+    // reads are from the input stream, and writes to the output stream
+    let fd = lv.rs2_channel().val;
+    cc.constraint(f_ecall * sys.f_read * (fd - P::Scalar::from_canonical_u32(FD_STDIN)));
+    cc.constraint(f_ecall * sys.f_write * (fd - P::Scalar::from_canonical_u32(FD_STDOUT)));
+
+    // f_xfer is set if any bytes are transferred, in which case the io table
+    // is looked up
+    let len = lv.rd_channel().val;
+    cc.constraint(f_ecall * (sys.n_bytes - (sys.f_read + sys.f_write) * len));
+    cc.constraint(f_ecall * (sys.f_xfer - sys.n_bytes * sys.n_bytes_inv));
+    cc.constraint(f_ecall * sys.n_bytes * (P::ONES - sys.f_xfer));
+
+    // each stream is transferred in order, between the io offsets of the
+    // public inputs. The last row is halted, so it transfers nothing.
+    cc.constraint(f_ecall * sys.f_read * (sys.pos - lv.input_pos));
+    cc.constraint(f_ecall * sys.f_write * (sys.pos - lv.output_pos));
+    cc.constraint_first_row(lv.input_pos - pis.input_start);
+    cc.constraint_first_row(lv.output_pos - pis.output_start);
+    let n_read = f_ecall * sys.f_read * sys.n_bytes;
+    let n_written = f_ecall * sys.f_write * sys.n_bytes;
+    cc.constraint_transition(nv.input_pos - lv.input_pos - n_read);
+    cc.constraint_transition(nv.output_pos - lv.output_pos - n_written);
+    cc.constraint_last_row(lv.input_pos - pis.input_end);
+    cc.constraint_last_row(lv.output_pos - pis.output_end);
+
+    // ebreak has no effect besides incrementing the pc
+    for ch in &lv.membus {
+        cc.constraint(lv.op.f_ebreak * ch.f_on);
+    }
+}
+
+pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &CpuCols<ExtensionTarget<D>>,
+    nv: &CpuCols<ExtensionTarget<D>>,
+    pis: &CpuPublicInputs<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = cb.one_extension();
+    let f_ecall = lv.op.f_ecall;
+    let sys = lv.shared.syscall();
+
+    // ecall reads a7, a0 and a2
+    let args = [
+        (lv.rs1_channel(), REG_A7),
+        (lv.rs2_channel(), REG_A0),
+        (lv.rd_channel(), REG_A2),
+    ];
+    for (ch, reg) in args {
+        let t = cb.sub_extension(one, ch.f_on);
+        let cs = cb.mul_extension(f_ecall, t);
+        cc.constraint(cb, cs);
+        let cs = cb.mul_extension(f_ecall, ch.f_rw);
+        cc.constraint(cb, cs);
+        let cs = cb.mul_extension(f_ecall, ch.adr_seg);
+        cc.constraint(cb, cs);
+        let t = cb.add_const_extension(ch.adr_virt, -F::from_canonical_u8(reg));
+        let cs = cb.mul_extension(f_ecall, t);
+        cc.constraint(cb, cs);
+    }
+
+    // a7 selects exactly one syscall
+    for f in [sys.f_halt, sys.f_read, sys.f_write] {
+        let t = cb.mul_sub_extension(f, f, f);
+        let cs = cb.mul_extension(f_ecall, t);
+        cc.constraint(cb, cs);
+    }
+    let t = cb.add_many_extension([sys.f_halt, sys.f_read, sys.f_write]);
+    let cs = cb.mul_sub_extension(f_ecall, t, f_ecall);
+    cc.constraint(cb, cs);
+    let halt = F::from_canonical_u32(Syscall::Halt as u32);
+    let read = F::from_canonical_u32(Syscall::Read as u32);
+    let write = F::from_canonical_u32(Syscall::Write as u32);
+    let syscall = cb.mul_const_extension(halt, sys.f_halt);
+    let syscall = cb.mul_const_add_extension(read, sys.f_read, syscall);
+    let syscall = cb.mul_const_add_extension(write, sys.f_write, syscall);
+    let t = cb.sub_extension(lv.rs1_channel().val, syscall);
+    let cs = cb.mul_extension(f_ecall, t);
+    cc.constraint(cb, cs);
+
+    // reads are from the input stream, and writes to the output stream
+    let fd = lv.rs2_channel().val;
+    let t = cb.add_const_extension(fd, -F::from_canonical_u32(FD_STDIN));
+    let cs = cb.mul_many_extension([f_ecall, sys.f_read, t]);
+    cc.constraint(cb, cs);
+    let t = cb.add_const_extension(fd, -F::from_canonical_u32(FD_STDOUT));
+    let cs = cb.mul_many_extension([f_ecall, sys.f_write, t]);
+    cc.constraint(cb, cs);
+
+    // f_xfer is set if any bytes are transferred
+    let len = lv.rd_channel().val;
+    let f_io = cb.add_extension(sys.f_read, sys.f_write);
+    let t = cb.mul_extension(f_io, len);
+    let t = cb.sub_extension(sys.n_bytes, t);
+    let cs = cb.mul_extension(f_ecall, t);
+    cc.constraint(cb, cs);
+    let t = cb.mul_extension(sys.n_bytes, sys.n_bytes_inv);
+    let t = cb.sub_extension(sys.f_xfer, t);
+    let cs = cb.mul_extension(f_ecall, t);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, sys.f_xfer);
+    let cs = cb.mul_many_extension([f_ecall, sys.n_bytes, t]);
+    cc.constraint(cb, cs);
+
+    // each stream is transferred in order, between the io offsets
+    let t = cb.sub_extension(sys.pos, lv.input_pos);
+    let cs = cb.mul_many_extension([f_ecall, sys.f_read, t]);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(sys.pos, lv.output_pos);
+    let cs = cb.mul_many_extension([f_ecall, sys.f_write, t]);
+    cc.constraint(cb, cs);
+    let cs = cb.sub_extension(lv.input_pos, pis.input_start);
+    cc.constraint_first_row(cb, cs);
+    let cs = cb.sub_extension(lv.output_pos, pis.output_start);
+    cc.constraint_first_row(cb, cs);
+    let n_read = cb.mul_many_extension([f_ecall, sys.f_read, sys.n_bytes]);
+    let t = cb.sub_extension(nv.input_pos, lv.input_pos);
+    let cs = cb.sub_extension(t, n_read);
+    cc.constraint_transition(cb, cs);
+    let n_written = cb.mul_many_extension([f_ecall, sys.f_write, sys.n_bytes]);
+    let t = cb.sub_extension(nv.output_pos, lv.output_pos);
+    let cs = cb.sub_extension(t, n_written);
+    cc.constraint_transition(cb, cs);
+    let cs = cb.sub_extension(lv.input_pos, pis.input_end);
+    cc.constraint_last_row(cb, cs);
+    let cs = cb.sub_extension(lv.output_pos, pis.output_end);
+    cc.constraint_last_row(cb, cs);
+
+    // ebreak has no effect besides incrementing the pc
+    for ch in &lv.membus {
+        let cs = cb.mul_extension(lv.op.f_ebreak, ch.f_on);
+        cc.constraint(cb, cs);
+    }
+}
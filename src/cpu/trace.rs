@@ -0,0 +1,230 @@
+use core::cmp::max;
+
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+use plonky2::util::transpose;
+
+use crate::cpu::columns::{
+    CpuCols, MemChannel, OpCols, RegBits, N_REG_BITS, RD_CHANNEL, RS1_CHANNEL, RS2_CHANNEL,
+};
+use crate::cpu::control_flow::INSTRUCTION_BYTES;
+use crate::cpu::shift::SHAMT_MASK;
+use crate::cpu::syscall::Syscall;
+use crate::mem::trace::MemOp;
+use crate::vm::machine::CpuStep;
+use crate::vm::opcode::Opcode;
+
+/// Returns the flag column of `opcode`.
+pub(crate) fn op_flag<T>(op: &mut OpCols<T>, opcode: Opcode) -> &mut T {
+    match opcode {
+        Opcode::ADD | Opcode::SUB | Opcode::SLT | Opcode::SLTU => &mut op.f_arith,
+        Opcode::AND | Opcode::OR | Opcode::XOR => &mut op.f_bits,
+        Opcode::SLL | Opcode::SRL | Opcode::SRA => &mut op.f_shift,
+        Opcode::MUL
+        | Opcode::MULH
+        | Opcode::MULHSU
+        | Opcode::MULHU
+        | Opcode::DIV
+        | Opcode::DIVU
+        | Opcode::REM
+        | Opcode::REMU => &mut op.f_muldiv,
+        Opcode::LW => &mut op.f_lw,
+        Opcode::LB => &mut op.f_lb,
+        Opcode::LH => &mut op.f_lh,
+        Opcode::LBU => &mut op.f_lbu,
+        Opcode::LHU => &mut op.f_lhu,
+        Opcode::SW => &mut op.f_sw,
+        Opcode::SB => &mut op.f_sb,
+        Opcode::SH => &mut op.f_sh,
+        Opcode::LUI => &mut op.f_lui,
+        Opcode::AUIPC => &mut op.f_auipc,
+        Opcode::ECALL => &mut op.f_ecall,
+        Opcode::EBREAK => &mut op.f_ebreak,
+        Opcode::JAL => &mut op.f_jal,
+        Opcode::JALR => &mut op.f_jalr,
+        Opcode::BEQ => &mut op.f_beq,
+        Opcode::BNE => &mut op.f_bne,
+        Opcode::BLT => &mut op.f_blt,
+        Opcode::BGE => &mut op.f_bge,
+        Opcode::BLTU => &mut op.f_bltu,
+        Opcode::BGEU => &mut op.f_bgeu,
+    }
+}
+
+fn mem_channel<F: Field>(op: &MemOp) -> MemChannel<F> {
+    MemChannel {
+        f_on: F::from_bool(op.on),
+        f_rw: F::from_bool(op.kind.into()),
+        adr_seg: F::from_canonical_usize(op.adr.seg as usize),
+        adr_virt: F::from_canonical_usize(op.adr.virt),
+        val: F::from_canonical_u32(op.val),
+    }
+}
+
+/// Returns the bits of register index `reg` in little-endian order.
+fn reg_bits<F: Field>(reg: u8) -> [F; N_REG_BITS] {
+    core::array::from_fn(|i| F::from_bool(reg & (1 << i) != 0))
+}
+
+/// Returns the carry of `x + y`.
+fn carry(x: u32, y: u32) -> bool {
+    x.overflowing_add(y).1
+}
+
+impl CpuStep {
+    /// Returns the syscall made by this step, if it is an ECALL.
+    fn syscall(&self) -> Option<Syscall> {
+        if self.ix.opcode != Opcode::ECALL {
+            return None;
+        }
+        self.membus[RS1_CHANNEL].and_then(|op| Syscall::from_u32(op.val))
+    }
+
+    /// Returns the number of bytes transferred by a READ or WRITE syscall.
+    fn n_bytes(&self) -> usize {
+        match self.syscall() {
+            Some(Syscall::Read | Syscall::Write) => {
+                self.membus[RD_CHANNEL].map_or(0, |op| op.val as usize)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Returns the input and output stream positions after this step.
+    fn next_io_pos(&self) -> (usize, usize) {
+        match self.syscall() {
+            Some(Syscall::Read) => (self.input_pos + self.n_bytes(), self.output_pos),
+            Some(Syscall::Write) => (self.input_pos, self.output_pos + self.n_bytes()),
+            _ => (self.input_pos, self.output_pos),
+        }
+    }
+
+    fn into_row<F: Field>(self) -> CpuCols<F> {
+        let ix = self.ix;
+        let mut row = CpuCols {
+            clock: F::from_canonical_usize(self.clock),
+            pc: F::from_canonical_u32(self.pc),
+            opcode: F::from_canonical_u8(ix.opcode as u8),
+            rs1: F::from_canonical_u8(ix.rs1),
+            rs2: F::from_canonical_u8(ix.rs2),
+            rd: F::from_canonical_u8(ix.rd),
+            reg_bits: RegBits {
+                rs1: reg_bits(ix.rs1),
+                rs2: reg_bits(ix.rs2),
+                rd: reg_bits(ix.rd),
+            },
+            imm: F::from_canonical_u32(ix.imm),
+            f_imm: F::from_bool(ix.uses_imm),
+            input_pos: F::from_canonical_usize(self.input_pos),
+            output_pos: F::from_canonical_usize(self.output_pos),
+            ..Default::default()
+        };
+        *op_flag(&mut row.op, ix.opcode) = F::ONE;
+
+        for (ch, op) in row.membus.iter_mut().zip(&self.membus) {
+            if let Some(op) = op {
+                *ch = mem_channel(op);
+            }
+        }
+
+        let ix_bytes = INSTRUCTION_BYTES as u32;
+        let rs1_val = self.membus[RS1_CHANNEL].map_or(0, |op| op.val);
+        let rs2_val = self.membus[RS2_CHANNEL].map_or(0, |op| op.val);
+
+        let pc_carry = match ix.opcode {
+            Opcode::LW | Opcode::LB | Opcode::LH | Opcode::LBU | Opcode::LHU => {
+                // the address is stored in the (disabled) rs2 channel
+                row.membus[RS2_CHANNEL].adr_virt = F::from_canonical_u32(self.adr_virt);
+                row.shared.memio_mut().adr_carry = F::from_bool(carry(rs1_val, ix.imm));
+                carry(self.pc, ix_bytes)
+            }
+            Opcode::SW | Opcode::SB | Opcode::SH => {
+                // the address is stored in the (disabled) rd channel
+                row.membus[RD_CHANNEL].adr_virt = F::from_canonical_u32(self.adr_virt);
+                row.shared.memio_mut().adr_carry = F::from_bool(carry(rs1_val, ix.imm));
+                carry(self.pc, ix_bytes)
+            }
+            Opcode::JAL => {
+                row.shared.jump_mut().rd_carry = F::from_bool(carry(self.pc, ix_bytes));
+                carry(self.pc, ix.imm)
+            }
+            Opcode::JALR => {
+                let jump = row.shared.jump_mut();
+                jump.rd_carry = F::from_bool(carry(self.pc, ix_bytes));
+                let target = rs1_val.wrapping_add(ix.imm);
+                jump.target = F::from_canonical_u32(target);
+                jump.target_lsb = F::from_canonical_u32(target & 1);
+                carry(rs1_val, ix.imm)
+            }
+            Opcode::BEQ | Opcode::BNE | Opcode::BLT | Opcode::BGE | Opcode::BLTU | Opcode::BGEU => {
+                let pc_offset = if self.take_branch { ix.imm } else { ix_bytes };
+                let diff = F::from_canonical_u32(rs1_val) - F::from_canonical_u32(rs2_val);
+
+                let branch = row.shared.branch_mut();
+                branch.f_take_branch = F::from_bool(self.take_branch);
+                branch.diff_pinv = diff.try_inverse().unwrap_or_default();
+                branch.pc_offset = F::from_canonical_u32(pc_offset);
+                carry(self.pc, pc_offset)
+            }
+            Opcode::SLL | Opcode::SRL | Opcode::SRA => {
+                let in1 = if ix.uses_imm { ix.imm } else { rs2_val };
+                row.shared.shift_mut().shamt = F::from_canonical_u32(in1 & SHAMT_MASK);
+                carry(self.pc, ix_bytes)
+            }
+            Opcode::ECALL => {
+                let syscall = self.syscall();
+                let n_bytes = F::from_canonical_usize(self.n_bytes());
+                let pos = match syscall {
+                    Some(Syscall::Write) => self.output_pos,
+                    _ => self.input_pos,
+                };
+
+                let sys = row.shared.syscall_mut();
+                sys.f_halt = F::from_bool(syscall == Some(Syscall::Halt));
+                sys.f_read = F::from_bool(syscall == Some(Syscall::Read));
+                sys.f_write = F::from_bool(syscall == Some(Syscall::Write));
+                sys.n_bytes = n_bytes;
+                sys.n_bytes_inv = n_bytes.try_inverse().unwrap_or_default();
+                sys.f_xfer = F::from_bool(n_bytes.is_nonzero());
+                sys.pos = F::from_canonical_usize(pos);
+                carry(self.pc, ix_bytes)
+            }
+            _ => carry(self.pc, ix_bytes),
+        };
+        row.pc_carry = F::from_bool(pc_carry);
+        row
+    }
+}
+
+pub(crate) fn gen_trace<F: Field>(
+    steps: Vec<CpuStep>,
+    min_rows: usize,
+) -> Vec<PolynomialValues<F>> {
+    let trace = gen_trace_rows(steps, min_rows);
+    let trace_rows: Vec<_> = trace.iter().map(CpuCols::to_vec).collect();
+    let trace_cols = transpose(&trace_rows);
+    trace_cols.into_iter().map(PolynomialValues::new).collect()
+}
+
+/// Generates one row per step, followed by halted rows which keep the final
+/// program counter and stream positions while the clock continues to run.
+/// There is always at least one halted row.
+pub(crate) fn gen_trace_rows<F: Field>(steps: Vec<CpuStep>, min_rows: usize) -> Vec<CpuCols<F>> {
+    let n_rows = max(steps.len() + 1, min_rows).next_power_of_two();
+    let (clock, pc) = steps
+        .last()
+        .map_or((0, 0), |step| (step.clock + 1, step.next_pc));
+    let (input_pos, output_pos) = steps.last().map_or((0, 0), CpuStep::next_io_pos);
+
+    let mut rows: Vec<_> = steps.into_iter().map(CpuStep::into_row).collect();
+    let halted = (clock..).map(|clock| CpuCols {
+        clock: F::from_canonical_usize(clock),
+        pc: F::from_canonical_u32(pc),
+        input_pos: F::from_canonical_usize(input_pos),
+        output_pos: F::from_canonical_usize(output_pos),
+        ..Default::default()
+    });
+    let n_halted = n_rows - rows.len();
+    rows.extend(halted.take(n_halted));
+    rows
+}
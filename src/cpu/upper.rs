@@ -0,0 +1,77 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+
+use crate::cpu::columns::CpuCols;
+
+/// The amount by which the immediate of LUI and AUIPC is shifted.
+pub(crate) const UPPER_IMM_SHIFT: u32 = 12;
+
+pub(crate) fn eval<P: PackedField>(
+    lv: &CpuCols<P>,
+    nv: &CpuCols<P>,
+    cc: &mut ConstraintConsumer<P>,
+) {
+    let f_lui = lv.op.f_lui;
+    let f_auipc = lv.op.f_auipc;
+    let f_upper = f_lui + f_auipc;
+
+    // both lui and auipc disable the rs1 and rs2 memory channels
+    cc.constraint(f_upper * lv.rs1_channel().f_on);
+    cc.constraint(f_upper * lv.rs2_channel().f_on);
+
+    // both lui and auipc write to rd
+    let ch_rd = lv.rd_channel();
+    cc.constraint(f_upper * (P::ONES - ch_rd.f_on));
+    cc.constraint(f_upper * (P::ONES - ch_rd.f_rw));
+    cc.constraint(f_upper * ch_rd.adr_seg);
+    cc.constraint(f_upper * (lv.rd - ch_rd.adr_virt));
+
+    // lui sets rd = imm << 12. For auipc, rd = pc + (imm << 12) is checked by
+    // the arith table.
+    let upper_imm = lv.imm * P::Scalar::from_canonical_u32(1 << UPPER_IMM_SHIFT);
+    cc.constraint(f_lui * (ch_rd.val - upper_imm));
+}
+
+pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &CpuCols<ExtensionTarget<D>>,
+    nv: &CpuCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = cb.one_extension();
+    let f_lui = lv.op.f_lui;
+    let f_auipc = lv.op.f_auipc;
+    let f_upper = cb.add_extension(f_lui, f_auipc);
+
+    // both lui and auipc disable the rs1 and rs2 memory channels
+    let cs = cb.mul_extension(f_upper, lv.rs1_channel().f_on);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_upper, lv.rs2_channel().f_on);
+    cc.constraint(cb, cs);
+
+    // both lui and auipc write to rd
+    let ch_rd = lv.rd_channel();
+    let t = cb.sub_extension(one, ch_rd.f_on);
+    let cs = cb.mul_extension(f_upper, t);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, ch_rd.f_rw);
+    let cs = cb.mul_extension(f_upper, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(f_upper, ch_rd.adr_seg);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(lv.rd, ch_rd.adr_virt);
+    let cs = cb.mul_extension(f_upper, t);
+    cc.constraint(cb, cs);
+
+    // lui sets rd = imm << 12
+    let shift = F::from_canonical_u32(1 << UPPER_IMM_SHIFT);
+    let t = cb.mul_const_extension(-shift, lv.imm);
+    let t = cb.add_extension(ch_rd.val, t);
+    let cs = cb.mul_extension(f_lui, t);
+    cc.constraint(cb, cs);
+}
@@ -0,0 +1,58 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+use rizzo_derive::{Columns, DerefColumns};
+
+/// The value of each struct field is the index of the corresponding column.
+pub(crate) const IO_COL_MAP: IoCols<usize> = make_col_map();
+/// The number of field elements in `IoCols`.
+pub(crate) const N_IO_COLS: usize = core::mem::size_of::<IoCols<u8>>();
+/// The number of field elements in `TapeCols`.
+pub(crate) const N_TAPE_COLS: usize = core::mem::size_of::<TapeCols<u8>>();
+
+/// Columns holding a single byte of the input or output stream. Their values
+/// are fixed by the public inputs and outputs, and are checked by the
+/// verifier.
+#[repr(C)]
+#[derive(DerefColumns, Clone, Debug, Default)]
+pub(crate) struct TapeCols<T> {
+    /// 1 if this row holds a byte of the input stream.
+    pub f_in: T,
+    /// 1 if this row holds a byte of the output stream.
+    pub f_out: T,
+    /// The position of the byte in its stream.
+    pub pos: T,
+    /// The value of the byte.
+    pub byte: T,
+}
+
+/// Columns for the io stark.
+///
+/// Each row transfers a single byte between main memory and the input or
+/// output stream. The bytes transferred by a syscall occupy consecutive rows,
+/// the first of which is looked up by the CPU.
+#[repr(C)]
+#[derive(Columns, DerefColumns, Clone, Debug)]
+pub(crate) struct IoCols<T> {
+    /// The transferred byte. These are the first `N_TAPE_COLS` columns.
+    pub tape: TapeCols<T>,
+    /// 1 if this is the first byte transferred by a syscall.
+    pub f_start: T,
+    /// The clock of the syscall.
+    pub clock: T,
+    /// The main memory address of the byte.
+    pub adr: T,
+    /// The number of bytes left to transfer after this one.
+    pub rem: T,
+}
+
+impl<T: Copy> IoCols<T> {
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        Borrow::<[T; N_IO_COLS]>::borrow(self).to_vec()
+    }
+}
+
+const fn make_col_map() -> IoCols<usize> {
+    let arr = crate::util::indices_arr::<N_IO_COLS>();
+    unsafe { core::mem::transmute::<[usize; N_IO_COLS], IoCols<usize>>(arr) }
+}
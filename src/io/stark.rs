@@ -0,0 +1,270 @@
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::cross_table_lookup::TableWithColumns;
+use starky::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use starky::lookup::{Column, Filter};
+use starky::stark::Stark;
+
+use crate::cpu::columns::N_MEM_CHANNELS;
+use crate::cpu::syscall::REG_A1;
+use crate::io::columns::{IoCols, IO_COL_MAP, N_IO_COLS};
+use crate::mem::Segment;
+use crate::stark::Table;
+
+/// The timestamp of the memory accesses of a syscall, which is the same as
+/// that of loads and stores.
+fn mem_timestamp<F: Field>() -> Column<F> {
+    let n_channels = F::from_canonical_usize(N_MEM_CHANNELS);
+    Column::linear_combination_with_constant([(IO_COL_MAP.clock, n_channels)], n_channels)
+}
+
+pub(crate) fn ctl_looked<F: Field>() -> TableWithColumns<F> {
+    // the number of bytes transferred by the syscall is `rem + 1` in its
+    // first row
+    let len = Column::linear_combination_with_constant([(IO_COL_MAP.rem, F::ONE)], F::ONE);
+    let cols = vec![
+        Column::single(IO_COL_MAP.tape.f_out),
+        Column::single(IO_COL_MAP.clock),
+        len,
+        Column::single(IO_COL_MAP.tape.pos),
+    ];
+
+    let filter = Filter::new_simple(Column::single(IO_COL_MAP.f_start));
+    TableWithColumns::new(Table::Io as usize, cols, filter)
+}
+
+/// Looking table io->memory, reading or writing the transferred byte.
+pub(crate) fn ctl_looking_mem<F: Field>() -> TableWithColumns<F> {
+    let tape = &IO_COL_MAP.tape;
+    let cols = vec![
+        // bytes read from the input stream are written to memory
+        Column::single(tape.f_in),
+        Column::constant(F::from_canonical_usize(Segment::Main as usize)),
+        Column::single(IO_COL_MAP.adr),
+        Column::single(tape.byte),
+        mem_timestamp(),
+    ];
+
+    let filter = Filter::new_simple(Column::sum([tape.f_in, tape.f_out]));
+    TableWithColumns::new(Table::Io as usize, cols, filter)
+}
+
+/// Looking table io->memory, reading the buffer address from register `a1`
+/// in the first row of each syscall.
+pub(crate) fn ctl_looking_buf<F: Field>() -> TableWithColumns<F> {
+    let cols = vec![
+        Column::constant(F::ZERO),
+        Column::constant(F::from_canonical_usize(Segment::Reg as usize)),
+        Column::constant(F::from_canonical_u8(REG_A1)),
+        Column::single(IO_COL_MAP.adr),
+        mem_timestamp(),
+    ];
+
+    let filter = Filter::new_simple(Column::single(IO_COL_MAP.f_start));
+    TableWithColumns::new(Table::Io as usize, cols, filter)
+}
+
+fn eval_all<P: PackedField>(lv: &IoCols<P>, nv: &IoCols<P>, cc: &mut ConstraintConsumer<P>) {
+    // the tape columns are checked by the verifier
+    let f_on = lv.tape.f_in + lv.tape.f_out;
+    let f_on_next = nv.tape.f_in + nv.tape.f_out;
+
+    // syscalls start on rows holding a byte, and the first byte starts one
+    cc.constraint(lv.f_start * (lv.f_start - P::ONES));
+    cc.constraint(lv.f_start * (P::ONES - f_on));
+    cc.constraint_first_row(f_on - lv.f_start);
+
+    // the next row either starts a syscall, or transfers the next byte of the
+    // same syscall
+    let f_cont = f_on_next - nv.f_start;
+    cc.constraint_transition(f_cont * (nv.tape.f_in - lv.tape.f_in));
+    cc.constraint_transition(f_cont * (nv.tape.f_out - lv.tape.f_out));
+    cc.constraint_transition(f_cont * (nv.clock - lv.clock));
+    cc.constraint_transition(f_cont * (nv.adr - lv.adr - P::ONES));
+    cc.constraint_transition(f_cont * (nv.rem - lv.rem + P::ONES));
+
+    // a syscall only ends once every byte has been transferred. Since `rem`
+    // decreases by one in each row, it can't wrap around to zero before the
+    // end of the trace.
+    cc.constraint_transition(lv.rem * (P::ONES - f_cont));
+    cc.constraint_last_row(lv.rem);
+}
+
+fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &IoCols<ExtensionTarget<D>>,
+    nv: &IoCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = cb.one_extension();
+    let f_on = cb.add_extension(lv.tape.f_in, lv.tape.f_out);
+    let f_on_next = cb.add_extension(nv.tape.f_in, nv.tape.f_out);
+
+    // syscalls start on rows holding a byte, and the first byte starts one
+    let cs = cb.mul_sub_extension(lv.f_start, lv.f_start, lv.f_start);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, f_on);
+    let cs = cb.mul_extension(lv.f_start, t);
+    cc.constraint(cb, cs);
+    let cs = cb.sub_extension(f_on, lv.f_start);
+    cc.constraint_first_row(cb, cs);
+
+    // the next row either starts a syscall, or transfers the next byte of the
+    // same syscall
+    let f_cont = cb.sub_extension(f_on_next, nv.f_start);
+    let t = cb.sub_extension(nv.tape.f_in, lv.tape.f_in);
+    let cs = cb.mul_extension(f_cont, t);
+    cc.constraint_transition(cb, cs);
+    let t = cb.sub_extension(nv.tape.f_out, lv.tape.f_out);
+    let cs = cb.mul_extension(f_cont, t);
+    cc.constraint_transition(cb, cs);
+    let t = cb.sub_extension(nv.clock, lv.clock);
+    let cs = cb.mul_extension(f_cont, t);
+    cc.constraint_transition(cb, cs);
+    let t = cb.sub_extension(nv.adr, lv.adr);
+    let cs = cb.mul_sub_extension(f_cont, t, f_cont);
+    cc.constraint_transition(cb, cs);
+    let t = cb.sub_extension(nv.rem, lv.rem);
+    let cs = cb.mul_add_extension(f_cont, t, f_cont);
+    cc.constraint_transition(cb, cs);
+
+    // a syscall only ends once every byte has been transferred
+    let t = cb.sub_extension(one, f_cont);
+    let cs = cb.mul_extension(lv.rem, t);
+    cc.constraint_transition(cb, cs);
+    cc.constraint_last_row(cb, lv.rem);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IoStark<F, const D: usize> {
+    _unused: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for IoStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, N_IO_COLS, 0>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget = StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, N_IO_COLS, 0>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        frame: &Self::EvaluationFrame<FE, P, D2>,
+        cc: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local: &[P; N_IO_COLS] = frame.get_local_values().try_into().unwrap();
+        let local: &IoCols<P> = local.borrow();
+        let next: &[P; N_IO_COLS] = frame.get_next_values().try_into().unwrap();
+        let next: &IoCols<P> = next.borrow();
+        eval_all(local, next, cc);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        frame: &Self::EvaluationFrameTarget,
+        cc: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local: &[ExtensionTarget<D>; N_IO_COLS] = frame.get_local_values().try_into().unwrap();
+        let local: &IoCols<ExtensionTarget<D>> = local.borrow();
+        let next: &[ExtensionTarget<D>; N_IO_COLS] = frame.get_next_values().try_into().unwrap();
+        let next: &IoCols<ExtensionTarget<D>> = next.borrow();
+        eval_all_circuit(cb, local, next, cc);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn requires_ctls(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::IoStark;
+    use crate::io::trace::gen_trace;
+    use crate::vm::asm::assemble;
+    use crate::vm::Machine;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = IoStark<F, D>;
+
+    #[test]
+    fn test_stark_degree() {
+        let stark: S = Default::default();
+        test_stark_low_degree(stark).unwrap();
+    }
+
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
+
+    #[test]
+    fn test_gen_eval() {
+        crate::util::impl_stark_no_ctls!(IoStark);
+        type S = IoStarkNoCtls<F, D>;
+        const CFG: StarkConfig = StarkConfig::standard_fast_config();
+
+        // read 3 bytes, then write them back along with an empty write
+        // and a read of the last 2 bytes
+        let program = assemble(
+            "
+                li a7, 63
+                li a0, 0
+                li a1, 0x100
+                li a2, 3
+                ecall
+                li a7, 64
+                li a0, 1
+                ecall
+                li a2, 0
+                ecall
+                li a7, 63
+                li a0, 0
+                li a2, 2
+                ecall
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program)
+            .with_input([1, 2, 3, 4, 5])
+            .run(100)
+            .unwrap();
+        assert_eq!(log.tape.input, [1, 2, 3, 4, 5]);
+        assert_eq!(log.tape.output, [1, 2, 3]);
+
+        let stark: S = Default::default();
+        let min_rows = CFG.fri_config.num_cap_elements();
+        let trace = gen_trace::<F>(log.io, min_rows);
+        let mut t = TimingTree::default();
+        let proof = prove::<F, C, S, D>(stark, &CFG, trace, &[], &mut t).unwrap();
+        verify_stark_proof(stark, proof, &CFG).unwrap();
+    }
+}
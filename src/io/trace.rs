@@ -0,0 +1,192 @@
+use core::cmp::max;
+use core::ops::Range;
+
+use anyhow::{ensure, Result};
+use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::util::transpose;
+
+use crate::io::columns::{IoCols, TapeCols};
+use crate::util::hash_rows;
+
+/// The bytes read from the input stream and written to the output stream
+/// by an execution, in order. A segment of an execution transfers the bytes
+/// of each stream from the position in which the previous segment stopped.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IoTape {
+    /// The bytes consumed by READ syscalls.
+    pub input: Vec<u8>,
+    /// The bytes produced by WRITE syscalls.
+    pub output: Vec<u8>,
+    /// The position in the input stream of the first byte of `input`.
+    pub input_start: usize,
+    /// The position in the output stream of the first byte of `output`.
+    pub output_start: usize,
+}
+
+impl IoTape {
+    /// Returns the position in the input stream following `input`.
+    pub fn input_end(&self) -> usize {
+        self.input_start + self.input.len()
+    }
+
+    /// Returns the position in the output stream following `output`.
+    pub fn output_end(&self) -> usize {
+        self.output_start + self.output.len()
+    }
+
+    /// Returns the bytes at the positions `input` of the input stream and
+    /// `output` of the output stream, or `None` if the tape doesn't hold all
+    /// of them.
+    pub(crate) fn chunk(&self, input: Range<usize>, output: Range<usize>) -> Option<Self> {
+        let bytes = |stream: &[u8], start: usize, range: &Range<usize>| {
+            let range = range.start.checked_sub(start)?..range.end.checked_sub(start)?;
+            stream.get(range).map(<[u8]>::to_vec)
+        };
+        Some(Self {
+            input: bytes(&self.input, self.input_start, &input)?,
+            output: bytes(&self.output, self.output_start, &output)?,
+            input_start: input.start,
+            output_start: output.start,
+        })
+    }
+
+    /// Returns the total number of bytes in both streams.
+    pub fn len(&self) -> usize {
+        self.input.len() + self.output.len()
+    }
+
+    /// Returns true if both streams are empty.
+    pub fn is_empty(&self) -> bool {
+        self.input.is_empty() && self.output.is_empty()
+    }
+
+    /// Returns a hash of the position and value of every byte of the input
+    /// stream.
+    pub fn input_hash<F: RichField>(&self) -> HashOut<F> {
+        let rows = self.rows::<F>(0).filter(|row| row.f_in.is_one());
+        hash_rows(rows.map(|row| vec![row.pos, row.byte]))
+    }
+
+    /// Returns a hash of the position and value of every byte of the output
+    /// stream.
+    pub fn output_hash<F: RichField>(&self) -> HashOut<F> {
+        let rows = self.rows::<F>(0).filter(|row| row.f_out.is_one());
+        hash_rows(rows.map(|row| vec![row.pos, row.byte]))
+    }
+
+    /// Returns the input bytes followed by the output bytes as rows of tape
+    /// columns, followed by padding rows up to a total of `n_rows` rows.
+    pub(crate) fn rows<F: Field>(&self, n_rows: usize) -> impl Iterator<Item = TapeCols<F>> + '_ {
+        let input = (self.input_start..)
+            .zip(&self.input)
+            .map(|(pos, &byte)| TapeCols {
+                f_in: F::ONE,
+                pos: F::from_canonical_usize(pos),
+                byte: F::from_canonical_u8(byte),
+                ..Default::default()
+            });
+        let output = (self.output_start..)
+            .zip(&self.output)
+            .map(|(pos, &byte)| TapeCols {
+                f_out: F::ONE,
+                pos: F::from_canonical_usize(pos),
+                byte: F::from_canonical_u8(byte),
+                ..Default::default()
+            });
+        let n_padding = n_rows.saturating_sub(self.len());
+        input
+            .chain(output)
+            .chain((0..n_padding).map(|_| TapeCols::default()))
+    }
+
+    /// Returns the polynomials of the tape columns of an io table with
+    /// `2^degree_bits` rows.
+    pub(crate) fn tape_polys<F: Field>(
+        &self,
+        degree_bits: usize,
+    ) -> Result<Vec<PolynomialCoeffs<F>>> {
+        let n_rows = 1 << degree_bits;
+        ensure!(
+            self.len() <= n_rows,
+            "streams hold {} bytes but the io table has {n_rows} rows",
+            self.len(),
+        );
+        let rows: Vec<_> = self.rows(n_rows).map(|row| row.to_vec()).collect();
+        let cols = transpose(&rows);
+        Ok(cols
+            .into_iter()
+            .map(|col| PolynomialValues::new(col).ifft())
+            .collect())
+    }
+}
+
+/// The direction of a transfer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum IoKind {
+    /// Bytes are copied from the input stream to main memory.
+    Read,
+    /// Bytes are copied from main memory to the output stream.
+    Write,
+}
+
+/// The bytes transferred by a single READ or WRITE syscall.
+#[derive(Clone, Debug)]
+pub(crate) struct IoOp {
+    pub kind: IoKind,
+    /// The value of the clock when the syscall was executed.
+    pub clock: usize,
+    /// The main memory address of the buffer.
+    pub buf: u32,
+    /// The position of the first byte in its stream.
+    pub pos: usize,
+    /// The transferred bytes.
+    pub bytes: Vec<u8>,
+}
+
+impl IoOp {
+    fn into_rows<F: Field>(self) -> impl Iterator<Item = IoCols<F>> {
+        let Self {
+            kind,
+            clock,
+            buf,
+            pos,
+            bytes,
+        } = self;
+        let len = bytes.len();
+        bytes.into_iter().enumerate().map(move |(i, byte)| IoCols {
+            tape: TapeCols {
+                f_in: F::from_bool(kind == IoKind::Read),
+                f_out: F::from_bool(kind == IoKind::Write),
+                pos: F::from_canonical_usize(pos + i),
+                byte: F::from_canonical_u8(byte),
+            },
+            f_start: F::from_bool(i == 0),
+            clock: F::from_canonical_usize(clock),
+            adr: F::from_canonical_u32(buf + i as u32),
+            rem: F::from_canonical_usize(len - 1 - i),
+        })
+    }
+}
+
+pub(crate) fn gen_trace<F: Field>(ops: Vec<IoOp>, min_rows: usize) -> Vec<PolynomialValues<F>> {
+    let trace = gen_trace_rows(ops, min_rows);
+    let trace_rows: Vec<_> = trace.iter().map(IoCols::to_vec).collect();
+    let trace_cols = transpose(&trace_rows);
+    trace_cols.into_iter().map(PolynomialValues::new).collect()
+}
+
+/// Generates one row per transferred byte. All reads come before all writes,
+/// so that the tape columns only depend on the input and output streams.
+fn gen_trace_rows<F: Field>(ops: Vec<IoOp>, min_rows: usize) -> Vec<IoCols<F>> {
+    let (reads, writes): (Vec<_>, Vec<_>) = ops.into_iter().partition(|op| op.kind == IoKind::Read);
+    let mut rows: Vec<_> = reads
+        .into_iter()
+        .chain(writes)
+        .flat_map(IoOp::into_rows)
+        .collect();
+    let n_rows = max(rows.len(), min_rows).next_power_of_two();
+    rows.resize(n_rows, IoCols::default());
+    rows
+}
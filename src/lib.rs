@@ -6,8 +6,17 @@ pub mod arith;
 pub mod bits;
 pub mod bytes;
 pub mod cpu;
+pub mod io;
 pub mod iter;
 pub mod mem;
+pub mod memfinal;
+pub mod meminit;
+pub mod muldiv;
+pub mod program;
+pub mod proof;
+pub mod prover;
+pub mod recursive_verifier;
 pub mod stark;
 pub mod util;
+pub mod verifier;
 pub mod vm;
@@ -43,7 +43,8 @@ pub(crate) struct MemCols<T> {
     pub f_reg0: T,
     /// 1 if `adr_seg` differs in the next row.
     pub f_seg_diff: T,
-    /// 1 if `adr_virt` differs in the next row and `adr_seg` does not.
+    /// 1 if `adr_virt` differs in the next row and `adr_seg` does not, or if
+    /// this is the last row.
     pub f_virt_diff: T,
     /// Range check columns.
     pub range_check: RangeCheck<T>,
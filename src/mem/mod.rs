@@ -1,4 +1,5 @@
 pub mod columns;
+pub mod page;
 pub mod stark;
 pub mod trace;
 
@@ -7,3 +8,7 @@ pub enum Segment {
     Reg,
     Main,
 }
+
+/// The timestamp of the writes initializing memory, which precede every
+/// other memory access.
+pub(crate) const INIT_TIME: usize = 0;
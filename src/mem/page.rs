@@ -0,0 +1,531 @@
+//! Paged memory, committed to by a Poseidon Merkle tree.
+//!
+//! Main memory is cut into pages of [`PAGE_BYTES`] bytes, and the registers
+//! form one more page. The pages are the leaves of a sparse Merkle tree whose
+//! root identifies the whole contents of memory, so that the segments of an
+//! execution are linked by roots only: a segment pages in the pages it
+//! touches, with a [`PageProof`] against the root in which it starts, and
+//! pages them out at its end, giving the root in which the next segment
+//! starts. Untouched pages never enter the trace.
+
+use anyhow::{anyhow, ensure, Context, Result};
+use hashbrown::HashMap;
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+
+use crate::mem::Segment;
+use crate::memfinal::trace::FinalMem;
+use crate::meminit::trace::MemImage;
+use crate::vm::machine::N_REGS;
+
+/// The base 2 logarithm of [`PAGE_BYTES`].
+pub(crate) const PAGE_BITS: usize = 10;
+/// The number of bytes in a page of main memory.
+pub const PAGE_BYTES: usize = 1 << PAGE_BITS;
+/// The index of the page holding the registers, following the pages of
+/// main memory.
+pub(crate) const REG_PAGE: usize = 1 << (32 - PAGE_BITS);
+/// The number of levels of the Merkle tree above its leaves.
+pub const TREE_DEPTH: usize = 32 - PAGE_BITS + 1;
+
+/// Returns the index of the page holding the address `virt` of segment
+/// `seg`, along with the offset of the address in that page.
+pub(crate) fn page_of(seg: Segment, virt: u32) -> (usize, usize) {
+    match seg {
+        Segment::Reg => (REG_PAGE, virt as usize),
+        Segment::Main => ((virt >> PAGE_BITS) as usize, virt as usize % PAGE_BYTES),
+    }
+}
+
+/// Returns the number of values in the page at `index`.
+fn page_len(index: usize) -> usize {
+    if index == REG_PAGE {
+        N_REGS
+    } else {
+        PAGE_BYTES
+    }
+}
+
+/// Returns the hash of a page, which is 0 if the page only holds zeros.
+pub(crate) fn page_hash<F: RichField>(page: &[u32]) -> HashOut<F> {
+    if page.iter().all(|&val| val == 0) {
+        return HashOut::ZERO;
+    }
+    let elements: Vec<F> = page.iter().map(|&val| F::from_canonical_u32(val)).collect();
+    PoseidonHash::hash_no_pad(&elements)
+}
+
+/// Circuit version of [`page_hash`]. The values of the page must be range
+/// checked, so that their sum is 0 only if they all are.
+pub(crate) fn page_hash_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    page: &[Target],
+) -> HashOutTarget {
+    let zero = cb.zero();
+    let sum = cb.add_many(page);
+    let f_empty = cb.is_equal(sum, zero);
+    let hash = cb.hash_n_to_hash_no_pad::<PoseidonHash>(page.to_vec());
+    let zero_hash = HashOutTarget {
+        elements: [zero; 4],
+    };
+    select_hash_circuit(cb, f_empty, zero_hash, hash)
+}
+
+/// Returns the root of the Merkle tree computed from the hash of the leaf at
+/// the index with little-endian bits `index_bits`, and from the hashes of the
+/// siblings of the path from the leaf to the root, from the leaf up.
+pub(crate) fn path_root_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    leaf: HashOutTarget,
+    index_bits: &[BoolTarget],
+    siblings: &[HashOutTarget],
+) -> HashOutTarget {
+    index_bits
+        .iter()
+        .zip(siblings)
+        .fold(leaf, |node, (&f_right, &sibling)| {
+            let left = select_hash_circuit(cb, f_right, sibling, node);
+            let right = select_hash_circuit(cb, f_right, node, sibling);
+            let inputs = left.elements.into_iter().chain(right.elements).collect();
+            cb.hash_n_to_hash_no_pad::<PoseidonHash>(inputs)
+        })
+}
+
+/// Returns `x` if `b` is set, and `y` otherwise.
+pub(crate) fn select_hash_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    b: BoolTarget,
+    x: HashOutTarget,
+    y: HashOutTarget,
+) -> HashOutTarget {
+    let elements = core::array::from_fn(|i| cb.select(b, x.elements[i], y.elements[i]));
+    HashOutTarget { elements }
+}
+
+/// Returns the hash of an empty subtree at each level of the Merkle tree.
+fn empty_hashes<F: RichField>() -> Vec<HashOut<F>> {
+    let mut hashes = vec![HashOut::ZERO];
+    for level in 0..TREE_DEPTH {
+        hashes.push(PoseidonHash::two_to_one(hashes[level], hashes[level]));
+    }
+    hashes
+}
+
+/// Computes the root of the Merkle tree from the hashes of `leaves`, which
+/// are ordered by index. The hash of any other node needed along the way is
+/// provided by `sibling`, given its level and index.
+fn merkle_root<F: RichField>(
+    leaves: Vec<(usize, HashOut<F>)>,
+    mut sibling: impl FnMut(usize, usize) -> Result<HashOut<F>>,
+) -> Result<HashOut<F>> {
+    ensure!(!leaves.is_empty(), "no pages to compute the root from");
+    let mut nodes = leaves;
+    for level in 0..TREE_DEPTH {
+        let mut parents = Vec::with_capacity(nodes.len());
+        let mut nodes_iter = nodes.into_iter().peekable();
+        while let Some((index, hash)) = nodes_iter.next() {
+            let (left, right) = if index % 2 == 0 {
+                let right = match nodes_iter.next_if(|&(next, _)| next == index + 1) {
+                    Some((_, right)) => right,
+                    None => sibling(level, index + 1)?,
+                };
+                (hash, right)
+            } else {
+                (sibling(level, index - 1)?, hash)
+            };
+            parents.push((index / 2, PoseidonHash::two_to_one(left, right)));
+        }
+        nodes = parents;
+    }
+    Ok(nodes[0].1)
+}
+
+/// Returns the hash of the node at `index` in `level` of the Merkle tree,
+/// where `pages` are the non-empty leaves below that node.
+fn node_hash<F: RichField>(
+    level: usize,
+    index: usize,
+    pages: &[(usize, Vec<u32>)],
+    empty: &[HashOut<F>],
+) -> HashOut<F> {
+    match pages {
+        [] => empty[level],
+        [(_, page)] if level == 0 => page_hash(page),
+        _ => {
+            let mid = (2 * index + 1) << (level - 1);
+            let (left, right) = pages.split_at(pages.partition_point(|&(i, _)| i < mid));
+            let left = node_hash(level - 1, 2 * index, left, empty);
+            let right = node_hash(level - 1, 2 * index + 1, right, empty);
+            PoseidonHash::two_to_one(left, right)
+        }
+    }
+}
+
+/// Sets the hash of the leaf at `index` among `nodes`, and recomputes the
+/// hashes of the nodes on its path to the root, whose siblings must be among
+/// `nodes`.
+fn update_leaf<F: RichField>(
+    nodes: &mut HashMap<(usize, usize), HashOut<F>>,
+    index: usize,
+    hash: HashOut<F>,
+) {
+    nodes.insert((0, index), hash);
+    for level in 0..TREE_DEPTH {
+        let i = index >> level;
+        let parent = PoseidonHash::two_to_one(nodes[&(level, i & !1)], nodes[&(level, i | 1)]);
+        nodes.insert((level + 1, i >> 1), parent);
+    }
+}
+
+/// The contents of the registers and main memory, cut into pages. Pages
+/// holding only zeros are left out.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PagedMemory {
+    pages: Vec<(usize, Vec<u32>)>,
+}
+
+impl PagedMemory {
+    /// Collects the pages of the registers `regs` and the bytes of `image`.
+    pub(crate) fn new(image: &HashMap<u32, u8>, regs: &[u32; N_REGS]) -> Self {
+        let regs = (0..).zip(regs).map(|(reg, &val)| (Segment::Reg, reg, val));
+        let bytes = image
+            .iter()
+            .map(|(&adr, &val)| (Segment::Main, adr, val.into()));
+        Self::default().write(regs.chain(bytes))
+    }
+
+    /// Returns the memory in which a segment starting from this memory and
+    /// ending with `final_mem` stops.
+    pub(crate) fn with_final(&self, final_mem: &FinalMem) -> Self {
+        self.write(final_mem.cells().iter().copied())
+    }
+
+    /// Returns this memory with the value of each address of `cells`
+    /// overwritten.
+    fn write(&self, cells: impl IntoIterator<Item = (Segment, u32, u32)>) -> Self {
+        let mut pages: HashMap<usize, Vec<u32>> = self.pages.iter().cloned().collect();
+        for (seg, virt, val) in cells {
+            let (index, offset) = page_of(seg, virt);
+            let page = pages
+                .entry(index)
+                .or_insert_with(|| vec![0; page_len(index)]);
+            page[offset] = val;
+        }
+        let pages = pages
+            .into_iter()
+            .filter(|(_, page)| page.iter().any(|&val| val != 0))
+            .sorted_by_key(|&(index, _)| index)
+            .collect();
+        Self { pages }
+    }
+
+    /// Returns the root of the Merkle tree of the pages.
+    pub fn root<F: RichField>(&self) -> HashOut<F> {
+        node_hash(TREE_DEPTH, 0, &self.pages, &empty_hashes())
+    }
+
+    /// Returns the contents of the page at `index`.
+    fn page(&self, index: usize) -> Vec<u32> {
+        match self.pages.binary_search_by_key(&index, |&(i, _)| i) {
+            Ok(i) => self.pages[i].1.clone(),
+            Err(_) => vec![0; page_len(index)],
+        }
+    }
+
+    /// Returns the pages at `indices`, along with the proof that they belong
+    /// to this memory.
+    pub(crate) fn prove<F: RichField>(
+        &self,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> PageProof<F> {
+        let pages: Vec<_> = indices
+            .into_iter()
+            .sorted()
+            .dedup()
+            .map(|index| (index, self.page(index)))
+            .collect();
+        let leaves = pages
+            .iter()
+            .map(|(index, page)| (*index, page_hash(page)))
+            .collect();
+
+        let empty = empty_hashes();
+        let mut siblings = vec![];
+        merkle_root(leaves, |level, index| {
+            let start = index << level;
+            let end = (index + 1) << level;
+            let lo = self.pages.partition_point(|&(i, _)| i < start);
+            let hi = self.pages.partition_point(|&(i, _)| i < end);
+            let hash = node_hash(level, index, &self.pages[lo..hi], &empty);
+            siblings.push(hash);
+            Ok(hash)
+        })
+        .expect("at least one page is proven");
+        PageProof { pages, siblings }
+    }
+}
+
+/// A set of pages, along with the hashes of the nodes of the Merkle tree
+/// needed to compute its root from them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PageProof<F: RichField> {
+    /// The index and contents of each page, ordered by index.
+    pages: Vec<(usize, Vec<u32>)>,
+    /// The hashes of the siblings of the paths from the pages to the root, in
+    /// the order in which they are consumed by [`merkle_root`].
+    siblings: Vec<HashOut<F>>,
+}
+
+/// A page as it is at the start and at the end of a segment, along with the
+/// path from its leaf to the root of the Merkle tree.
+#[derive(Clone, Debug)]
+pub(crate) struct PageUpdate<F: RichField> {
+    /// The index of the page.
+    pub index: usize,
+    /// The contents of the page at the start of the segment.
+    pub start: Vec<u32>,
+    /// The contents of the page at the end of the segment.
+    pub end: Vec<u32>,
+    /// For each value of the page, true if its address is in the final
+    /// memory of the segment.
+    pub written: Vec<bool>,
+    /// The hashes of the siblings of the path from the leaf to the root,
+    /// from the leaf up.
+    pub siblings: Vec<HashOut<F>>,
+}
+
+impl<F: RichField> PageProof<F> {
+    /// Checks that the pages belong to the memory with the given `root`, and
+    /// returns their contents as the image in which a segment starts.
+    pub fn page_in(&self, root: HashOut<F>) -> Result<MemImage> {
+        ensure!(
+            self.root(&self.pages)? == root,
+            "pages do not match the memory root"
+        );
+        let mut bytes = HashMap::default();
+        let mut regs = [0; N_REGS];
+        for (index, page) in &self.pages {
+            if *index == REG_PAGE {
+                regs.copy_from_slice(page);
+            } else {
+                let base = (*index << PAGE_BITS) as u32;
+                let page_bytes = (0..).zip(page).map(|(i, &val)| (base + i, val as u8));
+                bytes.extend(page_bytes.filter(|&(_, val)| val != 0));
+            }
+        }
+        Ok(MemImage::new(&bytes, &regs))
+    }
+
+    /// Writes `final_mem` to the pages, and returns the root of the memory in
+    /// which a segment ending with `final_mem` stops. Every address of
+    /// `final_mem` must belong to one of the pages.
+    pub fn page_out(&self, final_mem: &FinalMem) -> Result<HashOut<F>> {
+        self.root(&self.write(final_mem)?)
+    }
+
+    /// Returns each page as it is at the start and at the end of a segment
+    /// ending with `final_mem`, along with the siblings of the path from the
+    /// page to the root. The pages are updated one at a time in order of
+    /// index, and the siblings of each page are those of the tree in which
+    /// the previous pages are updated.
+    pub(crate) fn updates(&self, final_mem: &FinalMem) -> Result<Vec<PageUpdate<F>>> {
+        let end_pages = self.write(final_mem)?;
+        let mut nodes = self.nodes()?;
+        let mut updates = vec![];
+        for ((index, start), (_, end)) in self.pages.iter().zip(end_pages) {
+            let siblings = (0..TREE_DEPTH)
+                .map(|level| nodes[&(level, (index >> level) ^ 1)])
+                .collect();
+            let mut written = vec![false; start.len()];
+            for &(seg, virt, _) in final_mem.cells() {
+                let (i, offset) = page_of(seg, virt);
+                if i == *index {
+                    written[offset] = true;
+                }
+            }
+            update_leaf(&mut nodes, *index, page_hash(&end));
+            updates.push(PageUpdate {
+                index: *index,
+                start: start.clone(),
+                end,
+                written,
+                siblings,
+            });
+        }
+        Ok(updates)
+    }
+
+    /// Returns the hash of every node of the Merkle tree which is either on
+    /// the path from a page to the root or a sibling of such a node, indexed
+    /// by level and index.
+    fn nodes(&self) -> Result<HashMap<(usize, usize), HashOut<F>>> {
+        let leaves: Vec<_> = self
+            .pages
+            .iter()
+            .map(|(index, page)| (*index, page_hash(page)))
+            .collect();
+        let mut nodes: HashMap<_, _> = leaves
+            .iter()
+            .map(|&(index, hash)| ((0, index), hash))
+            .collect();
+        let mut siblings = self.siblings.iter();
+        merkle_root(leaves, |level, index| {
+            let hash = *siblings.next().context("page proof has too few siblings")?;
+            nodes.insert((level, index), hash);
+            Ok(hash)
+        })?;
+        for level in 0..TREE_DEPTH {
+            for (index, _) in &self.pages {
+                let i = index >> level;
+                let parent =
+                    PoseidonHash::two_to_one(nodes[&(level, i & !1)], nodes[&(level, i | 1)]);
+                nodes.insert((level + 1, i >> 1), parent);
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Returns the pages with the value of each address of `final_mem`
+    /// overwritten. Every address must belong to one of the pages.
+    fn write(&self, final_mem: &FinalMem) -> Result<Vec<(usize, Vec<u32>)>> {
+        let mut pages = self.pages.clone();
+        for &(seg, virt, val) in final_mem.cells() {
+            let (index, offset) = page_of(seg, virt);
+            let i = pages
+                .binary_search_by_key(&index, |&(i, _)| i)
+                .map_err(|_| anyhow!("address {virt:#x} of {seg:?} was not paged in"))?;
+            let cell = pages[i]
+                .1
+                .get_mut(offset)
+                .with_context(|| format!("address {virt:#x} of {seg:?} is out of bounds"))?;
+            *cell = val;
+        }
+        Ok(pages)
+    }
+
+    /// Computes the root of the Merkle tree from `pages`, which take the
+    /// place of the proven pages.
+    fn root(&self, pages: &[(usize, Vec<u32>)]) -> Result<HashOut<F>> {
+        ensure!(
+            pages.array_windows::<2>().all(|[(i, _), (j, _)]| i < j),
+            "pages are not ordered by index"
+        );
+        for (index, page) in pages {
+            ensure!(*index <= REG_PAGE, "page index {index} is out of bounds");
+            ensure!(
+                page.len() == page_len(*index),
+                "page {index} has {} values instead of {}",
+                page.len(),
+                page_len(*index),
+            );
+            ensure!(
+                *index == REG_PAGE || page.iter().all(|&val| val <= u8::MAX.into()),
+                "page {index} of main memory holds a value which is not a byte"
+            );
+        }
+
+        let leaves = pages
+            .iter()
+            .map(|(index, page)| (*index, page_hash(page)))
+            .collect();
+        let mut siblings = self.siblings.iter();
+        let root = merkle_root(leaves, |_, _| {
+            siblings
+                .next()
+                .copied()
+                .context("page proof has too few siblings")
+        })?;
+        ensure!(
+            siblings.next().is_none(),
+            "page proof has too many siblings"
+        );
+        Ok(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hashbrown::HashMap;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::hash::poseidon::PoseidonHash;
+    use plonky2::plonk::config::Hasher;
+
+    use super::{page_hash, PagedMemory, PAGE_BYTES, REG_PAGE};
+    use crate::mem::trace::{MemAddress, MemKind, MemOp};
+    use crate::mem::Segment;
+    use crate::memfinal::trace::FinalMem;
+    use crate::vm::machine::N_REGS;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_page_in_out() {
+        let image: HashMap<u32, u8> = [(0, 1), (PAGE_BYTES as u32 + 3, 2), (0xffff_fff0, 3)].into();
+        let mut regs = [0; N_REGS];
+        regs[2] = 0x8000;
+        let memory = PagedMemory::new(&image, &regs);
+        let root = memory.root::<F>();
+
+        // an empty page is proven like any other
+        let proof = memory.prove::<F>([0, 1, 2, REG_PAGE]);
+        let mem_image = proof.page_in(root).unwrap();
+        assert_eq!(mem_image.len(), 3);
+        assert!(proof.page_in(PagedMemory::default().root()).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.pages[0].1[3] = 5;
+        assert!(bad_proof.page_in(root).is_err());
+
+        // paging out gives the root of the updated memory
+        let write = |seg, virt, val| MemOp {
+            on: true,
+            time: 1,
+            kind: MemKind::Write,
+            adr: MemAddress::new(seg, virt),
+            val,
+        };
+        let final_mem = FinalMem::from_ops(vec![
+            write(Segment::Main, PAGE_BYTES + 3, 0),
+            write(Segment::Main, 2 * PAGE_BYTES, 4),
+            write(Segment::Reg, 1, 7),
+        ]);
+        regs[1] = 7;
+        let image: HashMap<u32, u8> = [(0, 1), (2 * PAGE_BYTES as u32, 4), (0xffff_fff0, 3)].into();
+        let root = PagedMemory::new(&image, &regs).root::<F>();
+        assert_eq!(proof.page_out(&final_mem).unwrap(), root);
+
+        // as does updating the pages one at a time along their paths
+        let path_root = |index: usize, page: &[u32], siblings: &[HashOut<F>]| {
+            let leaf = page_hash(page);
+            siblings
+                .iter()
+                .zip(0..)
+                .fold(leaf, |node, (&sibling, level)| {
+                    if (index >> level) & 1 == 0 {
+                        PoseidonHash::two_to_one(node, sibling)
+                    } else {
+                        PoseidonHash::two_to_one(sibling, node)
+                    }
+                })
+        };
+        let mut update_root = memory.root::<F>();
+        for update in proof.updates(&final_mem).unwrap() {
+            assert_eq!(
+                path_root(update.index, &update.start, &update.siblings),
+                update_root
+            );
+            update_root = path_root(update.index, &update.end, &update.siblings);
+        }
+        assert_eq!(update_root, root);
+
+        // every address must have been paged in
+        let proof = memory.prove::<F>([1, REG_PAGE]);
+        let final_mem = FinalMem::from_ops(vec![write(Segment::Main, 0, 2)]);
+        assert!(proof.page_out(&final_mem).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use core::cmp::max;
 use core::iter::repeat;
 
 use hashbrown::HashMap;
@@ -82,18 +83,20 @@ impl MemOp {
     }
 }
 
-pub(crate) fn gen_trace<F: RichField>(ops: Vec<MemOp>) -> Vec<PolynomialValues<F>> {
-    let trace = gen_trace_rows(ops);
+pub(crate) fn gen_trace<F: RichField>(
+    ops: Vec<MemOp>,
+    min_rows: usize,
+) -> Vec<PolynomialValues<F>> {
+    let trace = gen_trace_rows(ops, min_rows);
     let trace_rows: Vec<_> = trace.iter().map(MemCols::to_vec).collect();
     let trace_cols = transpose(&trace_rows);
     trace_cols.into_iter().map(PolynomialValues::new).collect()
 }
 
-pub(crate) fn gen_trace_rows<F: RichField>(mut ops: Vec<MemOp>) -> Vec<MemCols<F>> {
+pub(crate) fn gen_trace_rows<F: RichField>(ops: Vec<MemOp>, min_rows: usize) -> Vec<MemCols<F>> {
     // fill range check gaps, then re-sort and add padding rows
-    fill_rc_gaps(&mut ops);
-    ops.sort_by_key(MemOp::sort_key);
-    pad(&mut ops);
+    let mut ops = sorted_ops(ops);
+    pad(&mut ops, min_rows);
 
     let mut rc_freq = HashMap::default();
     let mut rows: Vec<_> = ops.into_par_iter().map(MemOp::into_row::<F>).collect();
@@ -143,13 +146,25 @@ fn trace<F: RichField>(
             let freq = map.entry(nv.adr_virt).or_insert(0);
             *freq += 1;
         }
+    } else {
+        // the last row ends the accesses to its address
+        lv.f_virt_diff = F::ONE;
     }
 
     let freq = map.entry(lv.range_check.val).or_insert(0);
     *freq += 1;
 }
 
-fn pad(ops: &mut Vec<MemOp>) {
+/// Returns the memory ops along with the dummy reads filling range check gaps,
+/// sorted by address and time. These are the rows of the memory table before
+/// padding.
+pub(crate) fn sorted_ops(mut ops: Vec<MemOp>) -> Vec<MemOp> {
+    fill_rc_gaps(&mut ops);
+    ops.sort_by_key(MemOp::sort_key);
+    ops
+}
+
+fn pad(ops: &mut Vec<MemOp>, min_rows: usize) {
     let last_op = *ops.last().unwrap();
     let pad_op = MemOp {
         on: false,
@@ -157,7 +172,7 @@ fn pad(ops: &mut Vec<MemOp>) {
         ..last_op
     };
     let len = ops.len();
-    let padded_len = len.next_power_of_two();
+    let padded_len = max(len, min_rows).next_power_of_two();
     println!("padding memory ops from {} to {} rows.", len, padded_len);
     ops.extend(repeat(pad_op).take(padded_len - len));
 }
@@ -0,0 +1,35 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+use rizzo_derive::{Columns, DerefColumns};
+
+/// The value of each struct field is the index of the corresponding column.
+pub(crate) const MEMFINAL_COL_MAP: MemFinalCols<usize> = make_col_map();
+/// The number of field elements in `MemFinalCols`.
+pub(crate) const N_MEMFINAL_COLS: usize = core::mem::size_of::<MemFinalCols<u8>>();
+
+/// Columns holding the last value of a single memory address. Their values
+/// are fixed by the final memory, and are checked by the verifier.
+#[repr(C)]
+#[derive(Columns, DerefColumns, Clone, Debug)]
+pub(crate) struct MemFinalCols<T> {
+    /// 1 if this row holds an address, 0 if it's a padding row.
+    pub f_final: T,
+    /// Address segment.
+    pub adr_seg: T,
+    /// Virtual address.
+    pub adr_virt: T,
+    /// The value of the address at the end of execution.
+    pub val: T,
+}
+
+impl<T: Copy> MemFinalCols<T> {
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        Borrow::<[T; N_MEMFINAL_COLS]>::borrow(self).to_vec()
+    }
+}
+
+const fn make_col_map() -> MemFinalCols<usize> {
+    let arr = crate::util::indices_arr::<N_MEMFINAL_COLS>();
+    unsafe { core::mem::transmute::<[usize; N_MEMFINAL_COLS], MemFinalCols<usize>>(arr) }
+}
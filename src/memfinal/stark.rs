@@ -0,0 +1,151 @@
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::cross_table_lookup::TableWithColumns;
+use starky::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use starky::lookup::{Column, Filter};
+use starky::stark::Stark;
+
+use crate::memfinal::columns::{MemFinalCols, MEMFINAL_COL_MAP, N_MEMFINAL_COLS};
+use crate::stark::Table;
+
+/// Looked table for the last value of every address of the memory table.
+pub(crate) fn ctl_looked<F: Field>() -> TableWithColumns<F> {
+    let cols = Column::singles([
+        MEMFINAL_COL_MAP.adr_seg,
+        MEMFINAL_COL_MAP.adr_virt,
+        MEMFINAL_COL_MAP.val,
+    ])
+    .collect();
+
+    let filter = Filter::new_simple(Column::single(MEMFINAL_COL_MAP.f_final));
+    TableWithColumns::new(Table::MemFinal as usize, cols, filter)
+}
+
+fn eval_all<P: PackedField>(lv: &MemFinalCols<P>, cc: &mut ConstraintConsumer<P>) {
+    // the verifier checks every column against the final memory, so only
+    // the CTL filter is left to constrain
+    cc.constraint(lv.f_final * (P::ONES - lv.f_final));
+}
+
+fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &MemFinalCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = cb.one_extension();
+
+    let f_padding = cb.sub_extension(one, lv.f_final);
+    let cs = cb.mul_extension(lv.f_final, f_padding);
+    cc.constraint(cb, cs);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemFinalStark<F, const D: usize> {
+    _unused: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemFinalStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, N_MEMFINAL_COLS, 0>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, N_MEMFINAL_COLS, 0>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        frame: &Self::EvaluationFrame<FE, P, D2>,
+        cc: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local: &[P; N_MEMFINAL_COLS] = frame.get_local_values().try_into().unwrap();
+        let local: &MemFinalCols<P> = local.borrow();
+        eval_all(local, cc)
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        frame: &Self::EvaluationFrameTarget,
+        cc: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local: &[ExtensionTarget<D>; N_MEMFINAL_COLS] =
+            frame.get_local_values().try_into().unwrap();
+        let local: &MemFinalCols<ExtensionTarget<D>> = local.borrow();
+        eval_all_circuit(cb, local, cc);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn requires_ctls(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::MemFinalStark;
+    use crate::memfinal::trace::gen_trace;
+    use crate::vm::Machine;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = MemFinalStark<F, D>;
+
+    #[test]
+    fn test_stark_degree() {
+        let stark: S = Default::default();
+        test_stark_low_degree(stark).unwrap();
+    }
+
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
+
+    #[test]
+    fn test_gen_eval() {
+        crate::util::impl_stark_no_ctls!(MemFinalStark);
+        type S = MemFinalStarkNoCtls<F, D>;
+        const CFG: StarkConfig = StarkConfig::standard_fast_config();
+
+        let stark: S = Default::default();
+        let program = [
+            0x40000093, // addi x1, x0, 1024
+            0x0010a023, // sw x1, 0(x1)
+            0x05d00893, // addi a7, x0, 93
+            0x00000073, // ecall
+        ];
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let final_mem = log.final_mem();
+        assert!(!final_mem.is_empty());
+
+        let min_rows = CFG.fri_config.num_cap_elements();
+        let trace = gen_trace::<F>(&final_mem, min_rows);
+        let mut t = TimingTree::default();
+        let proof = prove::<F, C, S, D>(stark, &CFG, trace, &[], &mut t).unwrap();
+        verify_stark_proof(stark, proof, &CFG).unwrap();
+    }
+}
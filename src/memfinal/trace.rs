@@ -0,0 +1,117 @@
+use core::cmp::max;
+
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::util::transpose;
+
+use crate::mem::trace::{sorted_ops, MemOp};
+use crate::mem::Segment;
+use crate::memfinal::columns::MemFinalCols;
+use crate::util::hash_rows;
+
+/// The contents of memory at the end of execution, ordered by address.
+///
+/// This holds the last value of every address of the memory table except
+/// register `x0`, including addresses which were only read to fill range
+/// check gaps, whose value is 0.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FinalMem {
+    cells: Vec<(Segment, u32, u32)>,
+}
+
+impl FinalMem {
+    /// Collects the last value of every address accessed by `ops`.
+    pub(crate) fn from_ops(ops: Vec<MemOp>) -> Self {
+        let cells = sorted_ops(ops)
+            .into_iter()
+            .filter(|op| !op.adr.is_reg0())
+            .chunk_by(|op| (op.adr.seg, op.adr.virt))
+            .into_iter()
+            .map(|((seg, virt), ops)| {
+                let last = ops.last().unwrap();
+                (seg, virt as u32, last.val)
+            })
+            .collect();
+        Self { cells }
+    }
+
+    /// Returns every address and its final value.
+    pub(crate) fn cells(&self) -> &[(Segment, u32, u32)] {
+        &self.cells
+    }
+
+    /// Returns the number of addresses.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns true if no address was accessed.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns the final value of the address `virt` in segment `seg`, or
+    /// `None` if it is not in the memory table.
+    pub fn get(&self, seg: Segment, virt: u32) -> Option<u32> {
+        let i = self
+            .cells
+            .binary_search_by_key(&(seg, virt), |&(seg, virt, _)| (seg, virt))
+            .ok()?;
+        Some(self.cells[i].2)
+    }
+
+    /// Returns a hash of every address and its final value.
+    pub fn hash<F: RichField>(&self) -> HashOut<F> {
+        hash_rows(self.rows::<F>(self.len()).map(|row| row[1..].to_vec()))
+    }
+
+    /// Returns the addresses as rows of the memfinal table, followed by
+    /// padding rows up to a total of `n_rows` rows.
+    pub(crate) fn rows<F: Field>(
+        &self,
+        n_rows: usize,
+    ) -> impl Iterator<Item = MemFinalCols<F>> + '_ {
+        let rows = self.cells.iter().map(|&(seg, virt, val)| MemFinalCols {
+            f_final: F::ONE,
+            adr_seg: F::from_canonical_usize(seg as usize),
+            adr_virt: F::from_canonical_u32(virt),
+            val: F::from_canonical_u32(val),
+        });
+        let n_padding = n_rows.saturating_sub(self.len());
+        rows.chain((0..n_padding).map(|_| MemFinalCols::default()))
+    }
+
+    /// Returns the polynomials of every column of a memfinal table with
+    /// `2^degree_bits` rows.
+    pub(crate) fn final_polys<F: Field>(
+        &self,
+        degree_bits: usize,
+    ) -> Result<Vec<PolynomialCoeffs<F>>> {
+        let n_rows = 1 << degree_bits;
+        ensure!(
+            self.len() <= n_rows,
+            "final memory has {} addresses but the memfinal table has {n_rows} rows",
+            self.len(),
+        );
+        let rows: Vec<_> = self.rows(n_rows).map(|row| row.to_vec()).collect();
+        let cols = transpose(&rows);
+        Ok(cols
+            .into_iter()
+            .map(|col| PolynomialValues::new(col).ifft())
+            .collect())
+    }
+}
+
+/// Generates the memfinal table holding `final_mem`.
+pub(crate) fn gen_trace<F: Field>(
+    final_mem: &FinalMem,
+    min_rows: usize,
+) -> Vec<PolynomialValues<F>> {
+    let n_rows = max(final_mem.len(), min_rows).next_power_of_two();
+    let trace_rows: Vec<_> = final_mem.rows(n_rows).map(|row| row.to_vec()).collect();
+    let trace_cols = transpose(&trace_rows);
+    trace_cols.into_iter().map(PolynomialValues::new).collect()
+}
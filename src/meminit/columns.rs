@@ -0,0 +1,35 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+use rizzo_derive::{Columns, DerefColumns};
+
+/// The value of each struct field is the index of the corresponding column.
+pub(crate) const MEMINIT_COL_MAP: MemInitCols<usize> = make_col_map();
+/// The number of field elements in `MemInitCols`.
+pub(crate) const N_MEMINIT_COLS: usize = core::mem::size_of::<MemInitCols<u8>>();
+
+/// Columns holding a single address of the initial memory image. Their values
+/// are fixed by the image, and are checked by the verifier.
+#[repr(C)]
+#[derive(Columns, DerefColumns, Clone, Debug)]
+pub(crate) struct MemInitCols<T> {
+    /// 1 if this row holds an address of the image, 0 if it's a padding row.
+    pub f_init: T,
+    /// Address segment.
+    pub adr_seg: T,
+    /// Virtual address.
+    pub adr_virt: T,
+    /// The initial value of the address.
+    pub val: T,
+}
+
+impl<T: Copy> MemInitCols<T> {
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        Borrow::<[T; N_MEMINIT_COLS]>::borrow(self).to_vec()
+    }
+}
+
+const fn make_col_map() -> MemInitCols<usize> {
+    let arr = crate::util::indices_arr::<N_MEMINIT_COLS>();
+    unsafe { core::mem::transmute::<[usize; N_MEMINIT_COLS], MemInitCols<usize>>(arr) }
+}
@@ -0,0 +1,157 @@
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::cross_table_lookup::TableWithColumns;
+use starky::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use starky::lookup::{Column, Filter};
+use starky::stark::Stark;
+
+use crate::mem::INIT_TIME;
+use crate::meminit::columns::{MemInitCols, MEMINIT_COL_MAP, N_MEMINIT_COLS};
+use crate::stark::Table;
+
+/// Looking table meminit->memory, writing each value of the image before any
+/// other memory access.
+pub(crate) fn ctl_looking_mem<F: Field>() -> TableWithColumns<F> {
+    let cols = vec![
+        Column::constant(F::ONE),
+        Column::single(MEMINIT_COL_MAP.adr_seg),
+        Column::single(MEMINIT_COL_MAP.adr_virt),
+        Column::single(MEMINIT_COL_MAP.val),
+        Column::constant(F::from_canonical_usize(INIT_TIME)),
+    ];
+
+    let filter = Filter::new_simple(Column::single(MEMINIT_COL_MAP.f_init));
+    TableWithColumns::new(Table::MemInit as usize, cols, filter)
+}
+
+fn eval_all<P: PackedField>(lv: &MemInitCols<P>, cc: &mut ConstraintConsumer<P>) {
+    // every column is checked by the verifier, so this only guards the
+    // filter of the memory CTL
+    cc.constraint(lv.f_init * (P::ONES - lv.f_init));
+}
+
+fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &MemInitCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = cb.one_extension();
+
+    let f_padding = cb.sub_extension(one, lv.f_init);
+    let cs = cb.mul_extension(lv.f_init, f_padding);
+    cc.constraint(cb, cs);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemInitStark<F, const D: usize> {
+    _unused: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemInitStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, N_MEMINIT_COLS, 0>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, N_MEMINIT_COLS, 0>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        frame: &Self::EvaluationFrame<FE, P, D2>,
+        cc: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local: &[P; N_MEMINIT_COLS] = frame.get_local_values().try_into().unwrap();
+        let local: &MemInitCols<P> = local.borrow();
+        eval_all(local, cc)
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        frame: &Self::EvaluationFrameTarget,
+        cc: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local: &[ExtensionTarget<D>; N_MEMINIT_COLS] =
+            frame.get_local_values().try_into().unwrap();
+        let local: &MemInitCols<ExtensionTarget<D>> = local.borrow();
+        eval_all_circuit(cb, local, cc);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn requires_ctls(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::MemInitStark;
+    use crate::meminit::trace::gen_trace;
+    use crate::vm::Machine;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = MemInitStark<F, D>;
+
+    #[test]
+    fn test_stark_degree() {
+        let stark: S = Default::default();
+        test_stark_low_degree(stark).unwrap();
+    }
+
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
+
+    #[test]
+    fn test_gen_eval() {
+        crate::util::impl_stark_no_ctls!(MemInitStark);
+        type S = MemInitStarkNoCtls<F, D>;
+        const CFG: StarkConfig = StarkConfig::standard_fast_config();
+
+        let stark: S = Default::default();
+        let program = [
+            0x00300093, // addi x1, x0, 3
+            0xfff08093, // loop: addi x1, x1, -1
+            0xfe009ee3, // bne x1, x0, loop
+            0x05d00893, // addi a7, x0, 93
+            0x00000073, // ecall
+        ];
+        let log = Machine::from_words(0x100, &program).run(100).unwrap();
+        // zero bytes are left out of the image
+        let image = log.mem_image();
+        let bytes = program.iter().flat_map(|word| word.to_le_bytes());
+        assert_eq!(image.len(), bytes.filter(|&byte| byte != 0).count());
+
+        let min_rows = CFG.fri_config.num_cap_elements();
+        let trace = gen_trace::<F>(&image, min_rows);
+        let mut t = TimingTree::default();
+        let proof = prove::<F, C, S, D>(stark, &CFG, trace, &[], &mut t).unwrap();
+        verify_stark_proof(stark, proof, &CFG).unwrap();
+    }
+}
@@ -0,0 +1,120 @@
+use core::cmp::max;
+
+use anyhow::{ensure, Result};
+use hashbrown::HashMap;
+use itertools::Itertools;
+use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::util::transpose;
+
+use crate::mem::trace::{MemAddress, MemKind, MemOp};
+use crate::mem::{Segment, INIT_TIME};
+use crate::meminit::columns::MemInitCols;
+use crate::util::hash_rows;
+use crate::vm::machine::N_REGS;
+
+/// The initial contents of the registers and main memory, ordered by
+/// address. Only non-zero values are held, as the rest of memory is
+/// initialized to 0.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MemImage {
+    cells: Vec<(Segment, u32, u32)>,
+}
+
+impl MemImage {
+    /// Collects the non-zero registers of `regs` and bytes of `image`.
+    pub(crate) fn new(image: &HashMap<u32, u8>, regs: &[u32; N_REGS]) -> Self {
+        let regs = (0..).zip(regs).map(|(reg, &val)| (Segment::Reg, reg, val));
+        let bytes = image
+            .iter()
+            .map(|(&adr, &val)| (Segment::Main, adr, val.into()));
+        let cells = regs
+            .chain(bytes)
+            .filter(|&(_, _, val)| val != 0)
+            .sorted()
+            .collect();
+        Self { cells }
+    }
+
+    /// Returns the bytes of main memory held by the image.
+    pub(crate) fn main_bytes(&self) -> HashMap<u32, u8> {
+        self.cells
+            .iter()
+            .filter(|&&(seg, _, _)| seg == Segment::Main)
+            .map(|&(_, virt, val)| (virt, val as u8))
+            .collect()
+    }
+
+    /// Returns the number of initialized addresses.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns true if no address is initialized.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns a hash of every initialized address and its value,
+    /// identifying the image.
+    pub fn hash<F: RichField>(&self) -> HashOut<F> {
+        hash_rows(self.rows::<F>(self.len()).map(|row| row[1..].to_vec()))
+    }
+
+    /// Returns the writes initializing memory, which precede every other
+    /// memory access.
+    pub(crate) fn mem_ops(&self) -> impl Iterator<Item = MemOp> + '_ {
+        self.cells.iter().map(|&(seg, virt, val)| MemOp {
+            on: true,
+            time: INIT_TIME,
+            kind: MemKind::Write,
+            adr: MemAddress::new(seg, virt as usize),
+            val,
+        })
+    }
+
+    /// Returns the addresses as rows of the meminit table, followed by
+    /// padding rows up to a total of `n_rows` rows.
+    pub(crate) fn rows<F: Field>(
+        &self,
+        n_rows: usize,
+    ) -> impl Iterator<Item = MemInitCols<F>> + '_ {
+        let rows = self.cells.iter().map(|&(seg, virt, val)| MemInitCols {
+            f_init: F::ONE,
+            adr_seg: F::from_canonical_usize(seg as usize),
+            adr_virt: F::from_canonical_u32(virt),
+            val: F::from_canonical_u32(val),
+        });
+        let n_padding = n_rows.saturating_sub(self.len());
+        rows.chain((0..n_padding).map(|_| MemInitCols::default()))
+    }
+
+    /// Returns the polynomials of every column of a meminit table with
+    /// `2^degree_bits` rows.
+    pub(crate) fn image_polys<F: Field>(
+        &self,
+        degree_bits: usize,
+    ) -> Result<Vec<PolynomialCoeffs<F>>> {
+        let n_rows = 1 << degree_bits;
+        ensure!(
+            self.len() <= n_rows,
+            "image has {} addresses but the meminit table has {n_rows} rows",
+            self.len(),
+        );
+        let rows: Vec<_> = self.rows(n_rows).map(|row| row.to_vec()).collect();
+        let cols = transpose(&rows);
+        Ok(cols
+            .into_iter()
+            .map(|col| PolynomialValues::new(col).ifft())
+            .collect())
+    }
+}
+
+/// Generates the meminit table holding `image`.
+pub(crate) fn gen_trace<F: Field>(image: &MemImage, min_rows: usize) -> Vec<PolynomialValues<F>> {
+    let n_rows = max(image.len(), min_rows).next_power_of_two();
+    let trace_rows: Vec<_> = image.rows(n_rows).map(|row| row.to_vec()).collect();
+    let trace_cols = transpose(&trace_rows);
+    trace_cols.into_iter().map(PolynomialValues::new).collect()
+}
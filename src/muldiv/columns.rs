@@ -0,0 +1,118 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+use rizzo_derive::{Columns, DerefColumns};
+
+use crate::bytes::columns::RangeCheck;
+use crate::bytes::BYTES_WORD;
+
+/// The value of each struct field is the index of the corresponding column.
+pub(crate) const MULDIV_COL_MAP: MulDivCols<usize> = make_col_map();
+/// The value of each struct field is the index of the corresponding column.
+pub(crate) const OP_COL_MAP: OpCols<usize> = make_op_col_map();
+/// The number of field elements in `MulDivCols`.
+pub(crate) const N_MULDIV_COLS: usize = core::mem::size_of::<MulDivCols<u8>>();
+/// The number of field elements in `OpCols`.
+pub(crate) const N_OP_COLS: usize = core::mem::size_of::<OpCols<u8>>();
+
+/// The number of bytes in the 64-bit product.
+pub(crate) const BYTES_DWORD: usize = 2 * BYTES_WORD;
+
+/// Flag columns for the operation to perform.
+#[repr(C)]
+#[derive(DerefColumns, Clone, Debug, Default)]
+pub(crate) struct OpCols<T> {
+    /// Low word of the product.
+    pub f_mul: T,
+    /// High word of the signed product.
+    pub f_mulh: T,
+    /// High word of the product of a signed and an unsigned operand.
+    pub f_mulhsu: T,
+    /// High word of the unsigned product.
+    pub f_mulhu: T,
+    /// Signed division.
+    pub f_div: T,
+    /// Unsigned division.
+    pub f_divu: T,
+    /// Signed remainder.
+    pub f_rem: T,
+    /// Unsigned remainder.
+    pub f_remu: T,
+}
+
+/// Columns for the multiplication and division stark.
+///
+/// Every operation is checked with the single relation `x * y + z = prod`,
+/// where `x`, `y` and `z` are extended to 64 bits according to the
+/// signedness of the operation. Multiplications set `x = in0`, `y = in1` and
+/// `z = 0`. Divisions set `x` to the quotient, `y = in1` and `z` to the
+/// remainder, and check that `prod` is the extended dividend `in0`.
+#[repr(C)]
+#[derive(Columns, DerefColumns, Clone, Debug)]
+pub(crate) struct MulDivCols<T> {
+    /// The operation to perform.
+    pub op: OpCols<T>,
+    /// First operand.
+    pub in0: T,
+    /// Output.
+    pub out: T,
+    /// LE bytes of the first factor.
+    pub x: [T; BYTES_WORD],
+    /// LE bytes of the second factor, which is always the second operand.
+    pub y: [T; BYTES_WORD],
+    /// LE bytes of the addend.
+    pub z: [T; BYTES_WORD],
+    /// LE bytes of `x * y + z`, truncated to 64 bits.
+    pub prod: [T; BYTES_DWORD],
+    /// The carry out of each byte of `prod`, as two LE bytes.
+    pub carry: [[T; 2]; BYTES_DWORD],
+    /// The most significant bit of `x`.
+    pub x_msb: T,
+    /// The most significant bit of `y`.
+    pub y_msb: T,
+    /// The most significant bit of `z`.
+    pub z_msb: T,
+    /// The most significant bit of the low word of `prod`.
+    pub prod_msb: T,
+    /// 1 if `x` is signed and negative.
+    pub x_neg: T,
+    /// 1 if `y` is signed and negative.
+    pub y_neg: T,
+    /// 1 if `z` is signed and negative.
+    pub z_neg: T,
+    /// 1 if the low word of `prod` is signed and negative.
+    pub prod_neg: T,
+    /// 1 if `y` is zero.
+    pub f_y_zero: T,
+    /// The inverse of the sum of the bytes of `y`, if nonzero.
+    pub y_inv: T,
+    /// 1 for the signed division of `-2^31` by `-1`.
+    pub f_overflow: T,
+    /// Auxiliary column used to compute `f_overflow`.
+    pub overflow_inv: T,
+    /// The absolute value of `y`.
+    pub y_abs: T,
+    /// The absolute value of `z`.
+    pub z_abs: T,
+    /// LE bytes of `y_abs - z_abs - 1`. Used to check that the remainder is
+    /// smaller than the divisor.
+    pub rem_diff: [T; BYTES_WORD],
+    /// Range check columns.
+    pub range_check: RangeCheck<T>,
+}
+
+impl<T: Copy> MulDivCols<T> {
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        Borrow::<[T; N_MULDIV_COLS]>::borrow(self).to_vec()
+    }
+}
+
+const fn make_col_map() -> MulDivCols<usize> {
+    let arr = crate::util::indices_arr::<N_MULDIV_COLS>();
+    unsafe { core::mem::transmute::<[usize; N_MULDIV_COLS], MulDivCols<usize>>(arr) }
+}
+
+const fn make_op_col_map() -> OpCols<usize> {
+    let arr = crate::util::indices_arr::<N_OP_COLS>();
+    unsafe { core::mem::transmute::<[usize; N_OP_COLS], OpCols<usize>>(arr) }
+}
@@ -0,0 +1,167 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+
+use crate::bytes::BYTES_WORD;
+use crate::muldiv::columns::MulDivCols;
+use crate::util::{felt_from_le_bytes, felt_from_le_bytes_circuit};
+
+/// The value of the sign bit of the most significant byte of a word.
+const SIGN_BIT_BYTE: u32 = 1 << 7;
+
+/// Returns `|word|`, given the flag `neg` set if `word` is negative.
+fn abs<P: PackedField>(word: P, neg: P) -> P {
+    let two_pow_32 = P::Scalar::from_canonical_u64(1 << 32);
+    word + neg * (P::ONES * two_pow_32 - word * P::Scalar::TWO)
+}
+
+pub(crate) fn eval<P: PackedField>(lv: &MulDivCols<P>, cc: &mut ConstraintConsumer<P>) {
+    let op = &lv.op;
+    let f_div_ops = op.f_div + op.f_divu + op.f_rem + op.f_remu;
+    let f_signed_div = op.f_div + op.f_rem;
+    let ff = P::Scalar::from_canonical_u8(u8::MAX);
+
+    // f_y_zero is 1 exactly when the divisor is zero. The sum of its bytes
+    // is small, so it's zero exactly when all bytes are.
+    let y_sum: P = lv.y.into_iter().sum();
+    let f_y_nonzero = y_sum * lv.y_inv;
+    cc.constraint(f_div_ops * (P::ONES - f_y_nonzero - lv.f_y_zero));
+    cc.constraint(y_sum * lv.f_y_zero);
+
+    // division by zero gives a quotient with all bits set
+    for x in lv.x {
+        cc.constraint(f_div_ops * lv.f_y_zero * (x - ff));
+    }
+
+    // f_overflow is 1 exactly when dividing -2^31 by -1 with a signed
+    // division. Every term of the sum below is nonnegative, and all are zero
+    // in that case only.
+    let sign_bit_byte = P::Scalar::from_canonical_u32(SIGN_BIT_BYTE);
+    let dividend_high = lv.prod[BYTES_WORD - 1] - lv.prod_msb * sign_bit_byte;
+    let dividend_sum: P = lv.prod[..BYTES_WORD - 1].iter().copied().sum();
+    let divisor_sum: P = lv.y.into_iter().map(|y| P::ONES * ff - y).sum();
+    let overflow_sum = dividend_sum + dividend_high + (P::ONES - lv.prod_msb) + divisor_sum;
+    let f_not_overflow = overflow_sum * lv.overflow_inv;
+    cc.constraint(lv.f_overflow - f_signed_div * (P::ONES - f_not_overflow));
+    cc.constraint(overflow_sum * lv.f_overflow);
+
+    // prod is the extended dividend, except on overflow where the quotient
+    // wraps around
+    let f_check_high = f_div_ops * (P::ONES - lv.f_overflow);
+    for &byte in &lv.prod[BYTES_WORD..] {
+        cc.constraint(f_check_high * (byte - lv.prod_neg * ff));
+    }
+
+    // a nonzero remainder has the sign of the dividend
+    let z_word = felt_from_le_bytes(lv.z);
+    cc.constraint(lv.z_neg * (P::ONES - lv.prod_neg));
+    cc.constraint(lv.prod_neg * (P::ONES - lv.z_neg) * z_word);
+
+    // |remainder| < |divisor|, unless dividing by zero
+    let y_word = felt_from_le_bytes(lv.y);
+    let rem_diff = felt_from_le_bytes(lv.rem_diff);
+    cc.constraint(lv.y_abs - abs(y_word, lv.y_neg));
+    cc.constraint(lv.z_abs - abs(z_word, lv.z_neg));
+    let f_check_rem = f_div_ops * (P::ONES - lv.f_y_zero);
+    cc.constraint(f_check_rem * (lv.y_abs - lv.z_abs - P::ONES - rem_diff));
+}
+
+/// Circuit version of [`abs`].
+fn abs_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    word: ExtensionTarget<D>,
+    neg: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    let two_pow_32 = cb.constant_extension(F::Extension::from_canonical_u64(1 << 32));
+    let t = cb.mul_const_add_extension(-F::TWO, word, two_pow_32);
+    cb.mul_add_extension(neg, t, word)
+}
+
+pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &MulDivCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = cb.one_extension();
+    let op = &lv.op;
+    let f_div_ops = cb.add_many_extension([op.f_div, op.f_divu, op.f_rem, op.f_remu]);
+    let f_signed_div = cb.add_extension(op.f_div, op.f_rem);
+    let ff = F::from_canonical_u8(u8::MAX);
+
+    // f_y_zero is 1 exactly when the divisor is zero
+    let y_sum = cb.add_many_extension(lv.y);
+    let f_y_nonzero = cb.mul_extension(y_sum, lv.y_inv);
+    let t = cb.sub_extension(one, f_y_nonzero);
+    let t = cb.sub_extension(t, lv.f_y_zero);
+    let cs = cb.mul_extension(f_div_ops, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(y_sum, lv.f_y_zero);
+    cc.constraint(cb, cs);
+
+    // division by zero gives a quotient with all bits set
+    let f_div_zero = cb.mul_extension(f_div_ops, lv.f_y_zero);
+    for x in lv.x {
+        let t = cb.add_const_extension(x, -ff);
+        let cs = cb.mul_extension(f_div_zero, t);
+        cc.constraint(cb, cs);
+    }
+
+    // f_overflow is 1 exactly when dividing -2^31 by -1 with a signed
+    // division
+    let sign_bit_byte = F::from_canonical_u32(SIGN_BIT_BYTE);
+    let dividend_high =
+        cb.mul_const_add_extension(-sign_bit_byte, lv.prod_msb, lv.prod[BYTES_WORD - 1]);
+    let dividend_sum = cb.add_many_extension(lv.prod[..BYTES_WORD - 1].iter().copied());
+    let y_sum_neg = cb.mul_const_extension(F::NEG_ONE, y_sum);
+    let divisor_sum = cb.add_const_extension(y_sum_neg, ff * F::from_canonical_usize(BYTES_WORD));
+    let not_prod_msb = cb.sub_extension(one, lv.prod_msb);
+    let overflow_sum =
+        cb.add_many_extension([dividend_sum, dividend_high, not_prod_msb, divisor_sum]);
+    let f_not_overflow = cb.mul_extension(overflow_sum, lv.overflow_inv);
+    let t = cb.sub_extension(one, f_not_overflow);
+    let t = cb.mul_extension(f_signed_div, t);
+    let cs = cb.sub_extension(lv.f_overflow, t);
+    cc.constraint(cb, cs);
+    let cs = cb.mul_extension(overflow_sum, lv.f_overflow);
+    cc.constraint(cb, cs);
+
+    // prod is the extended dividend, except on overflow
+    let not_overflow = cb.sub_extension(one, lv.f_overflow);
+    let f_check_high = cb.mul_extension(f_div_ops, not_overflow);
+    for &byte in &lv.prod[BYTES_WORD..] {
+        let t = cb.mul_const_extension(ff, lv.prod_neg);
+        let t = cb.sub_extension(byte, t);
+        let cs = cb.mul_extension(f_check_high, t);
+        cc.constraint(cb, cs);
+    }
+
+    // a nonzero remainder has the sign of the dividend
+    let z_word = felt_from_le_bytes_circuit(cb, lv.z);
+    let t = cb.sub_extension(one, lv.prod_neg);
+    let cs = cb.mul_extension(lv.z_neg, t);
+    cc.constraint(cb, cs);
+    let t = cb.sub_extension(one, lv.z_neg);
+    let cs = cb.mul_many_extension([lv.prod_neg, t, z_word]);
+    cc.constraint(cb, cs);
+
+    // |remainder| < |divisor|, unless dividing by zero
+    let y_word = felt_from_le_bytes_circuit(cb, lv.y);
+    let rem_diff = felt_from_le_bytes_circuit(cb, lv.rem_diff);
+    let y_abs = abs_circuit(cb, y_word, lv.y_neg);
+    let cs = cb.sub_extension(lv.y_abs, y_abs);
+    cc.constraint(cb, cs);
+    let z_abs = abs_circuit(cb, z_word, lv.z_neg);
+    let cs = cb.sub_extension(lv.z_abs, z_abs);
+    cc.constraint(cb, cs);
+    let not_y_zero = cb.sub_extension(one, lv.f_y_zero);
+    let f_check_rem = cb.mul_extension(f_div_ops, not_y_zero);
+    let t = cb.sub_extension(lv.y_abs, lv.z_abs);
+    let t = cb.sub_extension(t, one);
+    let t = cb.sub_extension(t, rem_diff);
+    let cs = cb.mul_extension(f_check_rem, t);
+    cc.constraint(cb, cs);
+}
@@ -0,0 +1,6 @@
+pub mod columns;
+pub mod div;
+pub mod flags;
+pub mod mul;
+pub mod stark;
+pub mod trace;
@@ -0,0 +1,157 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+
+use crate::bytes::BYTES_WORD;
+use crate::muldiv::columns::{MulDivCols, BYTES_DWORD};
+use crate::util::{felt_from_le_bytes, felt_from_le_bytes_circuit};
+
+/// Returns the bytes of a word extended to 64 bits, where every extension
+/// byte is `0xff` if `neg` is 1 and `0x00` otherwise.
+fn extend<P: PackedField>(bytes: [P; BYTES_WORD], neg: P) -> [P; BYTES_DWORD] {
+    let ext_byte = neg * P::Scalar::from_canonical_u8(u8::MAX);
+    core::array::from_fn(|i| if i < BYTES_WORD { bytes[i] } else { ext_byte })
+}
+
+pub(crate) fn eval<P: PackedField>(lv: &MulDivCols<P>, cc: &mut ConstraintConsumer<P>) {
+    let op = &lv.op;
+    let f_mul_ops = op.f_mul + op.f_mulh + op.f_mulhsu + op.f_mulhu;
+    let f_div_ops = op.f_div + op.f_divu + op.f_rem + op.f_remu;
+    let f_signed_div = op.f_div + op.f_rem;
+
+    // sign bits in {0, 1}. The lookup checks that each is the most
+    // significant bit of its word.
+    for msb in [lv.x_msb, lv.y_msb, lv.z_msb, lv.prod_msb] {
+        cc.constraint(msb * (msb - P::ONES));
+    }
+
+    // a word is negative if it's interpreted as signed and its sign bit is set
+    let x_signed = op.f_mulh + op.f_mulhsu + f_signed_div;
+    let y_signed = op.f_mulh + f_signed_div;
+    cc.constraint(lv.x_neg - x_signed * lv.x_msb);
+    cc.constraint(lv.y_neg - y_signed * lv.y_msb);
+    cc.constraint(lv.z_neg - f_signed_div * lv.z_msb);
+    cc.constraint(lv.prod_neg - f_signed_div * lv.prod_msb);
+
+    // multiplications have no addend
+    for z in lv.z {
+        cc.constraint(f_mul_ops * z);
+    }
+
+    // x * y + z = prod, one byte at a time. All bytes and carries are range
+    // checked, so no term can overflow the field.
+    let x = extend(lv.x, lv.x_neg);
+    let y = extend(lv.y, lv.y_neg);
+    let z = extend(lv.z, lv.z_neg);
+    let base = P::Scalar::from_canonical_u32(1 << 8);
+    let carry = lv.carry.map(|[lo, hi]| lo + hi * base);
+    for k in 0..BYTES_DWORD {
+        let carry_in = if k > 0 { carry[k - 1] } else { P::ZEROS };
+        let sum: P = (0..=k).map(|i| x[i] * y[k - i]).sum();
+        cc.constraint(sum + z[k] + carry_in - lv.prod[k] - carry[k] * base);
+    }
+
+    // select the operand and the output from the words of the relation
+    let x_word = felt_from_le_bytes(lv.x);
+    let z_word = felt_from_le_bytes(lv.z);
+    let prod_lo = felt_from_le_bytes(lv.prod[..BYTES_WORD].iter().copied());
+    let prod_hi = felt_from_le_bytes(lv.prod[BYTES_WORD..].iter().copied());
+    cc.constraint(f_mul_ops * (lv.in0 - x_word));
+    cc.constraint(f_div_ops * (lv.in0 - prod_lo));
+    cc.constraint(op.f_mul * (lv.out - prod_lo));
+    cc.constraint((op.f_mulh + op.f_mulhsu + op.f_mulhu) * (lv.out - prod_hi));
+    cc.constraint((op.f_div + op.f_divu) * (lv.out - x_word));
+    cc.constraint((op.f_rem + op.f_remu) * (lv.out - z_word));
+}
+
+/// Circuit version of [`extend`].
+fn extend_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    bytes: [ExtensionTarget<D>; BYTES_WORD],
+    neg: ExtensionTarget<D>,
+) -> [ExtensionTarget<D>; BYTES_DWORD] {
+    let ext_byte = cb.mul_const_extension(F::from_canonical_u8(u8::MAX), neg);
+    core::array::from_fn(|i| if i < BYTES_WORD { bytes[i] } else { ext_byte })
+}
+
+pub(crate) fn eval_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &MulDivCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let op = &lv.op;
+    let f_mul_ops = cb.add_many_extension([op.f_mul, op.f_mulh, op.f_mulhsu, op.f_mulhu]);
+    let f_div_ops = cb.add_many_extension([op.f_div, op.f_divu, op.f_rem, op.f_remu]);
+    let f_signed_div = cb.add_extension(op.f_div, op.f_rem);
+
+    // sign bits in {0, 1}
+    for msb in [lv.x_msb, lv.y_msb, lv.z_msb, lv.prod_msb] {
+        let cs = cb.mul_sub_extension(msb, msb, msb);
+        cc.constraint(cb, cs);
+    }
+
+    // a word is negative if it's interpreted as signed and its sign bit is set
+    let x_signed = cb.add_many_extension([op.f_mulh, op.f_mulhsu, f_signed_div]);
+    let y_signed = cb.add_extension(op.f_mulh, f_signed_div);
+    for (neg, signed, msb) in [
+        (lv.x_neg, x_signed, lv.x_msb),
+        (lv.y_neg, y_signed, lv.y_msb),
+        (lv.z_neg, f_signed_div, lv.z_msb),
+        (lv.prod_neg, f_signed_div, lv.prod_msb),
+    ] {
+        let t = cb.mul_extension(signed, msb);
+        let cs = cb.sub_extension(neg, t);
+        cc.constraint(cb, cs);
+    }
+
+    // multiplications have no addend
+    for z in lv.z {
+        let cs = cb.mul_extension(f_mul_ops, z);
+        cc.constraint(cb, cs);
+    }
+
+    // x * y + z = prod, one byte at a time
+    let x = extend_circuit(cb, lv.x, lv.x_neg);
+    let y = extend_circuit(cb, lv.y, lv.y_neg);
+    let z = extend_circuit(cb, lv.z, lv.z_neg);
+    let base = F::from_canonical_u32(1 << 8);
+    let carry = lv
+        .carry
+        .map(|[lo, hi]| cb.mul_const_add_extension(base, hi, lo));
+    for k in 0..BYTES_DWORD {
+        let mut acc = cb.sub_extension(z[k], lv.prod[k]);
+        acc = cb.mul_const_add_extension(-base, carry[k], acc);
+        if k > 0 {
+            acc = cb.add_extension(acc, carry[k - 1]);
+        }
+        for i in 0..=k {
+            acc = cb.mul_add_extension(x[i], y[k - i], acc);
+        }
+        cc.constraint(cb, acc);
+    }
+
+    // select the operand and the output from the words of the relation
+    let x_word = felt_from_le_bytes_circuit(cb, lv.x);
+    let z_word = felt_from_le_bytes_circuit(cb, lv.z);
+    let prod_lo = felt_from_le_bytes_circuit(cb, lv.prod[..BYTES_WORD].iter().copied());
+    let prod_hi = felt_from_le_bytes_circuit(cb, lv.prod[BYTES_WORD..].iter().copied());
+    let f_mul_hi = cb.add_many_extension([op.f_mulh, op.f_mulhsu, op.f_mulhu]);
+    let f_quotient = cb.add_extension(op.f_div, op.f_divu);
+    let f_remainder = cb.add_extension(op.f_rem, op.f_remu);
+    for (flag, lhs, rhs) in [
+        (f_mul_ops, lv.in0, x_word),
+        (f_div_ops, lv.in0, prod_lo),
+        (op.f_mul, lv.out, prod_lo),
+        (f_mul_hi, lv.out, prod_hi),
+        (f_quotient, lv.out, x_word),
+        (f_remainder, lv.out, z_word),
+    ] {
+        let t = cb.sub_extension(lhs, rhs);
+        let cs = cb.mul_extension(flag, t);
+        cc.constraint(cb, cs);
+    }
+}
@@ -0,0 +1,246 @@
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::cross_table_lookup::TableWithColumns;
+use starky::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use starky::lookup::{Column, Filter, Lookup};
+use starky::stark::Stark;
+
+use crate::bytes::BYTES_WORD;
+use crate::muldiv::columns::{MulDivCols, MULDIV_COL_MAP, N_MULDIV_COLS};
+use crate::muldiv::{div, flags, mul};
+use crate::stark::Table;
+use crate::util::fst;
+use crate::vm::opcode::Opcode;
+
+/// Operation flags and the corresponding opcode.
+const MULDIV_OPS: [(usize, u8); 8] = [
+    (MULDIV_COL_MAP.op.f_mul, Opcode::MUL as u8),
+    (MULDIV_COL_MAP.op.f_mulh, Opcode::MULH as u8),
+    (MULDIV_COL_MAP.op.f_mulhsu, Opcode::MULHSU as u8),
+    (MULDIV_COL_MAP.op.f_mulhu, Opcode::MULHU as u8),
+    (MULDIV_COL_MAP.op.f_div, Opcode::DIV as u8),
+    (MULDIV_COL_MAP.op.f_divu, Opcode::DIVU as u8),
+    (MULDIV_COL_MAP.op.f_rem, Opcode::REM as u8),
+    (MULDIV_COL_MAP.op.f_remu, Opcode::REMU as u8),
+];
+
+pub(crate) fn ctl_looked<F: Field>() -> TableWithColumns<F> {
+    // the first column evaluates to the opcode of the selected instruction
+    let op_comb = MULDIV_OPS.map(|(f, op)| (f, F::from_canonical_u8(op)));
+    let cols = vec![
+        Column::linear_combination(op_comb),
+        Column::single(MULDIV_COL_MAP.in0),
+        Column::le_bytes(MULDIV_COL_MAP.y),
+        Column::single(MULDIV_COL_MAP.out),
+    ];
+
+    let filter = Filter::new_simple(Column::sum(MULDIV_OPS.map(fst)));
+    TableWithColumns::new(Table::MulDiv as usize, cols, filter)
+}
+
+/// Columns whose values are range checked to be bytes.
+fn range_checked_cols<F: Field>() -> Vec<Column<F>> {
+    let map = &MULDIV_COL_MAP;
+    let bytes = map.x.iter().chain(&map.y).chain(&map.z).chain(&map.prod);
+    let carries = map.carry.iter().flatten();
+    let mut cols: Vec<_> = Column::singles(bytes.chain(carries).chain(&map.rem_diff)).collect();
+
+    // `2 * high_byte - 256 * msb` is a byte exactly when `msb` is the most
+    // significant bit of `high_byte`
+    let two = F::TWO;
+    let neg_base = -F::from_canonical_u32(1 << 8);
+    cols.extend(
+        [
+            (map.x[BYTES_WORD - 1], map.x_msb),
+            (map.y[BYTES_WORD - 1], map.y_msb),
+            (map.z[BYTES_WORD - 1], map.z_msb),
+            (map.prod[BYTES_WORD - 1], map.prod_msb),
+        ]
+        .map(|(byte, msb)| Column::linear_combination([(byte, two), (msb, neg_base)])),
+    );
+    cols
+}
+
+fn eval_all<P: PackedField>(
+    lv: &MulDivCols<P>,
+    nv: &MulDivCols<P>,
+    cc: &mut ConstraintConsumer<P>,
+) {
+    flags::eval(lv, cc);
+    mul::eval(lv, cc);
+    div::eval(lv, cc);
+
+    // range check
+    let count = lv.range_check.count;
+    let count_next = nv.range_check.count;
+    let delta = count_next - count;
+    cc.constraint_first_row(count);
+    cc.constraint_transition(delta * (delta - P::ONES));
+    cc.constraint_last_row(count - P::Scalar::from_canonical_u8(u8::MAX));
+}
+
+fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &MulDivCols<ExtensionTarget<D>>,
+    nv: &MulDivCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    flags::eval_circuit(cb, lv, cc);
+    mul::eval_circuit(cb, lv, cc);
+    div::eval_circuit(cb, lv, cc);
+
+    // range check
+    let count = lv.range_check.count;
+    let count_next = nv.range_check.count;
+    let delta = cb.sub_extension(count_next, count);
+    cc.constraint_first_row(cb, count);
+    let cs = cb.mul_sub_extension(delta, delta, delta);
+    cc.constraint_transition(cb, cs);
+    let cs = cb.add_const_extension(count, -F::from_canonical_u8(u8::MAX));
+    cc.constraint_last_row(cb, cs);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MulDivStark<F, const D: usize> {
+    _unused: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MulDivStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, N_MULDIV_COLS, 0>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, N_MULDIV_COLS, 0>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        frame: &Self::EvaluationFrame<FE, P, D2>,
+        cc: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local: &[P; N_MULDIV_COLS] = frame.get_local_values().try_into().unwrap();
+        let local: &MulDivCols<P> = local.borrow();
+        let next: &[P; N_MULDIV_COLS] = frame.get_next_values().try_into().unwrap();
+        let next: &MulDivCols<P> = next.borrow();
+        eval_all(local, next, cc);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        frame: &Self::EvaluationFrameTarget,
+        cc: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local: &[ExtensionTarget<D>; N_MULDIV_COLS] =
+            frame.get_local_values().try_into().unwrap();
+        let local: &MulDivCols<ExtensionTarget<D>> = local.borrow();
+        let next: &[ExtensionTarget<D>; N_MULDIV_COLS] =
+            frame.get_next_values().try_into().unwrap();
+        let next: &MulDivCols<ExtensionTarget<D>> = next.borrow();
+        eval_all_circuit(cb, local, next, cc);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn lookups(&self) -> Vec<Lookup<F>> {
+        let columns = range_checked_cols();
+        let n_cols = columns.len();
+        vec![Lookup {
+            columns,
+            table_column: Column::single(MULDIV_COL_MAP.range_check.count),
+            frequencies_column: Column::single(MULDIV_COL_MAP.range_check.freq),
+            filter_columns: vec![Default::default(); n_cols],
+        }]
+    }
+
+    fn requires_ctls(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use rand::Rng;
+    use starky::config::StarkConfig;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::MulDivStark;
+    use crate::muldiv::trace::{gen_trace, MulDivOp, Op};
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = MulDivStark<F, D>;
+
+    #[test]
+    fn test_stark_degree() {
+        let stark: S = Default::default();
+        test_stark_low_degree(stark).unwrap();
+    }
+
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
+
+    #[test]
+    fn test_gen_eval() {
+        crate::util::impl_stark_no_ctls!(MulDivStark);
+        type S = MulDivStarkNoCtls<F, D>;
+        const CFG: StarkConfig = StarkConfig::standard_fast_config();
+        let mut rng = rand::thread_rng();
+
+        let stark: S = Default::default();
+        let mut ops = vec![
+            // division by zero
+            MulDivOp::new(Op::DIV, 7, 0),
+            MulDivOp::new(Op::DIVU, 7, 0),
+            MulDivOp::new(Op::REM, -7i32 as u32, 0),
+            MulDivOp::new(Op::REMU, 7, 0),
+            // signed overflow
+            MulDivOp::new(Op::DIV, 1 << 31, u32::MAX),
+            MulDivOp::new(Op::REM, 1 << 31, u32::MAX),
+            // remainder takes the sign of the dividend
+            MulDivOp::new(Op::REM, -7i32 as u32, 2),
+            MulDivOp::new(Op::REM, 7, -2i32 as u32),
+            MulDivOp::new(Op::MULH, 1 << 31, 1 << 31),
+            MulDivOp::new(Op::MULHSU, u32::MAX, u32::MAX),
+        ];
+        for op in [
+            Op::MUL,
+            Op::MULH,
+            Op::MULHSU,
+            Op::MULHU,
+            Op::DIV,
+            Op::DIVU,
+            Op::REM,
+            Op::REMU,
+        ] {
+            ops.push(MulDivOp::new(op, rng.gen(), rng.gen()));
+        }
+        let min_rows = CFG.fri_config.num_cap_elements();
+        let trace = gen_trace::<F>(ops, min_rows);
+        let mut t = TimingTree::default();
+        let proof = prove::<F, C, S, D>(stark, &CFG, trace, &[], &mut t).unwrap();
+        verify_stark_proof(stark, proof, &CFG).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+use core::cmp::{max, min};
+
+use hashbrown::HashMap;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+use plonky2::util::transpose;
+
+use crate::bytes::columns::RangeCheck;
+use crate::bytes::BYTES_WORD;
+use crate::muldiv::columns::{MulDivCols, OpCols, BYTES_DWORD, OP_COL_MAP};
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Op {
+    /// Low word of the product.
+    MUL,
+    /// High word of the signed product.
+    MULH,
+    /// High word of the signed * unsigned product.
+    MULHSU,
+    /// High word of the unsigned product.
+    MULHU,
+    /// Signed division.
+    DIV,
+    /// Unsigned division.
+    DIVU,
+    /// Signed remainder.
+    REM,
+    /// Unsigned remainder.
+    REMU,
+}
+
+impl Op {
+    fn to_op_cols<F: Field>(self) -> OpCols<F> {
+        let mut cols = OpCols::default();
+        cols[match self {
+            Self::MUL => OP_COL_MAP.f_mul,
+            Self::MULH => OP_COL_MAP.f_mulh,
+            Self::MULHSU => OP_COL_MAP.f_mulhsu,
+            Self::MULHU => OP_COL_MAP.f_mulhu,
+            Self::DIV => OP_COL_MAP.f_div,
+            Self::DIVU => OP_COL_MAP.f_divu,
+            Self::REM => OP_COL_MAP.f_rem,
+            Self::REMU => OP_COL_MAP.f_remu,
+        }] = F::ONE;
+        cols
+    }
+
+    fn is_div(self) -> bool {
+        matches!(self, Self::DIV | Self::DIVU | Self::REM | Self::REMU)
+    }
+
+    /// Returns whether the first factor, the second factor and the addend
+    /// of `x * y + z` are signed.
+    fn signedness(self) -> (bool, bool, bool) {
+        match self {
+            Self::MUL | Self::MULHU | Self::DIVU | Self::REMU => (false, false, false),
+            Self::MULH => (true, true, false),
+            Self::MULHSU => (true, false, false),
+            Self::DIV | Self::REM => (true, true, true),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MulDivOp {
+    op: Op,
+    in0: u32,
+    in1: u32,
+}
+
+/// Returns the quotient and remainder of `in0 / in1` as specified by
+/// RV32M, including division by zero and signed overflow.
+fn div_rem(in0: u32, in1: u32, signed: bool) -> (u32, u32) {
+    if in1 == 0 {
+        (u32::MAX, in0)
+    } else if signed {
+        let (a, b) = (in0 as i32, in1 as i32);
+        (a.wrapping_div(b) as u32, a.wrapping_rem(b) as u32)
+    } else {
+        (in0 / in1, in0 % in1)
+    }
+}
+
+/// Extends `word` to 64 bits.
+fn extend(word: u32, signed: bool) -> u64 {
+    if signed {
+        word as i32 as i64 as u64
+    } else {
+        word.into()
+    }
+}
+
+/// Returns the absolute value of `word`, given whether it is negative.
+fn abs(word: u32, neg: bool) -> u64 {
+    if neg {
+        (1 << 32) - u64::from(word)
+    } else {
+        word.into()
+    }
+}
+
+impl MulDivOp {
+    pub(crate) fn new(op: Op, in0: u32, in1: u32) -> Self {
+        Self { op, in0, in1 }
+    }
+
+    fn into_row<F: Field>(self, map: &mut HashMap<u8, usize>, index: usize) -> MulDivCols<F> {
+        let (x, z, out) = if self.op.is_div() {
+            let signed = matches!(self.op, Op::DIV | Op::REM);
+            let (q, r) = div_rem(self.in0, self.in1, signed);
+            let out = if matches!(self.op, Op::DIV | Op::DIVU) {
+                q
+            } else {
+                r
+            };
+            (q, r, out)
+        } else {
+            let (x_signed, y_signed, _) = self.op.signedness();
+            let prod = extend(self.in0, x_signed).wrapping_mul(extend(self.in1, y_signed));
+            let out = match self.op {
+                Op::MUL => prod as u32,
+                _ => (prod >> 32) as u32,
+            };
+            (self.in0, 0, out)
+        };
+        let y = self.in1;
+
+        // x * y + z, one byte at a time
+        let (x_signed, y_signed, z_signed) = self.op.signedness();
+        let msb = |w: u32| w >> 31 != 0;
+        let (x_neg, y_neg, z_neg) = (x_signed && msb(x), y_signed && msb(y), z_signed && msb(z));
+        let x_ext = extend(x, x_signed).to_le_bytes();
+        let y_ext = extend(y, y_signed).to_le_bytes();
+        let z_ext = extend(z, z_signed).to_le_bytes();
+        let mut prod = [0u8; BYTES_DWORD];
+        let mut carry = [0u16; BYTES_DWORD];
+        for k in 0..BYTES_DWORD {
+            let carry_in = if k > 0 { carry[k - 1].into() } else { 0 };
+            let sum: u64 = (0..=k)
+                .map(|i| u64::from(x_ext[i]) * u64::from(y_ext[k - i]))
+                .sum::<u64>()
+                + u64::from(z_ext[k])
+                + carry_in;
+            prod[k] = sum as u8;
+            carry[k] = (sum >> 8) as u16;
+        }
+        let prod_lo = u32::from_le_bytes(prod[..BYTES_WORD].try_into().unwrap());
+        let prod_neg = z_signed && msb(prod_lo);
+
+        let [x_bytes, y_bytes, z_bytes] = [x, y, z].map(u32::to_le_bytes);
+        let mut row = MulDivCols {
+            op: self.op.to_op_cols(),
+            in0: F::from_canonical_u32(self.in0),
+            out: F::from_canonical_u32(out),
+            x: x_bytes.map(F::from_canonical_u8),
+            y: y_bytes.map(F::from_canonical_u8),
+            z: z_bytes.map(F::from_canonical_u8),
+            prod: prod.map(F::from_canonical_u8),
+            carry: carry.map(|c| c.to_le_bytes().map(F::from_canonical_u8)),
+            x_msb: F::from_bool(msb(x)),
+            y_msb: F::from_bool(msb(y)),
+            z_msb: F::from_bool(msb(z)),
+            prod_msb: F::from_bool(msb(prod_lo)),
+            x_neg: F::from_bool(x_neg),
+            y_neg: F::from_bool(y_neg),
+            z_neg: F::from_bool(z_neg),
+            prod_neg: F::from_bool(prod_neg),
+            f_y_zero: F::from_bool(y == 0),
+            y_abs: F::from_canonical_u64(abs(y, y_neg)),
+            z_abs: F::from_canonical_u64(abs(z, z_neg)),
+            range_check: RangeCheck {
+                count: rc_count(index),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let y_sum: u32 = y_bytes.into_iter().map(u32::from).sum();
+        row.y_inv = F::from_canonical_u32(y_sum)
+            .try_inverse()
+            .unwrap_or_default();
+
+        // the sum is zero exactly for a dividend of -2^31 and a divisor of -1
+        let dividend_sum: u32 = prod[..BYTES_WORD].iter().map(|&b| u32::from(b)).sum();
+        let divisor_sum: u32 = y_bytes.into_iter().map(|b| u32::from(u8::MAX - b)).sum();
+        let overflow_sum =
+            dividend_sum - (prod_lo >> 31 << 7) + u32::from(!msb(prod_lo)) + divisor_sum;
+        row.overflow_inv = F::from_canonical_u32(overflow_sum)
+            .try_inverse()
+            .unwrap_or_default();
+        row.f_overflow = F::from_bool(z_signed && overflow_sum == 0);
+
+        let rem_diff = if self.op.is_div() && y != 0 {
+            (abs(y, y_neg) - abs(z, z_neg) - 1) as u32
+        } else {
+            0
+        };
+        row.rem_diff = rem_diff.to_le_bytes().map(F::from_canonical_u8);
+
+        // record range checked values
+        let high_bytes = [x, y, z, prod_lo].map(|w| (w >> 24) as u8);
+        let msb_checks = high_bytes.map(|b| b << 1);
+        let carries = carry.iter().flat_map(|c| c.to_le_bytes());
+        x_bytes
+            .into_iter()
+            .chain(y_bytes)
+            .chain(z_bytes)
+            .chain(prod)
+            .chain(carries)
+            .chain(rem_diff.to_le_bytes())
+            .chain(msb_checks)
+            .for_each(|b| *map.entry(b).or_insert(0) += 1);
+        row
+    }
+}
+
+pub(crate) fn gen_trace<F: Field>(ops: Vec<MulDivOp>, min_rows: usize) -> Vec<PolynomialValues<F>> {
+    let trace = gen_trace_rows(ops, min_rows);
+    let trace_rows: Vec<_> = trace.iter().map(MulDivCols::to_vec).collect();
+    let trace_cols = transpose(&trace_rows);
+    trace_cols.into_iter().map(PolynomialValues::new).collect()
+}
+
+fn gen_trace_rows<F: Field>(ops: Vec<MulDivOp>, min_rows: usize) -> Vec<MulDivCols<F>> {
+    let n_ops = ops.len();
+    let n_rows = max(max(n_ops, u8::MAX.into()), min_rows).next_power_of_two();
+
+    let mut rc_freq = HashMap::default();
+    let mut rows: Vec<MulDivCols<F>> = ops
+        .into_iter()
+        .enumerate()
+        .map(|(i, op)| op.into_row(&mut rc_freq, i))
+        .chain((n_ops..n_rows).map(padding_row))
+        .collect();
+
+    // account for padding rows in range check frequencies
+    let n_checked = range_checked_per_row();
+    let pad_freq = rc_freq.entry(0).or_insert(0);
+    *pad_freq += n_checked * (n_rows - n_ops);
+
+    // write range check frequencies column
+    for (val, freq) in rc_freq {
+        rows[val as usize].range_check.freq = F::from_canonical_usize(freq);
+    }
+    rows
+}
+
+/// The number of values range checked in each row.
+fn range_checked_per_row() -> usize {
+    // x, y, z, prod, carries, rem_diff and the four sign bit checks
+    3 * BYTES_WORD + BYTES_DWORD + 2 * BYTES_DWORD + BYTES_WORD + 4
+}
+
+fn padding_row<F: Field>(index: usize) -> MulDivCols<F> {
+    MulDivCols {
+        range_check: RangeCheck {
+            count: rc_count(index),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn rc_count<F: Field>(index: usize) -> F {
+    F::from_canonical_usize(min(index, u8::MAX.into()))
+}
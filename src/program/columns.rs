@@ -0,0 +1,59 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::ops::{Deref, DerefMut, Index, IndexMut};
+
+use rizzo_derive::{Columns, DerefColumns};
+
+use crate::cpu::columns::OpCols;
+
+/// The value of each struct field is the index of the corresponding column.
+pub(crate) const PROGRAM_COL_MAP: ProgramCols<usize> = make_col_map();
+/// The number of field elements in `ProgramCols`.
+pub(crate) const N_PROGRAM_COLS: usize = core::mem::size_of::<ProgramCols<u8>>();
+/// The number of field elements in `RomCols`.
+pub(crate) const N_ROM_COLS: usize = core::mem::size_of::<RomCols<u8>>();
+
+/// Columns holding a single instruction of the program. Their values are
+/// fixed by the program, and are checked by the verifier.
+#[repr(C)]
+#[derive(DerefColumns, Clone, Debug, Default)]
+pub(crate) struct RomCols<T> {
+    /// 1 if this row holds an instruction, 0 if it's a padding row.
+    pub f_rom: T,
+    /// The address of the instruction.
+    pub pc: T,
+    /// The opcode value (our internal `Opcode` enum).
+    pub opcode: T,
+    /// The flags of the operation, as set in the CPU table.
+    pub op: OpCols<T>,
+    /// Source register `rs1`.
+    pub rs1: T,
+    /// Source register `rs2`.
+    pub rs2: T,
+    /// Destination register `rd`.
+    pub rd: T,
+    /// The immediate value.
+    pub imm: T,
+    /// 1 if the immediate value should be used.
+    pub f_imm: T,
+}
+
+/// Columns for the program stark.
+#[repr(C)]
+#[derive(Columns, DerefColumns, Clone, Debug)]
+pub(crate) struct ProgramCols<T> {
+    /// The instruction. These are the first `N_ROM_COLS` columns.
+    pub rom: RomCols<T>,
+    /// The number of times the instruction is executed.
+    pub mult: T,
+}
+
+impl<T: Copy> ProgramCols<T> {
+    pub(crate) fn to_vec(&self) -> Vec<T> {
+        Borrow::<[T; N_PROGRAM_COLS]>::borrow(self).to_vec()
+    }
+}
+
+const fn make_col_map() -> ProgramCols<usize> {
+    let arr = crate::util::indices_arr::<N_PROGRAM_COLS>();
+    unsafe { core::mem::transmute::<[usize; N_PROGRAM_COLS], ProgramCols<usize>>(arr) }
+}
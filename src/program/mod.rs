@@ -0,0 +1,3 @@
+pub mod columns;
+pub mod stark;
+pub mod trace;
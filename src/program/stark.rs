@@ -0,0 +1,154 @@
+use core::borrow::Borrow;
+use core::marker::PhantomData;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::cross_table_lookup::TableWithColumns;
+use starky::evaluation_frame::{StarkEvaluationFrame, StarkFrame};
+use starky::lookup::{Column, Filter};
+use starky::stark::Stark;
+
+use crate::program::columns::{ProgramCols, N_PROGRAM_COLS, PROGRAM_COL_MAP};
+use crate::stark::Table;
+
+pub(crate) fn ctl_looked<F: Field>() -> TableWithColumns<F> {
+    let rom = &PROGRAM_COL_MAP.rom;
+    let cols = Column::singles([
+        rom.pc, rom.opcode, rom.rs1, rom.rs2, rom.rd, rom.imm, rom.f_imm,
+    ])
+    .chain(Column::singles(rom.op.iter()))
+    .collect();
+
+    // each instruction is looked up once per execution
+    let filter = Filter::new_simple(Column::single(PROGRAM_COL_MAP.mult));
+    TableWithColumns::new(Table::Program as usize, cols, filter)
+}
+
+fn eval_all<P: PackedField>(lv: &ProgramCols<P>, cc: &mut ConstraintConsumer<P>) {
+    // the ROM columns are checked by the verifier, so only the multiplicity
+    // is left to constrain: padding rows can't be executed
+    let f_padding = P::ONES - lv.rom.f_rom;
+    cc.constraint(f_padding * lv.mult);
+}
+
+fn eval_all_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    lv: &ProgramCols<ExtensionTarget<D>>,
+    cc: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = cb.one_extension();
+
+    let f_padding = cb.sub_extension(one, lv.rom.f_rom);
+    let cs = cb.mul_extension(f_padding, lv.mult);
+    cc.constraint(cb, cs);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProgramStark<F, const D: usize> {
+    _unused: PhantomData<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for ProgramStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, N_PROGRAM_COLS, 0>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, N_PROGRAM_COLS, 0>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        frame: &Self::EvaluationFrame<FE, P, D2>,
+        cc: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let local: &[P; N_PROGRAM_COLS] = frame.get_local_values().try_into().unwrap();
+        let local: &ProgramCols<P> = local.borrow();
+        eval_all(local, cc)
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        frame: &Self::EvaluationFrameTarget,
+        cc: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let local: &[ExtensionTarget<D>; N_PROGRAM_COLS] =
+            frame.get_local_values().try_into().unwrap();
+        let local: &ProgramCols<ExtensionTarget<D>> = local.borrow();
+        eval_all_circuit(cb, local, cc);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+
+    fn requires_ctls(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::ProgramStark;
+    use crate::program::trace::{gen_trace, ProgramRom};
+    use crate::vm::Machine;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = ProgramStark<F, D>;
+
+    #[test]
+    fn test_stark_degree() {
+        let stark: S = Default::default();
+        test_stark_low_degree(stark).unwrap();
+    }
+
+    #[test]
+    fn test_stark_circuit() {
+        let stark: S = Default::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark).unwrap();
+    }
+
+    #[test]
+    fn test_gen_eval() {
+        crate::util::impl_stark_no_ctls!(ProgramStark);
+        type S = ProgramStarkNoCtls<F, D>;
+        const CFG: StarkConfig = StarkConfig::standard_fast_config();
+
+        let stark: S = Default::default();
+        let program = [
+            0x00300093, // addi x1, x0, 3
+            0xfff08093, // loop: addi x1, x1, -1
+            0xfe009ee3, // bne x1, x0, loop
+            0x05d00893, // addi a7, x0, 93
+            0x00000073, // ecall
+        ];
+        let log = Machine::from_words(0x100, &program).run(100).unwrap();
+        let rom = ProgramRom::from_image(&log.image);
+        assert_eq!(rom.len(), program.len());
+
+        let min_rows = CFG.fri_config.num_cap_elements();
+        let pcs = log.cpu.iter().map(|step| step.pc);
+        let trace = gen_trace::<F>(&rom, pcs, min_rows);
+        let mut t = TimingTree::default();
+        let proof = prove::<F, C, S, D>(stark, &CFG, trace, &[], &mut t).unwrap();
+        verify_stark_proof(stark, proof, &CFG).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use core::cmp::max;
+
+use anyhow::{ensure, Result};
+use hashbrown::HashMap;
+use itertools::Itertools;
+use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::plonk::config::Hasher;
+use plonky2::util::transpose;
+
+use crate::cpu::control_flow::INSTRUCTION_BYTES;
+use crate::cpu::trace::op_flag;
+use crate::program::columns::{ProgramCols, RomCols};
+use crate::vm::decode::{decode, Instruction};
+
+/// The instructions of a program, ordered by address.
+///
+/// Instructions are read from the initial memory image only, so
+/// self-modifying code is not supported.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProgramRom {
+    instructions: Vec<(u32, Instruction)>,
+}
+
+impl ProgramRom {
+    /// Decodes every aligned word of `image` which holds a valid
+    /// instruction. Missing bytes of a word are read as zero.
+    pub(crate) fn from_image(image: &HashMap<u32, u8>) -> Self {
+        let ix_bytes = INSTRUCTION_BYTES as u32;
+        let instructions = image
+            .keys()
+            .map(|&adr| adr - adr % ix_bytes)
+            .unique()
+            .sorted()
+            .filter_map(|pc| {
+                let bytes = core::array::from_fn(|i| {
+                    let adr = pc.wrapping_add(i as u32);
+                    image.get(&adr).copied().unwrap_or_default()
+                });
+                let ix = decode(u32::from_le_bytes(bytes)).ok()?;
+                Some((pc, ix))
+            })
+            .collect();
+        Self { instructions }
+    }
+
+    /// Returns the number of instructions.
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Returns true if the program has no instructions.
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Returns a hash of every instruction and its address, identifying the
+    /// program.
+    pub fn hash<F: RichField>(&self) -> HashOut<F> {
+        let elements: Vec<F> = self
+            .rows::<F>(self.len())
+            .flat_map(|row| row[1..].to_vec())
+            .collect();
+        PoseidonHash::hash_no_pad(&elements)
+    }
+
+    /// Returns the instructions as rows of ROM columns, followed by padding
+    /// rows up to a total of `n_rows` rows.
+    fn rows<F: Field>(&self, n_rows: usize) -> impl Iterator<Item = RomCols<F>> + '_ {
+        let rows = self.instructions.iter().map(|&(pc, ix)| {
+            let mut row = RomCols {
+                f_rom: F::ONE,
+                pc: F::from_canonical_u32(pc),
+                opcode: F::from_canonical_u8(ix.opcode as u8),
+                rs1: F::from_canonical_u8(ix.rs1),
+                rs2: F::from_canonical_u8(ix.rs2),
+                rd: F::from_canonical_u8(ix.rd),
+                imm: F::from_canonical_u32(ix.imm),
+                f_imm: F::from_bool(ix.uses_imm),
+                ..Default::default()
+            };
+            *op_flag(&mut row.op, ix.opcode) = F::ONE;
+            row
+        });
+        let n_padding = n_rows.saturating_sub(self.len());
+        rows.chain((0..n_padding).map(|_| RomCols::default()))
+    }
+
+    /// Returns the polynomials of the ROM columns of a program table with
+    /// `2^degree_bits` rows.
+    pub(crate) fn rom_polys<F: Field>(
+        &self,
+        degree_bits: usize,
+    ) -> Result<Vec<PolynomialCoeffs<F>>> {
+        let n_rows = 1 << degree_bits;
+        ensure!(
+            self.len() <= n_rows,
+            "program has {} instructions but the program table has {n_rows} rows",
+            self.len(),
+        );
+        let rows: Vec<_> = self.rows(n_rows).map(|row| row.to_vec()).collect();
+        let cols = transpose(&rows);
+        Ok(cols
+            .into_iter()
+            .map(|col| PolynomialValues::new(col).ifft())
+            .collect())
+    }
+}
+
+/// Generates the program table, where `pcs` are the addresses of every
+/// executed instruction.
+pub(crate) fn gen_trace<F: Field>(
+    rom: &ProgramRom,
+    pcs: impl IntoIterator<Item = u32>,
+    min_rows: usize,
+) -> Vec<PolynomialValues<F>> {
+    let trace = gen_trace_rows(rom, pcs, min_rows);
+    let trace_rows: Vec<_> = trace.iter().map(ProgramCols::to_vec).collect();
+    let trace_cols = transpose(&trace_rows);
+    trace_cols.into_iter().map(PolynomialValues::new).collect()
+}
+
+fn gen_trace_rows<F: Field>(
+    rom: &ProgramRom,
+    pcs: impl IntoIterator<Item = u32>,
+    min_rows: usize,
+) -> Vec<ProgramCols<F>> {
+    let counts = pcs.into_iter().counts();
+    let n_rows = max(rom.len(), min_rows).next_power_of_two();
+    let mults = rom
+        .instructions
+        .iter()
+        .map(|(pc, _)| counts.get(pc).copied().unwrap_or_default())
+        .chain(core::iter::repeat(0));
+    rom.rows(n_rows)
+        .zip(mults)
+        .map(|(rom, mult)| ProgramCols {
+            rom,
+            mult: F::from_canonical_usize(mult),
+        })
+        .collect()
+}
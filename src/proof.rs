@@ -0,0 +1,153 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::iop::challenger::Challenger;
+use plonky2::plonk::config::GenericConfig;
+use starky::config::StarkConfig;
+use starky::lookup::{get_grand_product_challenge_set, GrandProductChallengeSet};
+use starky::proof::{MultiProof, StarkProofChallenges};
+
+use crate::cpu::columns::CpuPublicInputs;
+use crate::mem::page::PageProof;
+use crate::memfinal::trace::FinalMem;
+use crate::stark::NUM_TABLES;
+
+/// A proof of a full execution, consisting of one stark proof per table.
+#[derive(Debug, Clone)]
+pub struct AllProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    /// One proof per table, along with the challenges used for the
+    /// cross-table lookups.
+    pub multi_proof: MultiProof<F, C, D, NUM_TABLES>,
+    /// The public values of the execution.
+    pub public_values: PublicValues<F>,
+    /// The contents of memory at the end of execution, which hash to the
+    /// final hash of the public values.
+    pub final_mem: FinalMem,
+}
+
+/// The proof of one segment of an execution, along with the memory it is
+/// verified against. The memory in which a segment starts is only known
+/// through the root of its pages, see [`page`](crate::mem::page), and the
+/// memory in which it stops is an output of its proof. The bytes it transfers
+/// are those of the streams of the whole execution between its io offsets.
+#[derive(Debug, Clone)]
+pub struct SegmentProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    /// The proof of the segment.
+    pub proof: AllProof<F, C, D>,
+    /// The pages touched by the segment, at its start.
+    pub pages: PageProof<F>,
+}
+
+/// Public values of an execution, exposed by the CPU table.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PublicValues<F: Field> {
+    /// A hash of the instructions of the program, see
+    /// [`ProgramRom::hash`](crate::program::trace::ProgramRom::hash).
+    pub program_hash: HashOut<F>,
+    /// A hash of the initial contents of main memory, see
+    /// [`MemImage::hash`](crate::meminit::trace::MemImage::hash).
+    pub image_hash: HashOut<F>,
+    /// A hash of the bytes read from the input stream, see
+    /// [`IoTape::input_hash`](crate::io::trace::IoTape::input_hash).
+    pub input_hash: HashOut<F>,
+    /// A hash of the bytes written to the output stream, see
+    /// [`IoTape::output_hash`](crate::io::trace::IoTape::output_hash).
+    pub output_hash: HashOut<F>,
+    /// A hash of the contents of memory at the end of execution, see
+    /// [`FinalMem::hash`](crate::memfinal::trace::FinalMem::hash).
+    pub final_hash: HashOut<F>,
+    /// The Merkle root of the registers and main memory in which execution
+    /// starts, see [`PagedMemory::root`](crate::mem::page::PagedMemory::root).
+    pub root_start: HashOut<F>,
+    /// The Merkle root of the registers and main memory in which execution
+    /// stops.
+    pub root_end: HashOut<F>,
+    /// The program counter of the first instruction.
+    pub pc_start: u32,
+    /// The program counter at which execution halted.
+    pub pc_end: u32,
+    /// The position in the input stream of the first byte read.
+    pub input_start: usize,
+    /// The position in the input stream following the last byte read.
+    pub input_end: usize,
+    /// The position in the output stream of the first byte written.
+    pub output_start: usize,
+    /// The position in the output stream following the last byte written.
+    pub output_end: usize,
+    /// The number of instructions executed before halting.
+    pub clock_end: usize,
+    /// True if execution halted with the HALT syscall, false if this is a
+    /// segment which stopped after its last instruction.
+    pub halted: bool,
+    /// The exit code passed to the HALT syscall.
+    pub exit_code: u32,
+}
+
+impl<F: Field> PublicValues<F> {
+    /// Returns the public inputs of the CPU table.
+    pub(crate) fn cpu_inputs(&self) -> CpuPublicInputs<F> {
+        CpuPublicInputs {
+            program_hash: self.program_hash.elements,
+            image_hash: self.image_hash.elements,
+            input_hash: self.input_hash.elements,
+            output_hash: self.output_hash.elements,
+            final_hash: self.final_hash.elements,
+            root_start: self.root_start.elements,
+            root_end: self.root_end.elements,
+            pc_start: F::from_canonical_u32(self.pc_start),
+            pc_end: F::from_canonical_u32(self.pc_end),
+            input_start: F::from_canonical_usize(self.input_start),
+            input_end: F::from_canonical_usize(self.input_end),
+            output_start: F::from_canonical_usize(self.output_start),
+            output_end: F::from_canonical_usize(self.output_end),
+            clock_end: F::from_canonical_usize(self.clock_end),
+            halted: F::from_bool(self.halted),
+            exit_code: F::from_canonical_u32(self.exit_code),
+        }
+    }
+}
+
+/// Randomness for all tables of an [`AllProof`].
+#[derive(Debug)]
+pub(crate) struct AllProofChallenges<F: RichField + Extendable<D>, const D: usize> {
+    /// Randomness used in each table's stark proof.
+    pub stark_challenges: [StarkProofChallenges<F, D>; NUM_TABLES],
+    /// Randomness used for the cross-table lookups.
+    pub ctl_challenges: GrandProductChallengeSet<F>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> AllProof<F, C, D> {
+    /// Returns the degree bits of every table's trace, ordered by
+    /// [`Table`](crate::stark::Table).
+    pub fn degree_bits(&self, cfg: &StarkConfig) -> [usize; NUM_TABLES] {
+        let stark_proofs = &self.multi_proof.stark_proofs;
+        core::array::from_fn(|i| stark_proofs[i].proof.recover_degree_bits(cfg))
+    }
+
+    /// Recomputes the challenges for every table, replaying the transcript
+    /// of [`prove_all`](crate::prover::prove_all).
+    pub(crate) fn get_challenges(&self, cfg: &StarkConfig) -> AllProofChallenges<F, D> {
+        let mut challenger = Challenger::<F, C::Hasher>::new();
+
+        let stark_proofs = &self.multi_proof.stark_proofs;
+        for proof in stark_proofs {
+            challenger.observe_cap(&proof.proof.trace_cap);
+        }
+        challenger.observe_elements(&self.public_values.cpu_inputs().to_vec());
+
+        let ctl_challenges = get_grand_product_challenge_set(&mut challenger, cfg.num_challenges);
+
+        AllProofChallenges {
+            stark_challenges: core::array::from_fn(|i| {
+                challenger.compact();
+                stark_proofs[i].proof.get_challenges(
+                    &mut challenger,
+                    Some(&ctl_challenges),
+                    true,
+                    cfg,
+                )
+            }),
+            ctl_challenges,
+        }
+    }
+}
@@ -0,0 +1,860 @@
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::challenger::Challenger;
+use plonky2::plonk::config::GenericConfig;
+use plonky2::util::timing::TimingTree;
+use starky::config::StarkConfig;
+use starky::cross_table_lookup::{get_ctl_data, CtlData};
+use starky::lookup::GrandProductChallengeSet;
+use starky::proof::{MultiProof, StarkProofWithMetadata};
+use starky::prover::prove_with_commitment;
+use starky::stark::Stark;
+
+use crate::memfinal::trace::FinalMem;
+use crate::proof::{AllProof, PublicValues};
+use crate::stark::{AllStark, Table, NUM_TABLES};
+
+/// Proves a full execution given the trace of every table, ordered by
+/// [`Table`], the public values of the execution and its final memory. All
+/// tables are proven together, with the cross-table lookups of [`AllStark`]
+/// connecting them.
+pub fn prove_all<F, C, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    cfg: &StarkConfig,
+    traces: [Vec<PolynomialValues<F>>; NUM_TABLES],
+    public_values: PublicValues<F>,
+    final_mem: FinalMem,
+    timing: &mut TimingTree,
+) -> Result<AllProof<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let rate_bits = cfg.fri_config.rate_bits;
+    let cap_height = cfg.fri_config.cap_height;
+
+    for (trace, table) in traces.iter().zip_eq(Table::all()) {
+        ensure!(
+            trace.iter().map(PolynomialValues::len).all_equal(),
+            "columns of the {table:?} trace have different lengths",
+        );
+    }
+    ensure!(
+        public_values.final_hash == final_mem.hash(),
+        "final hash does not match the final memory",
+    );
+
+    // Commit to the trace of every table.
+    let trace_commitments: Vec<_> = traces
+        .iter()
+        .map(|trace| {
+            PolynomialBatch::<F, C, D>::from_values(
+                trace.clone(),
+                rate_bits,
+                false,
+                cap_height,
+                timing,
+                None,
+            )
+        })
+        .collect();
+
+    let mut challenger = Challenger::<F, C::Hasher>::new();
+    for commitment in &trace_commitments {
+        challenger.observe_cap(&commitment.merkle_tree.cap);
+    }
+    let cpu_inputs = public_values.cpu_inputs().to_vec();
+    challenger.observe_elements(&cpu_inputs);
+
+    // Only the CPU table has public inputs.
+    let mut public_inputs: [Vec<F>; NUM_TABLES] = Default::default();
+    public_inputs[Table::Cpu as usize] = cpu_inputs;
+
+    // Compute the cross-table lookup polynomials of every table.
+    let (ctl_challenges, ctl_data) = get_ctl_data::<F, C, D, NUM_TABLES>(
+        cfg,
+        &traces,
+        &all_stark.cross_table_lookups,
+        &mut challenger,
+        all_stark.max_constraint_degree(),
+    );
+
+    let ctx = ProveCtx {
+        cfg,
+        traces: &traces,
+        public_inputs: &public_inputs,
+        commitments: &trace_commitments,
+        ctl_data: &ctl_data,
+        ctl_challenges: &ctl_challenges,
+    };
+    let stark_proofs = [
+        ctx.prove_table(
+            &all_stark.arith_stark,
+            Table::Arith,
+            &mut challenger,
+            timing,
+        )?,
+        ctx.prove_table(&all_stark.bit_stark, Table::Bits, &mut challenger, timing)?,
+        ctx.prove_table(&all_stark.byte_stark, Table::Bytes, &mut challenger, timing)?,
+        ctx.prove_table(&all_stark.cpu_stark, Table::Cpu, &mut challenger, timing)?,
+        ctx.prove_table(&all_stark.io_stark, Table::Io, &mut challenger, timing)?,
+        ctx.prove_table(&all_stark.mem_stark, Table::Mem, &mut challenger, timing)?,
+        ctx.prove_table(
+            &all_stark.memfinal_stark,
+            Table::MemFinal,
+            &mut challenger,
+            timing,
+        )?,
+        ctx.prove_table(
+            &all_stark.meminit_stark,
+            Table::MemInit,
+            &mut challenger,
+            timing,
+        )?,
+        ctx.prove_table(
+            &all_stark.muldiv_stark,
+            Table::MulDiv,
+            &mut challenger,
+            timing,
+        )?,
+        ctx.prove_table(
+            &all_stark.program_stark,
+            Table::Program,
+            &mut challenger,
+            timing,
+        )?,
+    ];
+
+    Ok(AllProof {
+        multi_proof: MultiProof {
+            stark_proofs,
+            ctl_challenges,
+        },
+        public_values,
+        final_mem,
+    })
+}
+
+/// Data shared by the proofs of every table.
+struct ProveCtx<'a, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    cfg: &'a StarkConfig,
+    traces: &'a [Vec<PolynomialValues<F>>; NUM_TABLES],
+    public_inputs: &'a [Vec<F>; NUM_TABLES],
+    commitments: &'a [PolynomialBatch<F, C, D>],
+    ctl_data: &'a [CtlData<'a, F>; NUM_TABLES],
+    ctl_challenges: &'a GrandProductChallengeSet<F>,
+}
+
+impl<'a, F, C, const D: usize> ProveCtx<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// Proves a single table using the already committed trace.
+    fn prove_table<S: Stark<F, D>>(
+        &self,
+        stark: &S,
+        table: Table,
+        challenger: &mut Challenger<F, C::Hasher>,
+        timing: &mut TimingTree,
+    ) -> Result<StarkProofWithMetadata<F, C, D>> {
+        let i = table as usize;
+
+        // Clear buffered outputs.
+        let init_challenger_state = challenger.compact();
+
+        let proof = prove_with_commitment(
+            stark,
+            self.cfg,
+            &self.traces[i],
+            &self.commitments[i],
+            Some(&self.ctl_data[i]),
+            Some(self.ctl_challenges),
+            challenger,
+            &self.public_inputs[i],
+            timing,
+        )?;
+
+        Ok(StarkProofWithMetadata {
+            proof: proof.proof,
+            init_challenger_state,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use plonky2::field::extension::Extendable;
+    use plonky2::field::polynomial::PolynomialValues;
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+
+    use crate::bits::columns::BIT_COL_MAP;
+    use crate::bytes::columns::BYTE_COL_MAP;
+    use crate::bytes::Alignment;
+    use crate::cpu::columns::CPU_COL_MAP;
+    use crate::io::trace::IoTape;
+    use crate::memfinal::trace::FinalMem;
+    use crate::meminit::trace::MemImage;
+    use crate::program::trace::ProgramRom;
+    use crate::proof::{AllProof, PublicValues, SegmentProof};
+    use crate::prover::prove_all;
+    use crate::stark::{AllStark, Table, NUM_TABLES};
+    use crate::verifier::{verify_all, verify_segments};
+    use crate::vm::asm::assemble;
+    use crate::vm::machine::ExecutionLog;
+    use crate::vm::Machine;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    const CFG: StarkConfig = StarkConfig::standard_fast_config();
+
+    /// Halts with exit code 0.
+    const HALT: &str = "
+        li a7, 93
+        li a0, 0
+        ecall
+    ";
+
+    /// Runs the program `src` followed by [`HALT`], then proves and verifies
+    /// its execution.
+    fn prove_and_verify(src: &str) {
+        prove_and_verify_with_input(src, []);
+    }
+
+    /// Runs the program `src` followed by [`HALT`] on `input`, then proves
+    /// and verifies its execution. Returns the public values of the proof.
+    fn prove_and_verify_with_input(
+        src: &str,
+        input: impl IntoIterator<Item = u8>,
+    ) -> PublicValues<F> {
+        prove_and_verify_with(src, input, Alignment::default())
+    }
+
+    /// Like [`prove_and_verify_with_input`], under the given alignment
+    /// policy.
+    fn prove_and_verify_with(
+        src: &str,
+        input: impl IntoIterator<Item = u8>,
+        alignment: Alignment,
+    ) -> PublicValues<F> {
+        let (all_stark, proof, rom, image, tape) = prove_program(src, input, alignment, |_| {});
+        let public_values = proof.public_values;
+        verify_all(&all_stark, proof, &rom, &image, &tape, &CFG).unwrap();
+        public_values
+    }
+
+    /// Runs the program `src` followed by [`HALT`] on `input` under the
+    /// given alignment policy, then applies `forge` to its traces before
+    /// proving them. Returns the proof along with the statement it is
+    /// verified against.
+    fn prove_program(
+        src: &str,
+        input: impl IntoIterator<Item = u8>,
+        alignment: Alignment,
+        forge: impl FnOnce(&mut [Vec<PolynomialValues<F>>; NUM_TABLES]),
+    ) -> (
+        AllStark<F, D>,
+        AllProof<F, C, D>,
+        ProgramRom,
+        MemImage,
+        IoTape,
+    ) {
+        let program = assemble(&format!("{src}{HALT}")).unwrap();
+        let log = Machine::from_words(0, &program)
+            .with_input(input)
+            .with_alignment(alignment)
+            .run(1000)
+            .unwrap();
+        let rom = ProgramRom::from_image(&log.image);
+        let image = log.mem_image();
+        let tape = log.tape.clone();
+        let all_stark = AllStark::<F, D>::new(alignment);
+        let proof = prove_log(&all_stark, log, forge);
+        (all_stark, proof, rom, image, tape)
+    }
+
+    /// Applies `forge` to the traces of the execution `log`, then proves
+    /// them.
+    fn prove_log(
+        all_stark: &AllStark<F, D>,
+        log: ExecutionLog,
+        forge: impl FnOnce(&mut [Vec<PolynomialValues<F>>; NUM_TABLES]),
+    ) -> AllProof<F, C, D> {
+        let public_values = log.public_values();
+        let final_mem = log.final_mem();
+        let mut traces = log.gen_traces::<F>(CFG.fri_config.num_cap_elements());
+        forge(&mut traces);
+        let mut timing = TimingTree::default();
+        let proof = prove_all::<F, C, D>(
+            all_stark,
+            &CFG,
+            traces,
+            public_values,
+            final_mem,
+            &mut timing,
+        )
+        .unwrap();
+        assert_eq!(proof.public_values, public_values);
+        proof
+    }
+
+    /// Runs the program `src` followed by [`HALT`] under the given alignment
+    /// policy, then applies `forge` to its traces before proving them.
+    /// Returns true if the forged traces can be proven and verified.
+    fn prove_forged(
+        src: &str,
+        alignment: Alignment,
+        forge: impl FnOnce(&mut [Vec<PolynomialValues<F>>; NUM_TABLES]),
+    ) -> bool {
+        // with debug assertions, starky checks the constraints and panics on
+        // traces which don't satisfy them. Otherwise the proof is generated,
+        // and rejected by the verifier
+        catch_unwind(AssertUnwindSafe(|| {
+            let (all_stark, proof, rom, image, tape) = prove_program(src, [], alignment, forge);
+            verify_all(&all_stark, proof, &rom, &image, &tape, &CFG)
+        }))
+        .is_ok_and(|res| res.is_ok())
+    }
+
+    #[test]
+    fn test_prove_all() {
+        prove_and_verify(
+            "
+                li x1, 1024
+                li x2, -127
+                sw x2, 0(x1)
+                sb x1, 4(x1)
+                lb x3, 0(x1)
+                lhu x4, 2(x1)
+                lw x5, 4(x1)
+                li x6, 3
+            loop:
+                addi x6, x6, -1
+                bne x6, x0, loop
+                blt x2, x1, skip
+                li x7, 1
+            skip:
+                and x8, x2, x1
+                xori x9, x2, 255
+                sltu x10, x1, x2
+                jal x11, end
+                li x7, 2
+            end:
+                sub x12, x1, x2
+                bgeu x12, x0, done
+                beq x0, x0, done
+            done:
+                add x0, x1, x2
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_verify_round_trip() {
+        let public_values = prove_and_verify_with_input("li x1, 7\nadd x2, x1, x1\n", []);
+        assert_eq!(public_values.exit_code, 0);
+        assert!(public_values.halted);
+    }
+
+    #[test]
+    fn test_verify_tampered() {
+        let src = "li x1, 7\nsw x1, 64(x0)\n";
+        let (all_stark, proof, rom, image, tape) =
+            prove_program(src, [], Alignment::default(), |_| {});
+        let verify = |proof| verify_all(&all_stark, proof, &rom, &image, &tape, &CFG);
+        verify(proof.clone()).unwrap();
+
+        // a claimed exit code which the execution didn't produce
+        let mut tampered = proof.clone();
+        tampered.public_values.exit_code += 1;
+        assert!(verify(tampered).is_err());
+
+        // an opening which doesn't match the committed trace
+        let mut tampered = proof.clone();
+        let openings = &mut tampered.multi_proof.stark_proofs[Table::Cpu as usize]
+            .proof
+            .openings;
+        openings.local_values[0] += <F as Extendable<D>>::Extension::ONE;
+        assert!(verify(tampered).is_err());
+
+        // a final memory which the execution didn't end with
+        let mut tampered = proof.clone();
+        tampered.final_mem = FinalMem::default();
+        assert!(verify(tampered).is_err());
+
+        // openings which omit the cross-table lookups
+        let mut tampered = proof.clone();
+        let openings = &mut tampered.multi_proof.stark_proofs[Table::Mem as usize]
+            .proof
+            .openings;
+        openings.ctl_zs_first = None;
+        assert!(verify(tampered).is_err());
+
+        // a trace commitment swapped with another table's
+        let mut tampered = proof;
+        let stark_proofs = &mut tampered.multi_proof.stark_proofs;
+        stark_proofs[Table::Arith as usize].proof.trace_cap =
+            stark_proofs[Table::Bits as usize].proof.trace_cap.clone();
+        assert!(verify(tampered).is_err());
+    }
+
+    #[test]
+    fn test_verify_unhalted() {
+        // the first segment stops before HALT, so it can't be verified as a
+        // whole execution
+        let program = assemble(&format!("li x1, 7\nadd x2, x1, x1\n{HALT}")).unwrap();
+        let logs = Machine::from_words(0, &program)
+            .run_segments(2, 1000)
+            .unwrap();
+        let log = logs.into_iter().next().unwrap();
+        let rom = ProgramRom::from_image(&log.image);
+        let image = log.mem_image();
+        let tape = log.tape.clone();
+        let all_stark = AllStark::<F, D>::default();
+        let proof = prove_log(&all_stark, log, |_| {});
+        assert!(!proof.public_values.halted);
+        let err = verify_all(&all_stark, proof, &rom, &image, &tape, &CFG).unwrap_err();
+        assert!(err.to_string().contains("did not halt"));
+    }
+
+    #[test]
+    fn test_prove_little_endian() {
+        // the low byte of a word lives at its lowest address
+        let public_values = prove_and_verify_with_input(
+            "
+                li x1, 0x11223344
+                sw x1, 256(x0)
+                lbu x2, 256(x0)
+                lhu x3, 258(x0)
+                li x4, 0x44
+                li x5, 0x1122
+                bne x2, x4, fail
+                bne x3, x5, fail
+                li a0, 0
+                li a7, 93
+                ecall
+            fail:
+                li a0, 1
+                li a7, 93
+                ecall
+            ",
+            [],
+        );
+        assert_eq!(public_values.exit_code, 0);
+    }
+
+    #[test]
+    fn test_prove_narrow_store() {
+        // the CPU looks up the whole source register, of which sb and sh only
+        // store the low bytes
+        let public_values = prove_and_verify_with_input(
+            "
+                li x1, 0x12345678
+                sb x1, 256(x0)
+                sh x1, 260(x0)
+                lw x2, 256(x0)
+                lw x3, 260(x0)
+                li x4, 0x78
+                li x5, 0x5678
+                li a0, 1
+                bne x2, x4, done
+                bne x3, x5, done
+                li a0, 0
+            done:
+                li a7, 93
+                ecall
+            ",
+            [],
+        );
+        assert_eq!(public_values.exit_code, 0);
+    }
+
+    #[test]
+    fn test_prove_rd_is_source() {
+        // rd is written in the same cycle as rs1 and rs2 are read
+        let public_values = prove_and_verify_with_input(
+            "
+                li x1, 5
+                addi x1, x1, 1
+                add x1, x1, x1
+                sub x2, x1, x0
+                li x3, 12
+                li a0, 0
+                beq x2, x3, done
+                li a0, 1
+            done:
+                li a7, 93
+                ecall
+            ",
+            [],
+        );
+        assert_eq!(public_values.exit_code, 0);
+    }
+
+    #[test]
+    fn test_prove_forged_flag() {
+        const SRC: &str = "
+            li x1, 6
+            and x2, x1, x1
+        ";
+        assert!(prove_forged(SRC, Alignment::default(), |_| {}));
+
+        // execute the `and` as an `add`, with a flag which disagrees with
+        // its opcode
+        assert!(!prove_forged(SRC, Alignment::default(), |traces| {
+            let cpu = &mut traces[Table::Cpu as usize];
+            cpu[CPU_COL_MAP.op.f_bits].values[1] = F::ZERO;
+            cpu[CPU_COL_MAP.op.f_arith].values[1] = F::ONE;
+        }));
+    }
+
+    #[test]
+    fn test_prove_branches() {
+        // each branch is taken exactly when it skips over the `li a0, 1`
+        // following it, so a0 remains 0 throughout
+        prove_and_verify(
+            "
+                li t0, -1
+                li t1, 1
+                beq t0, t0, b1
+                li a0, 1
+            b1: beq t0, t1, b2
+            b2: bne t0, t1, b3
+                li a0, 1
+            b3: bne t1, t1, b4
+            b4: blt t0, t1, b5
+                li a0, 1
+            b5: blt t1, t0, b6
+            b6: bltu t1, t0, b7
+                li a0, 1
+            b7: bltu t0, t1, b8
+            b8: bge t1, t0, b9
+                li a0, 1
+            b9: bge t0, t1, b10
+            b10: bgeu t0, t1, b11
+                li a0, 1
+            b11: bgeu t1, t0, b12
+            b12: bge t0, t0, b13
+                li a0, 1
+            b13: nop
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_jal() {
+        prove_and_verify(
+            "
+                jal ra, fwd
+                li a0, 1
+            back:
+                j end
+            fwd:
+                addi a1, ra, 4
+                j back
+            end:
+                nop
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_misaligned() {
+        // misaligned accesses are split into bytes, and may span two words
+        prove_and_verify(
+            "
+                li x1, 1024
+                li x2, 0x11223344
+                sw x2, 3(x1)
+                sh x2, 9(x1)
+                lhu x3, 5(x1)
+                lh x4, 2(x1)
+                lw x5, 7(x1)
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_strict_alignment() {
+        prove_and_verify_with(
+            "
+                li x1, 1024
+                li x2, -127
+                sw x2, 4(x1)
+                sh x2, 10(x1)
+                sb x2, 13(x1)
+                lw x3, 8(x1)
+                lh x4, 6(x1)
+                lhu x5, 10(x1)
+                lb x6, 5(x1)
+                lbu x7, 7(x1)
+            ",
+            [],
+            Alignment::Strict,
+        );
+    }
+
+    #[test]
+    fn test_prove_forged_alignment() {
+        const SRC: &str = "
+            li x1, 1024
+            lw x2, 4(x1)
+        ";
+        assert!(prove_forged(SRC, Alignment::Strict, |_| {}));
+
+        // the alignment of the load can't be left unchecked by dropping its
+        // row from the bits table
+        assert!(!prove_forged(SRC, Alignment::Strict, |traces| {
+            let bits = &mut traces[Table::Bits as usize];
+            bits[BIT_COL_MAP.op.f_and].values.fill(F::ZERO);
+        }));
+    }
+
+    #[test]
+    fn test_prove_forged_wrapping() {
+        const SRC: &str = "
+            li x1, 1024
+            lw x2, 1(x1)
+        ";
+        assert!(prove_forged(SRC, Alignment::Permissive, |_| {}));
+
+        // the addresses of the first and last bytes of the load are built
+        // from bytes, so that it can't wrap around the end of the address
+        // space. A limb past 0xff would let them exceed 2^32 - 1
+        for limbs in [BYTE_COL_MAP.adr_bytes, BYTE_COL_MAP.end_bytes] {
+            assert!(!prove_forged(SRC, Alignment::Permissive, |traces| {
+                let bytes = &mut traces[Table::Bytes as usize];
+                let high = bytes[limbs[1]].values[0];
+                bytes[limbs[0]].values[0] += high * F::from_canonical_u32(1 << 8);
+                bytes[limbs[1]].values[0] = F::ZERO;
+            }));
+        }
+    }
+
+    #[test]
+    fn test_prove_jalr() {
+        // jalr clears the lsb of odd targets, and the immediate may be negative
+        prove_and_verify(
+            "
+                auipc t0, 0
+                jalr ra, 13(t0)
+                li a0, 1
+                addi t1, ra, 19
+                jalr x0, -2(t1)
+                li a0, 2
+                nop
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_jalr_odd_target() {
+        // rs1 + imm = 9, so jalr lands on the `li a0, 1` at pc 8
+        const SRC: &str = "
+            li t0, 7
+            jalr ra, 2(t0)
+            li a0, 1
+        ";
+        assert!(prove_forged(SRC, Alignment::default(), |_| {}));
+
+        // the lsb can't be dropped, neither from the target nor from the
+        // cleared bit alone
+        let jump = CPU_COL_MAP.shared.jump();
+        assert!(!prove_forged(SRC, Alignment::default(), |traces| {
+            let cpu = &mut traces[Table::Cpu as usize];
+            cpu[jump.target].values[1] = F::from_canonical_u8(8);
+            cpu[jump.target_lsb].values[1] = F::ZERO;
+        }));
+        assert!(!prove_forged(SRC, Alignment::default(), |traces| {
+            let cpu = &mut traces[Table::Cpu as usize];
+            cpu[jump.target_lsb].values[1] = F::ZERO;
+        }));
+    }
+
+    #[test]
+    fn test_prove_image() {
+        // the program reads its own instructions from the initial image, and
+        // overwrites one of them after it has been executed
+        prove_and_verify(
+            "
+                lw a0, 0(zero)
+                lbu a1, 5(zero)
+                lh a2, 10(zero)
+                sw a0, 4(zero)
+                lw a3, 4(zero)
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_shifts() {
+        // shifts by a register only use the low 5 bits of rs2
+        prove_and_verify(
+            "
+                li t0, -1234567
+                li t1, 35
+                sll a0, t0, t1
+                srl a1, t0, t1
+                sra a2, t0, t1
+                slli a3, t0, 31
+                srli a4, t0, 17
+                srai a5, t0, 0
+                sll a6, t0, x0
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_upper_imm() {
+        prove_and_verify(
+            "
+                lui a0, 0x12345
+                auipc a1, 0xfffff
+                li a2, 0x12345678
+                auipc a3, 0
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_muldiv() {
+        prove_and_verify(
+            "
+                li t0, -2048
+                li t1, 7
+                mul t2, t0, t0
+                li t3, -512
+                mul t4, t2, t3
+                mulh a0, t4, t4
+                mulhsu a1, t0, t1
+                mulhu a2, t0, t1
+                div a3, t0, t1
+                divu a4, t0, t1
+                rem a5, t0, t1
+                remu a6, t0, t1
+                div a7, t1, x0
+                rem s0, t0, x0
+                li t5, -1
+                div s1, t4, t5
+                rem s2, t4, t5
+            ",
+        );
+    }
+
+    #[test]
+    fn test_prove_syscalls() {
+        // echo the input back in reverse, one byte at a time, then halt
+        let public_values = prove_and_verify_with_input(
+            "
+                li s0, 4
+                li a1, 0x200
+            read:
+                li a7, 63
+                li a0, 0
+                li a2, 1
+                ecall
+                addi a1, a1, 1
+                addi s0, s0, -1
+                bne s0, x0, read
+                li s0, 4
+            write:
+                addi a1, a1, -1
+                li a7, 64
+                li a0, 1
+                li a2, 1
+                ecall
+                addi s0, s0, -1
+                bne s0, x0, write
+                ebreak
+                li a7, 93
+                li a0, 3
+                ecall
+            ",
+            [1, 2, 3, 4],
+        );
+        assert_eq!(public_values.exit_code, 3);
+    }
+
+    #[test]
+    fn test_prove_segments() {
+        // echo the input one byte at a time, cut into segments of 5
+        // instructions which are proven separately, then chained
+        let program = assemble(&format!(
+            "
+                li s0, 3
+                li a1, 0x200
+            echo:
+                li a7, 63
+                li a0, 0
+                li a2, 1
+                ecall
+                li a7, 64
+                li a0, 1
+                ecall
+                addi a1, a1, 1
+                addi s0, s0, -1
+                bne s0, x0, echo
+                sb a1, 0(a1)
+            {HALT}"
+        ))
+        .unwrap();
+        let machine = Machine::from_words(0, &program).with_input([1, 2, 3]);
+        let mut log = machine.clone().run(1000).unwrap();
+        let root = log.memory().root();
+        let logs = machine.run_segments(5, 1000).unwrap();
+        assert!(logs.len() > 2);
+        let output: Vec<u8> = logs
+            .iter()
+            .flat_map(|log| log.tape.output.clone())
+            .collect();
+        assert_eq!(output, log.tape.output);
+
+        let all_stark = AllStark::<F, D>::default();
+        let segments: Vec<_> = logs
+            .into_iter()
+            .map(|mut log| {
+                let pages = log.restrict_to_touched_pages();
+                let proof = prove_log(&all_stark, log, |_| {});
+                SegmentProof { proof, pages }
+            })
+            .collect();
+
+        // the segments continue the streams where the previous ones stopped
+        for [prev, next] in segments.array_windows::<2>() {
+            let (prev, next) = (prev.proof.public_values, next.proof.public_values);
+            assert_eq!(next.root_start, prev.root_end);
+            assert_eq!(next.input_start, prev.input_end);
+            assert_eq!(next.output_start, prev.output_end);
+        }
+
+        // segments can't be skipped
+        let io = &log.tape;
+        let skipped = segments[1..].to_vec();
+        assert!(verify_segments(&all_stark, root, io, skipped, &CFG).is_err());
+        // nor can they transfer other streams, or only part of them
+        let mut bad_io = io.clone();
+        bad_io.output[1] += 1;
+        assert!(verify_segments(&all_stark, root, &bad_io, segments.clone(), &CFG).is_err());
+        let mut bad_io = io.clone();
+        bad_io.input.push(4);
+        assert!(verify_segments(&all_stark, root, &bad_io, segments.clone(), &CFG).is_err());
+        let final_root = verify_segments(&all_stark, root, io, segments, &CFG).unwrap();
+        let pages = log.restrict_to_touched_pages::<F>();
+        assert_eq!(final_root, pages.page_out(&log.final_mem()).unwrap());
+    }
+}
@@ -0,0 +1,801 @@
+use core::borrow::Borrow;
+
+use anyhow::{ensure, Context, Result};
+use plonky2::field::extension::Extendable;
+use plonky2::field::polynomial::PolynomialCoeffs;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use plonky2::iop::challenger::RecursiveChallenger;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2::util::log2_strict;
+use starky::config::StarkConfig;
+use starky::cross_table_lookup::{
+    verify_cross_table_lookups_circuit, CrossTableLookup, CtlCheckVarsTarget,
+};
+use starky::lookup::{get_grand_product_challenge_set_target, GrandProductChallengeSet};
+use starky::proof::{StarkProofChallengesTarget, StarkProofWithPublicInputsTarget};
+use starky::recursive_verifier::{
+    add_virtual_stark_proof_with_pis, set_stark_proof_target,
+    verify_stark_proof_with_challenges_circuit,
+};
+use starky::stark::Stark;
+
+use crate::cpu::columns::{CpuPublicInputs, N_CPU_PUBLIC_INPUTS};
+use crate::io::columns::{TapeCols, N_TAPE_COLS};
+use crate::io::trace::IoTape;
+use crate::mem::page::{
+    page_hash_circuit, path_root_circuit, select_hash_circuit, PageProof, PageUpdate, PAGE_BYTES,
+    REG_PAGE, TREE_DEPTH,
+};
+use crate::mem::Segment;
+use crate::memfinal::columns::MemFinalCols;
+use crate::meminit::columns::MemInitCols;
+use crate::program::columns::N_ROM_COLS;
+use crate::program::trace::ProgramRom;
+use crate::proof::AllProof;
+use crate::stark::{AllStark, Table, NUM_TABLES};
+use crate::util::hash_rows_circuit;
+use crate::vm::machine::N_REGS;
+
+/// A plonky2 circuit verifying an [`AllProof`], used to aggregate the proofs
+/// of every table into a single proof.
+///
+/// The circuit depends on the program, on the trace length of each table and
+/// on the number of pages of main memory it has room for, so it can only
+/// verify proofs of that program with the degree bits it was built for. The
+/// initial and final memory and the input and output streams are witnessed
+/// when proving, and bound to the image, final and io hashes. The memory is
+/// witnessed as the pages touched by the execution, whose paths link the
+/// start root to the end root. The public inputs of the circuit are those of
+/// the CPU table, so the final hash and the end root are outputs of the
+/// circuit.
+#[derive(Debug)]
+pub struct AllRecursiveCircuit<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// The underlying plonky2 circuit.
+    pub circuit: CircuitData<F, C, D>,
+    /// The proof of each table, ordered by [`Table`].
+    proof_targets: [StarkProofWithPublicInputsTarget<D>; NUM_TABLES],
+    /// The tape columns of every row of the io table.
+    tape_targets: Vec<TapeCols<Target>>,
+    /// The columns of every row of the meminit table.
+    image_targets: Vec<MemInitCols<Target>>,
+    /// The columns of every row of the memfinal table.
+    final_targets: Vec<MemFinalCols<Target>>,
+    /// The page of the registers.
+    reg_targets: PageTargets,
+    /// The pages of main memory, ordered by index.
+    page_targets: Vec<PageTargets>,
+    zero_target: Target,
+}
+
+impl<F, C, const D: usize> AllRecursiveCircuit<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+{
+    /// Builds a circuit verifying proofs of `all_stark` for executions of
+    /// the program `rom`, whose tables have the given degree bits, ordered by
+    /// [`Table`], and which touch at most `n_pages` pages of main memory.
+    pub fn new(
+        all_stark: &AllStark<F, D>,
+        rom: &ProgramRom,
+        degree_bits: [usize; NUM_TABLES],
+        n_pages: usize,
+        cfg: &StarkConfig,
+        circuit_cfg: CircuitConfig,
+    ) -> Result<Self> {
+        let mut cb = CircuitBuilder::<F, D>::new(circuit_cfg);
+        let zero_target = cb.zero();
+
+        let ctx = CircuitCtx { all_stark, cfg };
+        let proof_targets = [
+            ctx.add_proof(&mut cb, &all_stark.arith_stark, Table::Arith, degree_bits),
+            ctx.add_proof(&mut cb, &all_stark.bit_stark, Table::Bits, degree_bits),
+            ctx.add_proof(&mut cb, &all_stark.byte_stark, Table::Bytes, degree_bits),
+            ctx.add_proof(&mut cb, &all_stark.cpu_stark, Table::Cpu, degree_bits),
+            ctx.add_proof(&mut cb, &all_stark.io_stark, Table::Io, degree_bits),
+            ctx.add_proof(&mut cb, &all_stark.mem_stark, Table::Mem, degree_bits),
+            ctx.add_proof(
+                &mut cb,
+                &all_stark.memfinal_stark,
+                Table::MemFinal,
+                degree_bits,
+            ),
+            ctx.add_proof(
+                &mut cb,
+                &all_stark.meminit_stark,
+                Table::MemInit,
+                degree_bits,
+            ),
+            ctx.add_proof(&mut cb, &all_stark.muldiv_stark, Table::MulDiv, degree_bits),
+            ctx.add_proof(
+                &mut cb,
+                &all_stark.program_stark,
+                Table::Program,
+                degree_bits,
+            ),
+        ];
+
+        let cpu_inputs = &proof_targets[Table::Cpu as usize].public_inputs;
+        cb.register_public_inputs(cpu_inputs);
+
+        // The program hash must identify `rom`.
+        let cpu_inputs_arr: &[Target; N_CPU_PUBLIC_INPUTS] = cpu_inputs[..].try_into().unwrap();
+        let cpu_pis: &CpuPublicInputs<Target> = cpu_inputs_arr.borrow();
+        for (target, elt) in cpu_pis.program_hash.into_iter().zip(rom.hash().elements) {
+            let elt = cb.constant(elt);
+            cb.connect(target, elt);
+        }
+
+        // The rows of the meminit table are witnessed, and the initialized
+        // addresses must hash to the image hash.
+        let n_image_rows = 1 << degree_bits[Table::MemInit as usize];
+        let image_targets: Vec<_> = (0..n_image_rows)
+            .map(|_| add_meminit_row(&mut cb))
+            .collect();
+        let image_rows = image_targets.iter().map(|row| {
+            let flag = BoolTarget::new_unsafe(row.f_init);
+            (flag, vec![row.adr_seg, row.adr_virt, row.val])
+        });
+        let image_hash = hash_rows_circuit(&mut cb, image_rows);
+        cb.connect_hashes(image_hash, HashOutTarget::from(cpu_pis.image_hash));
+
+        // The tape columns of the io table are witnessed, and the bytes of
+        // each stream must hash to its io hash.
+        let n_io_rows = 1 << degree_bits[Table::Io as usize];
+        let tape_targets: Vec<_> = (0..n_io_rows).map(|_| add_tape_row(&mut cb)).collect();
+        let stream_rows = |f: fn(&TapeCols<Target>) -> Target| {
+            tape_targets
+                .iter()
+                .map(move |row| (BoolTarget::new_unsafe(f(row)), vec![row.pos, row.byte]))
+        };
+        let input_hash = hash_rows_circuit(&mut cb, stream_rows(|row| row.f_in));
+        let output_hash = hash_rows_circuit(&mut cb, stream_rows(|row| row.f_out));
+        cb.connect_hashes(input_hash, HashOutTarget::from(cpu_pis.input_hash));
+        cb.connect_hashes(output_hash, HashOutTarget::from(cpu_pis.output_hash));
+
+        // Likewise, the final hash is that of the rows of the memfinal table.
+        let n_final_rows = 1 << degree_bits[Table::MemFinal as usize];
+        let final_targets: Vec<_> = (0..n_final_rows)
+            .map(|_| add_memfinal_row(&mut cb))
+            .collect();
+        let final_rows = final_targets.iter().map(|row| {
+            let flag = BoolTarget::new_unsafe(row.f_final);
+            (flag, vec![row.adr_seg, row.adr_virt, row.val])
+        });
+        let final_hash = hash_rows_circuit(&mut cb, final_rows);
+        cb.connect_hashes(final_hash, HashOutTarget::from(cpu_pis.final_hash));
+
+        // The pages touched by the execution are witnessed at its start and
+        // at its end. The register page is always present, and the pages of
+        // main memory fill the first slots in order of index.
+        let reg_targets = add_page(&mut cb, N_REGS, 32);
+        let one = cb.one();
+        cb.connect(reg_targets.f_page.target, one);
+        let reg_index = cb.constant(F::from_canonical_usize(REG_PAGE));
+        cb.connect(reg_targets.index, reg_index);
+        // register x0 is not part of the final memory
+        cb.assert_zero(reg_targets.written[0].target);
+        let page_targets: Vec<_> = (0..n_pages)
+            .map(|_| add_page(&mut cb, PAGE_BYTES, 8))
+            .collect();
+        for page in &page_targets {
+            cb.assert_zero(page.index_bits[TREE_DEPTH - 1].target);
+        }
+        for [page, next] in page_targets.array_windows::<2>() {
+            let f_gap = cb.and(page.f_page, next.f_page);
+            cb.connect(f_gap.target, next.f_page.target);
+            let gap = cb.sub(next.index, page.index);
+            let gap = cb.add_const(gap, F::NEG_ONE);
+            let gap = cb.mul(gap, f_gap.target);
+            cb.range_check(gap, TREE_DEPTH - 1);
+        }
+
+        // The image holds the non-zero values of the pages at the start, and
+        // the final memory holds the values of the pages at the end whose
+        // address was accessed, so every accessed address is paged in.
+        // Values which are not accessed are left unchanged.
+        let zero = cb.zero();
+        let mut image_cells = vec![];
+        let mut final_cells = vec![];
+        let reg_page = (Segment::Reg, &reg_targets);
+        let main_pages = page_targets.iter().map(|page| (Segment::Main, page));
+        for (seg, page) in [reg_page].into_iter().chain(main_pages) {
+            let seg_target = cb.constant(F::from_canonical_usize(seg as usize));
+            let base = match seg {
+                Segment::Reg => zero,
+                Segment::Main => cb.mul_const(F::from_canonical_usize(PAGE_BYTES), page.index),
+            };
+            for (offset, ((&start, &end), &f_written)) in
+                (0..).zip(page.start.iter().zip(&page.end).zip(&page.written))
+            {
+                let virt = cb.add_const(base, F::from_canonical_usize(offset));
+                let f_zero = cb.is_equal(start, zero);
+                let f_nonzero = cb.not(f_zero);
+                let f_init = cb.and(page.f_page, f_nonzero);
+                image_cells.push((f_init, vec![seg_target, virt, start]));
+                let f_final = cb.and(page.f_page, f_written);
+                final_cells.push((f_final, vec![seg_target, virt, end]));
+                let kept = cb.select(f_written, end, start);
+                cb.connect(kept, end);
+            }
+        }
+        let page_image_hash = hash_rows_circuit(&mut cb, image_cells);
+        cb.connect_hashes(page_image_hash, HashOutTarget::from(cpu_pis.image_hash));
+        let page_final_hash = hash_rows_circuit(&mut cb, final_cells);
+        cb.connect_hashes(page_final_hash, HashOutTarget::from(cpu_pis.final_hash));
+
+        // Each page is on the path of the start root, updated with the other
+        // pages before it, and replacing it by its end gives the next root.
+        let mut root = HashOutTarget::from(cpu_pis.root_start);
+        for page in page_targets.iter().chain([&reg_targets]) {
+            let start = page_hash_circuit(&mut cb, &page.start);
+            let start_root = path_root_circuit(&mut cb, start, &page.index_bits, &page.siblings);
+            let start_root = select_hash_circuit(&mut cb, page.f_page, start_root, root);
+            cb.connect_hashes(start_root, root);
+            let end = page_hash_circuit(&mut cb, &page.end);
+            let end_root = path_root_circuit(&mut cb, end, &page.index_bits, &page.siblings);
+            root = select_hash_circuit(&mut cb, page.f_page, end_root, root);
+        }
+        cb.connect_hashes(root, HashOutTarget::from(cpu_pis.root_end));
+
+        // Replay the transcript of `prove_all`.
+        let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(&mut cb);
+        for proof in &proof_targets {
+            challenger.observe_cap(&proof.proof.trace_cap);
+        }
+        challenger.observe_elements(cpu_inputs);
+        let ctl_challenges =
+            get_grand_product_challenge_set_target(&mut cb, &mut challenger, cfg.num_challenges);
+        let [arith, bits, bytes, cpu, io_challenges, mem, memfinal, meminit, muldiv, program] =
+            core::array::from_fn(|i| {
+                challenger.compact(&mut cb);
+                proof_targets[i].proof.get_challenges::<F, C>(
+                    &mut cb,
+                    &mut challenger,
+                    Some(&ctl_challenges),
+                    true,
+                    cfg,
+                )
+            });
+
+        let proofs = &proof_targets;
+        let ctl_challenges = &ctl_challenges;
+        let stark = &all_stark.arith_stark;
+        ctx.verify_table::<C, _>(&mut cb, stark, Table::Arith, proofs, ctl_challenges, arith);
+        let stark = &all_stark.bit_stark;
+        ctx.verify_table::<C, _>(&mut cb, stark, Table::Bits, proofs, ctl_challenges, bits);
+        let stark = &all_stark.byte_stark;
+        ctx.verify_table::<C, _>(&mut cb, stark, Table::Bytes, proofs, ctl_challenges, bytes);
+        let stark = &all_stark.cpu_stark;
+        ctx.verify_table::<C, _>(&mut cb, stark, Table::Cpu, proofs, ctl_challenges, cpu);
+        let stark = &all_stark.io_stark;
+        let io_zeta = io_challenges.stark_zeta;
+        ctx.verify_table::<C, _>(
+            &mut cb,
+            stark,
+            Table::Io,
+            proofs,
+            ctl_challenges,
+            io_challenges,
+        );
+        let stark = &all_stark.mem_stark;
+        ctx.verify_table::<C, _>(&mut cb, stark, Table::Mem, proofs, ctl_challenges, mem);
+        let stark = &all_stark.memfinal_stark;
+        let final_zeta = memfinal.stark_zeta;
+        ctx.verify_table::<C, _>(
+            &mut cb,
+            stark,
+            Table::MemFinal,
+            proofs,
+            ctl_challenges,
+            memfinal,
+        );
+        let stark = &all_stark.meminit_stark;
+        let image_zeta = meminit.stark_zeta;
+        ctx.verify_table::<C, _>(
+            &mut cb,
+            stark,
+            Table::MemInit,
+            proofs,
+            ctl_challenges,
+            meminit,
+        );
+        let stark = &all_stark.muldiv_stark;
+        ctx.verify_table::<C, _>(
+            &mut cb,
+            stark,
+            Table::MulDiv,
+            proofs,
+            ctl_challenges,
+            muldiv,
+        );
+        let stark = &all_stark.program_stark;
+        let zeta = program.stark_zeta;
+        ctx.verify_table::<C, _>(
+            &mut cb,
+            stark,
+            Table::Program,
+            proofs,
+            ctl_challenges,
+            program,
+        );
+
+        // The ROM columns of the program table must hold the instructions of
+        // `rom`, which we check at the opening point.
+        let rom_polys = rom.rom_polys::<F>(degree_bits[Table::Program as usize])?;
+        let openings = &proof_targets[Table::Program as usize].proof.openings;
+        for (poly, &opening) in rom_polys.iter().zip(&openings.local_values[..N_ROM_COLS]) {
+            let expected = eval_poly_circuit(&mut cb, poly, zeta);
+            cb.connect_extension(opening, expected);
+        }
+
+        // The tape columns of the io table must hold the witnessed rows.
+        let tape_rows: Vec<_> = tape_targets.iter().map(|row| row.to_vec()).collect();
+        let tape_evals = eval_rows_circuit(&mut cb, &tape_rows, io_zeta);
+        let openings = &proof_targets[Table::Io as usize].proof.openings;
+        for (eval, &opening) in tape_evals
+            .into_iter()
+            .zip(&openings.local_values[..N_TAPE_COLS])
+        {
+            cb.connect_extension(opening, eval);
+        }
+
+        // Likewise, the meminit table must hold the witnessed rows.
+        let image_rows: Vec<_> = image_targets.iter().map(|row| row.to_vec()).collect();
+        let image_evals = eval_rows_circuit(&mut cb, &image_rows, image_zeta);
+        let openings = &proof_targets[Table::MemInit as usize].proof.openings;
+        for (eval, &opening) in image_evals.into_iter().zip(&openings.local_values) {
+            cb.connect_extension(opening, eval);
+        }
+
+        // Likewise, the memfinal table must hold the witnessed rows.
+        let final_rows: Vec<_> = final_targets.iter().map(|row| row.to_vec()).collect();
+        let final_evals = eval_rows_circuit(&mut cb, &final_rows, final_zeta);
+        let openings = &proof_targets[Table::MemFinal as usize].proof.openings;
+        for (eval, &opening) in final_evals.into_iter().zip(&openings.local_values) {
+            cb.connect_extension(opening, eval);
+        }
+
+        let ctl_zs_first = core::array::from_fn(|i| {
+            proof_targets[i]
+                .proof
+                .openings
+                .ctl_zs_first
+                .clone()
+                .expect("every table uses cross-table lookups")
+        });
+        verify_cross_table_lookups_circuit::<F, D, NUM_TABLES>(
+            &mut cb,
+            all_stark.cross_table_lookups.clone(),
+            ctl_zs_first,
+            None,
+            cfg,
+        );
+
+        Ok(Self {
+            circuit: cb.build::<C>(),
+            proof_targets,
+            tape_targets,
+            image_targets,
+            final_targets,
+            reg_targets,
+            page_targets,
+            zero_target,
+        })
+    }
+
+    /// Proves that `all_proof` is valid for an execution starting from the
+    /// memory of `pages`, which read and wrote the streams of `io`. The final
+    /// memory is that of `all_proof`.
+    pub fn prove(
+        &self,
+        all_proof: &AllProof<F, C, D>,
+        pages: &PageProof<F>,
+        io: &IoTape,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let image = pages.page_in(all_proof.public_values.root_start)?;
+        let updates = pages.updates(&all_proof.final_mem)?;
+        // the register page has the largest index
+        let (regs, main_pages) = updates.split_last().context("no pages are paged in")?;
+        ensure!(regs.index == REG_PAGE, "the registers are not paged in");
+        let n_pages = self.page_targets.len();
+        ensure!(
+            main_pages.len() <= n_pages,
+            "{} pages are paged in but the circuit has room for {n_pages}",
+            main_pages.len(),
+        );
+
+        let mut pw = PartialWitness::new();
+        let stark_proofs = &all_proof.multi_proof.stark_proofs;
+        for (target, proof) in self.proof_targets.iter().zip(stark_proofs) {
+            set_stark_proof_target(&mut pw, &target.proof, &proof.proof, self.zero_target);
+        }
+
+        let n_io_rows = self.tape_targets.len();
+        ensure!(
+            io.len() <= n_io_rows,
+            "streams hold {} bytes but the io table has {n_io_rows} rows",
+            io.len(),
+        );
+        for (targets, row) in self.tape_targets.iter().zip(io.rows::<F>(n_io_rows)) {
+            pw.set_target_arr(&targets.to_vec(), &row.to_vec());
+        }
+
+        let n_image_rows = self.image_targets.len();
+        ensure!(
+            image.len() <= n_image_rows,
+            "image has {} addresses but the meminit table has {n_image_rows} rows",
+            image.len(),
+        );
+        for (targets, row) in self.image_targets.iter().zip(image.rows::<F>(n_image_rows)) {
+            pw.set_target_arr(&targets.to_vec(), &row.to_vec());
+        }
+
+        let final_mem = &all_proof.final_mem;
+        let n_final_rows = self.final_targets.len();
+        ensure!(
+            final_mem.len() <= n_final_rows,
+            "final memory has {} addresses but the memfinal table has {n_final_rows} rows",
+            final_mem.len(),
+        );
+        for (targets, row) in self
+            .final_targets
+            .iter()
+            .zip(final_mem.rows::<F>(n_final_rows))
+        {
+            pw.set_target_arr(&targets.to_vec(), &row.to_vec());
+        }
+
+        set_page_target(&mut pw, &self.reg_targets, Some(regs));
+        for (i, targets) in self.page_targets.iter().enumerate() {
+            set_page_target(&mut pw, targets, main_pages.get(i));
+        }
+
+        let cpu_inputs = all_proof.public_values.cpu_inputs().to_vec();
+        let cpu_targets = &self.proof_targets[Table::Cpu as usize].public_inputs;
+        pw.set_target_arr(cpu_targets, &cpu_inputs);
+        self.circuit.prove(pw)
+    }
+
+    /// Verifies a proof generated by [`Self::prove`].
+    pub fn verify(&self, proof: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+        self.circuit.verify(proof)
+    }
+}
+
+/// Adds the targets of a row of tape columns. A row holds a byte of at most
+/// one stream.
+fn add_tape_row<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+) -> TapeCols<Target> {
+    let f_in = cb.add_virtual_bool_target_safe();
+    let f_out = cb.add_virtual_bool_target_safe();
+    let f_both = cb.and(f_in, f_out);
+    cb.assert_zero(f_both.target);
+    TapeCols {
+        f_in: f_in.target,
+        f_out: f_out.target,
+        pos: cb.add_virtual_target(),
+        byte: cb.add_virtual_target(),
+    }
+}
+
+/// Adds the targets of a row of the meminit table.
+fn add_meminit_row<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+) -> MemInitCols<Target> {
+    MemInitCols {
+        f_init: cb.add_virtual_bool_target_safe().target,
+        adr_seg: cb.add_virtual_target(),
+        adr_virt: cb.add_virtual_target(),
+        val: cb.add_virtual_target(),
+    }
+}
+
+/// Adds the targets of a row of the memfinal table.
+fn add_memfinal_row<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+) -> MemFinalCols<Target> {
+    MemFinalCols {
+        f_final: cb.add_virtual_bool_target_safe().target,
+        adr_seg: cb.add_virtual_target(),
+        adr_virt: cb.add_virtual_target(),
+        val: cb.add_virtual_target(),
+    }
+}
+
+/// The targets of a page of memory, as it is at the start and at the end of
+/// the execution.
+#[derive(Debug)]
+struct PageTargets {
+    /// 1 if the slot holds a page.
+    f_page: BoolTarget,
+    /// The index of the page.
+    index: Target,
+    /// The bits of `index`, in little-endian order.
+    index_bits: Vec<BoolTarget>,
+    /// The values of the page at the start.
+    start: Vec<Target>,
+    /// The values of the page at the end.
+    end: Vec<Target>,
+    /// 1 for each value whose address is in the final memory.
+    written: Vec<BoolTarget>,
+    /// The siblings of the path from the page to the root, from the leaf up.
+    siblings: Vec<HashOutTarget>,
+}
+
+/// Adds the targets of a page of `len` values of `val_bits` bits.
+fn add_page<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    len: usize,
+    val_bits: usize,
+) -> PageTargets {
+    let index = cb.add_virtual_target();
+    let mut add_vals = || -> Vec<_> {
+        let vals = cb.add_virtual_targets(len);
+        for &val in &vals {
+            cb.range_check(val, val_bits);
+        }
+        vals
+    };
+    let start = add_vals();
+    let end = add_vals();
+    PageTargets {
+        f_page: cb.add_virtual_bool_target_safe(),
+        index,
+        index_bits: cb.split_le(index, TREE_DEPTH),
+        start,
+        end,
+        written: (0..len)
+            .map(|_| cb.add_virtual_bool_target_safe())
+            .collect(),
+        siblings: cb.add_virtual_hashes(TREE_DEPTH),
+    }
+}
+
+/// Sets the targets of a page to `update`, or to an empty slot if `update`
+/// is `None`.
+fn set_page_target<F: RichField>(
+    pw: &mut PartialWitness<F>,
+    targets: &PageTargets,
+    update: Option<&PageUpdate<F>>,
+) {
+    let len = targets.start.len();
+    let empty = PageUpdate {
+        index: 0,
+        start: vec![0; len],
+        end: vec![0; len],
+        written: vec![false; len],
+        siblings: vec![HashOut::ZERO; TREE_DEPTH],
+    };
+    pw.set_bool_target(targets.f_page, update.is_some());
+    let update = update.unwrap_or(&empty);
+    pw.set_target(targets.index, F::from_canonical_usize(update.index));
+    let vals = targets.start.iter().zip(&update.start);
+    for (&target, &val) in vals.chain(targets.end.iter().zip(&update.end)) {
+        pw.set_target(target, F::from_canonical_u32(val));
+    }
+    for (&target, &written) in targets.written.iter().zip(&update.written) {
+        pw.set_bool_target(target, written);
+    }
+    for (&target, &hash) in targets.siblings.iter().zip(&update.siblings) {
+        pw.set_hash_target(target, hash);
+    }
+}
+
+/// Evaluates each column of `rows`, whose number is a power of two, at `x`.
+/// The value of a column in row `i` is the evaluation of its polynomial at
+/// `g^i`, where `g` generates the subgroup of order `rows.len()`, so by
+/// Lagrange interpolation its evaluation at `x` is
+/// `(x^n - 1) / n * sum_i col[i] * g^i / (x - g^i)`.
+fn eval_rows_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    rows: &[Vec<Target>],
+    x: ExtensionTarget<D>,
+) -> Vec<ExtensionTarget<D>> {
+    let n = rows.len();
+    let degree_bits = log2_strict(n);
+    let n_cols = rows.first().map_or(0, Vec::len);
+
+    let one = cb.one_extension();
+    let x_n = cb.exp_power_of_2_extension(x, degree_bits);
+    let vanishing = cb.sub_extension(x_n, one);
+    let n_inv = F::from_canonical_usize(n).inverse();
+    let factor = cb.mul_const_extension(n_inv, vanishing);
+
+    let zero = cb.zero_extension();
+    let mut evals = vec![zero; n_cols];
+    let g = F::primitive_root_of_unity(degree_bits);
+    for (row, g_i) in rows.iter().zip(g.powers()) {
+        // the Lagrange basis polynomial of row `i`, evaluated at `x`
+        let num = cb.mul_const_extension(g_i, factor);
+        let den = cb.add_const_extension(x, -g_i);
+        let basis = cb.div_extension(num, den);
+        for (eval, &val) in evals.iter_mut().zip(row) {
+            *eval = cb.scalar_mul_add_extension(val, basis, *eval);
+        }
+    }
+    evals
+}
+
+/// Evaluates `poly`, whose coefficients are fixed when building the circuit,
+/// at `x` using Horner's method.
+fn eval_poly_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    poly: &PolynomialCoeffs<F>,
+    x: ExtensionTarget<D>,
+) -> ExtensionTarget<D> {
+    let zero = cb.zero_extension();
+    poly.coeffs.iter().rev().fold(zero, |acc, &c| {
+        let acc = cb.mul_extension(acc, x);
+        cb.add_const_extension(acc, c)
+    })
+}
+
+/// Data shared by the verification of every table.
+struct CircuitCtx<'a, F: RichField + Extendable<D>, const D: usize> {
+    all_stark: &'a AllStark<F, D>,
+    cfg: &'a StarkConfig,
+}
+
+impl<'a, F: RichField + Extendable<D>, const D: usize> CircuitCtx<'a, F, D> {
+    /// Returns the total number of CTL helper columns of `table`, its number
+    /// of CTL Z polynomials, and its number of helper columns for each CTL.
+    fn num_ctl_helpers_zs(&self, table: Table) -> (usize, usize, Vec<usize>) {
+        CrossTableLookup::num_ctl_helpers_zs_all(
+            &self.all_stark.cross_table_lookups,
+            table as usize,
+            self.cfg.num_challenges,
+            self.all_stark.max_constraint_degree(),
+        )
+    }
+
+    /// Adds a virtual proof of `table`, along with its public inputs, to the
+    /// circuit.
+    fn add_proof<S: Stark<F, D>>(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        stark: &S,
+        table: Table,
+        degree_bits: [usize; NUM_TABLES],
+    ) -> StarkProofWithPublicInputsTarget<D> {
+        let (total_num_helpers, num_ctl_zs, _) = self.num_ctl_helpers_zs(table);
+        add_virtual_stark_proof_with_pis(
+            cb,
+            stark,
+            self.cfg,
+            degree_bits[table as usize],
+            total_num_helpers + num_ctl_zs,
+            num_ctl_zs,
+        )
+    }
+
+    /// Verifies the proof of a single table, given its challenges.
+    fn verify_table<C, S>(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        stark: &S,
+        table: Table,
+        proofs: &[StarkProofWithPublicInputsTarget<D>; NUM_TABLES],
+        ctl_challenges: &GrandProductChallengeSet<Target>,
+        challenges: StarkProofChallengesTarget<D>,
+    ) where
+        C: GenericConfig<D, F = F>,
+        C::Hasher: AlgebraicHasher<F>,
+        S: Stark<F, D>,
+    {
+        let proof = &proofs[table as usize];
+        let (total_num_helpers, _, num_helpers_by_ctl) = self.num_ctl_helpers_zs(table);
+        let ctl_vars = CtlCheckVarsTarget::from_proof(
+            table as usize,
+            &proof.proof,
+            &self.all_stark.cross_table_lookups,
+            ctl_challenges,
+            stark.num_lookup_helper_columns(self.cfg),
+            total_num_helpers,
+            &num_helpers_by_ctl,
+        );
+        verify_stark_proof_with_challenges_circuit::<F, C, S, D>(
+            cb,
+            stark,
+            &proof.proof,
+            &proof.public_inputs,
+            challenges,
+            Some(&ctl_vars),
+            self.cfg,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+
+    use super::AllRecursiveCircuit;
+    use crate::mem::page::{PagedMemory, REG_PAGE};
+    use crate::program::trace::ProgramRom;
+    use crate::prover::prove_all;
+    use crate::stark::AllStark;
+    use crate::vm::asm::assemble;
+    use crate::vm::Machine;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    const CFG: StarkConfig = StarkConfig::standard_fast_config();
+
+    #[test]
+    fn test_recursive_verify() {
+        let program = assemble(
+            "
+                li a0, 3
+                li a1, 256
+            loop:
+                sw a0, 0(a1)
+                addi a0, a0, -1
+                bne a0, zero, loop
+                lw a2, 0(a1)
+                xor a3, a2, a0
+                sltu a4, a0, a2
+                li a7, 93
+                ecall
+            ",
+        )
+        .unwrap();
+        let mut log = Machine::from_words(0, &program).run(1000).unwrap();
+        let pages = log.restrict_to_touched_pages::<F>();
+        let public_values = log.public_values();
+        let rom = ProgramRom::from_image(&log.image);
+        let tape = log.tape.clone();
+        let final_mem = log.final_mem();
+        let traces = log.gen_traces::<F>(CFG.fri_config.num_cap_elements());
+
+        let all_stark = AllStark::<F, D>::default();
+        let mut timing = TimingTree::default();
+        let all_proof = prove_all::<F, C, D>(
+            &all_stark,
+            &CFG,
+            traces,
+            public_values,
+            final_mem,
+            &mut timing,
+        )
+        .unwrap();
+
+        let degree_bits = all_proof.degree_bits(&CFG);
+        let circuit_cfg = CircuitConfig::standard_recursion_config();
+        // the program and its data share the first page
+        let n_pages = 1;
+        let circuit = AllRecursiveCircuit::<F, C, D>::new(
+            &all_stark,
+            &rom,
+            degree_bits,
+            n_pages,
+            &CFG,
+            circuit_cfg,
+        )
+        .unwrap();
+        let other_pages = PagedMemory::default().prove::<F>([0, REG_PAGE]);
+        assert!(circuit.prove(&all_proof, &other_pages, &tape).is_err());
+        let proof = circuit.prove(&all_proof, &pages, &tape).unwrap();
+        let cpu_inputs = public_values.cpu_inputs().to_vec();
+        assert_eq!(proof.public_inputs, cpu_inputs);
+        circuit.verify(proof).unwrap();
+    }
+}
@@ -1,9 +1,26 @@
+use plonky2::field::extension::Extendable;
 use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use starky::config::StarkConfig;
 use starky::cross_table_lookup::CrossTableLookup;
+use starky::stark::Stark;
 
-use crate::bytes::BYTES_WORD;
+use crate::arith::stark::ArithStark;
+use crate::bits::stark::BitStark;
+use crate::bytes::stark::ByteStark;
+use crate::bytes::{Alignment, BYTES_WORD};
 use crate::cpu::columns::N_MEM_CHANNELS;
-use crate::{arith, bits, bytes, cpu, mem};
+use crate::cpu::stark::CpuStark;
+use crate::io::stark::IoStark;
+use crate::mem::stark::MemStark;
+use crate::memfinal::stark::MemFinalStark;
+use crate::meminit::stark::MemInitStark;
+use crate::muldiv::stark::MulDivStark;
+use crate::program::stark::ProgramStark;
+use crate::{arith, bits, bytes, cpu, io, mem, memfinal, meminit, muldiv, program};
+
+/// The number of tables in [`AllStark`].
+pub const NUM_TABLES: usize = Table::Program as usize + 1;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Table {
@@ -11,11 +28,125 @@ pub enum Table {
     Bits,
     Bytes,
     Cpu,
+    Io,
     Mem,
+    MemFinal,
+    MemInit,
+    MulDiv,
+    Program,
+}
+
+impl Table {
+    /// Returns all tables, ordered by their index.
+    pub(crate) const fn all() -> [Self; NUM_TABLES] {
+        [
+            Self::Arith,
+            Self::Bits,
+            Self::Bytes,
+            Self::Cpu,
+            Self::Io,
+            Self::Mem,
+            Self::MemFinal,
+            Self::MemInit,
+            Self::MulDiv,
+            Self::Program,
+        ]
+    }
+}
+
+/// Every stark needed to prove a full execution, along with the cross-table
+/// lookups connecting them.
+#[derive(Debug, Clone)]
+pub struct AllStark<F: RichField + Extendable<D>, const D: usize> {
+    pub arith_stark: ArithStark<F, D>,
+    pub bit_stark: BitStark<F, D>,
+    pub byte_stark: ByteStark<F, D>,
+    pub cpu_stark: CpuStark<F, D>,
+    pub io_stark: IoStark<F, D>,
+    pub mem_stark: MemStark<F, D>,
+    pub memfinal_stark: MemFinalStark<F, D>,
+    pub meminit_stark: MemInitStark<F, D>,
+    pub muldiv_stark: MulDivStark<F, D>,
+    pub program_stark: ProgramStark<F, D>,
+    /// The alignment policy for loads and stores.
+    pub alignment: Alignment,
+    pub cross_table_lookups: Vec<CrossTableLookup<F>>,
 }
 
-fn all_cross_table_lookups<F: Field>() -> Vec<CrossTableLookup<F>> {
-    vec![ctl_arith(), ctl_bits(), ctl_bytes(), ctl_mem()]
+impl<F: RichField + Extendable<D>, const D: usize> Default for AllStark<F, D> {
+    fn default() -> Self {
+        Self::new(Alignment::default())
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> AllStark<F, D> {
+    /// Creates the starks proving executions under the given alignment
+    /// policy.
+    pub fn new(alignment: Alignment) -> Self {
+        Self {
+            arith_stark: Default::default(),
+            bit_stark: Default::default(),
+            byte_stark: Default::default(),
+            cpu_stark: Default::default(),
+            io_stark: Default::default(),
+            mem_stark: Default::default(),
+            memfinal_stark: Default::default(),
+            meminit_stark: Default::default(),
+            muldiv_stark: Default::default(),
+            program_stark: Default::default(),
+            alignment,
+            cross_table_lookups: all_cross_table_lookups(alignment),
+        }
+    }
+
+    /// Returns the number of lookup helper columns of each table.
+    pub(crate) fn num_lookups_helper_columns(&self, cfg: &StarkConfig) -> [usize; NUM_TABLES] {
+        [
+            self.arith_stark.num_lookup_helper_columns(cfg),
+            self.bit_stark.num_lookup_helper_columns(cfg),
+            self.byte_stark.num_lookup_helper_columns(cfg),
+            self.cpu_stark.num_lookup_helper_columns(cfg),
+            self.io_stark.num_lookup_helper_columns(cfg),
+            self.mem_stark.num_lookup_helper_columns(cfg),
+            self.memfinal_stark.num_lookup_helper_columns(cfg),
+            self.meminit_stark.num_lookup_helper_columns(cfg),
+            self.muldiv_stark.num_lookup_helper_columns(cfg),
+            self.program_stark.num_lookup_helper_columns(cfg),
+        ]
+    }
+
+    /// Returns the maximum constraint degree across all tables.
+    pub(crate) fn max_constraint_degree(&self) -> usize {
+        [
+            self.arith_stark.constraint_degree(),
+            self.bit_stark.constraint_degree(),
+            self.byte_stark.constraint_degree(),
+            self.cpu_stark.constraint_degree(),
+            self.io_stark.constraint_degree(),
+            self.mem_stark.constraint_degree(),
+            self.memfinal_stark.constraint_degree(),
+            self.meminit_stark.constraint_degree(),
+            self.muldiv_stark.constraint_degree(),
+            self.program_stark.constraint_degree(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap()
+    }
+}
+
+pub(crate) fn all_cross_table_lookups<F: Field>(alignment: Alignment) -> Vec<CrossTableLookup<F>> {
+    vec![
+        ctl_arith(),
+        ctl_bits(alignment),
+        ctl_shift(),
+        ctl_bytes(),
+        ctl_io(),
+        ctl_mem(),
+        ctl_memfinal(),
+        ctl_muldiv(),
+        ctl_program(),
+    ]
 }
 
 fn ctl_arith<F: Field>() -> CrossTableLookup<F> {
@@ -23,22 +154,35 @@ fn ctl_arith<F: Field>() -> CrossTableLookup<F> {
         cpu::stark::ctl_looking_arith_reg(),
         cpu::stark::ctl_looking_arith_imm(),
         cpu::stark::ctl_looking_branch(),
+        cpu::stark::ctl_looking_auipc(),
     ];
     let looked = arith::stark::ctl_looked();
     CrossTableLookup::new(looking, looked)
 }
 
-//TODO: Add `bits::stark::ctl_looked_shift` as second looked table.
-// See [0xPolygonZero/plonky2#1575](https://github.com/0xPolygonZero/plonky2/pull/1575)
-fn ctl_bits<F: Field>() -> CrossTableLookup<F> {
-    let looking = vec![
+fn ctl_bits<F: Field>(alignment: Alignment) -> CrossTableLookup<F> {
+    let mut looking = vec![
         cpu::stark::ctl_looking_bits_reg(),
         cpu::stark::ctl_looking_bits_imm(),
+        cpu::stark::ctl_looking_shift_mask(),
+        cpu::stark::ctl_looking_jalr_lsb(),
     ];
+    if alignment == Alignment::Strict {
+        looking.push(cpu::stark::ctl_looking_load_align());
+        looking.push(cpu::stark::ctl_looking_store_align());
+    }
     let looked = bits::stark::ctl_looked_logic();
     CrossTableLookup::new(looking, looked)
 }
 
+// A CTL has a single looked table, so shifts are looked up separately from
+// logic operations.
+fn ctl_shift<F: Field>() -> CrossTableLookup<F> {
+    let looking = vec![cpu::stark::ctl_looking_shift()];
+    let looked = bits::stark::ctl_looked_shift();
+    CrossTableLookup::new(looking, looked)
+}
+
 fn ctl_bytes<F: Field>() -> CrossTableLookup<F> {
     let looking = vec![
         cpu::stark::ctl_looking_read_bytes(),
@@ -48,10 +192,68 @@ fn ctl_bytes<F: Field>() -> CrossTableLookup<F> {
     CrossTableLookup::new(looking, looked)
 }
 
+fn ctl_io<F: Field>() -> CrossTableLookup<F> {
+    let looking = vec![cpu::stark::ctl_looking_io()];
+    let looked = io::stark::ctl_looked();
+    CrossTableLookup::new(looking, looked)
+}
+
 fn ctl_mem<F: Field>() -> CrossTableLookup<F> {
     let cpu = (0..N_MEM_CHANNELS).map(cpu::stark::ctl_looking_mem);
     let bytes = (0..BYTES_WORD).map(bytes::stark::ctl_looking_mem);
-    let looking = cpu.chain(bytes).collect();
+    let io = [io::stark::ctl_looking_mem(), io::stark::ctl_looking_buf()];
+    let init = [meminit::stark::ctl_looking_mem()];
+    let looking = cpu.chain(bytes).chain(io).chain(init).collect();
     let looked = mem::stark::ctl_looked();
     CrossTableLookup::new(looking, looked)
 }
+
+fn ctl_memfinal<F: Field>() -> CrossTableLookup<F> {
+    let looking = vec![mem::stark::ctl_looking_final()];
+    let looked = memfinal::stark::ctl_looked();
+    CrossTableLookup::new(looking, looked)
+}
+
+fn ctl_muldiv<F: Field>() -> CrossTableLookup<F> {
+    let looking = vec![cpu::stark::ctl_looking_muldiv()];
+    let looked = muldiv::stark::ctl_looked();
+    CrossTableLookup::new(looking, looked)
+}
+
+fn ctl_program<F: Field>() -> CrossTableLookup<F> {
+    let looking = vec![cpu::stark::ctl_looking_program()];
+    let looked = program::stark::ctl_looked();
+    CrossTableLookup::new(looking, looked)
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use starky::config::StarkConfig;
+
+    use super::*;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[test]
+    fn test_all_stark() {
+        const CFG: StarkConfig = StarkConfig::standard_fast_config();
+        let all_stark = AllStark::<F, D>::default();
+        assert_eq!(all_stark.cross_table_lookups.len(), 9);
+        assert_eq!(all_stark.max_constraint_degree(), 3);
+
+        // the arith, byte, memory and muldiv starks use lookups for range checking
+        let helpers = all_stark.num_lookups_helper_columns(&CFG);
+        assert!(helpers[Table::Arith as usize] > 0);
+        assert!(helpers[Table::Bytes as usize] > 0);
+        assert!(helpers[Table::Mem as usize] > 0);
+        assert!(helpers[Table::MulDiv as usize] > 0);
+        assert_eq!(helpers[Table::Cpu as usize], 0);
+        assert_eq!(helpers[Table::Io as usize], 0);
+        assert_eq!(helpers[Table::MemFinal as usize], 0);
+        assert_eq!(helpers[Table::MemInit as usize], 0);
+        assert_eq!(helpers[Table::Program as usize], 0);
+    }
+}
@@ -1,5 +1,12 @@
+use plonky2::field::extension::Extendable;
 use plonky2::field::packed::PackedField;
 use plonky2::field::types::Field;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
 
 /// Returns a field element constructed from its representation as a
 /// little-endian ordered iterator over field elements in {0, 1}.
@@ -10,6 +17,45 @@ pub(crate) fn felt_from_le_bits<P: PackedField>(bits: impl IntoIterator<Item = P
         .sum()
 }
 
+/// Circuit version of [`felt_from_le_bits`].
+pub(crate) fn felt_from_le_bits_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    bits: impl IntoIterator<Item = ExtensionTarget<D>>,
+) -> ExtensionTarget<D> {
+    let zero = cb.zero_extension();
+    bits.into_iter()
+        .zip(F::TWO.powers())
+        .fold(zero, |acc, (bit, base)| {
+            cb.mul_const_add_extension(base, bit, acc)
+        })
+}
+
+/// Returns a field element constructed from its representation as a
+/// little-endian ordered iterator over bytes.
+pub(crate) fn felt_from_le_bytes<P: PackedField>(bytes: impl IntoIterator<Item = P>) -> P {
+    let base = P::Scalar::from_canonical_u32(1 << 8);
+    bytes
+        .into_iter()
+        .zip(base.powers())
+        .map(|(byte, base)| byte * base)
+        .sum()
+}
+
+/// Circuit version of [`felt_from_le_bytes`].
+pub(crate) fn felt_from_le_bytes_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    bytes: impl IntoIterator<Item = ExtensionTarget<D>>,
+) -> ExtensionTarget<D> {
+    let zero = cb.zero_extension();
+    let base = F::from_canonical_u32(1 << 8);
+    bytes
+        .into_iter()
+        .zip(base.powers())
+        .fold(zero, |acc, (byte, base)| {
+            cb.mul_const_add_extension(base, byte, acc)
+        })
+}
+
 /// Returns the decomposition of `x` into bits in little-endian order.
 pub(crate) fn u32_to_le_bits<F: Field>(x: u32) -> [F; 32] {
     core::array::from_fn(|i| F::from_bool(x & (1 << i) != 0))
@@ -20,6 +66,34 @@ pub(crate) fn u8_to_le_bits<F: Field>(x: u8) -> [F; 8] {
     core::array::from_fn(|i| F::from_bool(x & (1 << i) != 0))
 }
 
+/// Hashes `rows` one at a time: starting from the zero hash, each row `r`
+/// updates the hash `h` to `hash(h || r)`. Unlike hashing all rows at once,
+/// this lets a circuit skip rows which aren't part of the hashed data.
+pub(crate) fn hash_rows<F: RichField>(rows: impl IntoIterator<Item = Vec<F>>) -> HashOut<F> {
+    rows.into_iter().fold(HashOut::ZERO, |h, row| {
+        let elements: Vec<F> = h.elements.into_iter().chain(row).collect();
+        PoseidonHash::hash_no_pad(&elements)
+    })
+}
+
+/// Circuit version of [`hash_rows`], where only the rows whose flag is set
+/// are hashed.
+pub(crate) fn hash_rows_circuit<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    rows: impl IntoIterator<Item = (BoolTarget, Vec<Target>)>,
+) -> HashOutTarget {
+    let zero = cb.zero();
+    let init = HashOutTarget {
+        elements: [zero; 4],
+    };
+    rows.into_iter().fold(init, |h, (flag, row)| {
+        let inputs = h.elements.into_iter().chain(row).collect();
+        let next = cb.hash_n_to_hash_no_pad::<PoseidonHash>(inputs);
+        let elements = core::array::from_fn(|i| cb.select(flag, next.elements[i], h.elements[i]));
+        HashOutTarget { elements }
+    })
+}
+
 /// Returns [0,1,..,N].
 pub(crate) const fn indices_arr<const N: usize>() -> [usize; N] {
     let mut arr = [0; N];
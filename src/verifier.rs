@@ -0,0 +1,350 @@
+use anyhow::{ensure, Context, Result};
+use plonky2::field::extension::Extendable;
+use plonky2::field::polynomial::PolynomialCoeffs;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::plonk::config::GenericConfig;
+use starky::config::StarkConfig;
+use starky::cross_table_lookup::{get_ctl_vars_from_proofs, verify_cross_table_lookups};
+use starky::proof::StarkOpeningSet;
+use starky::verifier::verify_stark_proof_with_challenges;
+
+use crate::io::columns::N_TAPE_COLS;
+use crate::io::trace::IoTape;
+use crate::memfinal::trace::FinalMem;
+use crate::meminit::trace::MemImage;
+use crate::program::columns::N_ROM_COLS;
+use crate::program::trace::ProgramRom;
+use crate::proof::{AllProof, AllProofChallenges, SegmentProof};
+use crate::stark::{AllStark, Table, NUM_TABLES};
+
+/// Verifies a proof generated by [`prove_all`](crate::prover::prove_all),
+/// for an execution of the program `rom`, starting from the memory `image`,
+/// which read and wrote the streams of `io`, and ended with HALT. The memory
+/// in which execution ended is an output of the proof, identified by the
+/// final hash of its public values.
+pub fn verify_all<F, C, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    all_proof: AllProof<F, C, D>,
+    rom: &ProgramRom,
+    image: &MemImage,
+    io: &IoTape,
+    cfg: &StarkConfig,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    // an execution which didn't halt is a segment, which may stop after any
+    // instruction
+    ensure!(all_proof.public_values.halted, "execution did not halt");
+    verify_execution(all_stark, all_proof, rom, image, io, cfg)
+}
+
+/// Like [`verify_all`], but also accepts an execution which stopped before
+/// HALT, as the segments of [`verify_segments`] do.
+fn verify_execution<F, C, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    all_proof: AllProof<F, C, D>,
+    rom: &ProgramRom,
+    image: &MemImage,
+    io: &IoTape,
+    cfg: &StarkConfig,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    // the exit code is only constrained by HALT
+    ensure!(
+        all_proof.public_values.halted || all_proof.public_values.exit_code == 0,
+        "execution did not halt but has an exit code",
+    );
+
+    // every table uses cross-table lookups, but a malformed proof may omit
+    // their openings
+    let mut ctl_zs_first: [Vec<F>; NUM_TABLES] = Default::default();
+    for (i, zs) in ctl_zs_first.iter_mut().enumerate() {
+        *zs = all_proof.multi_proof.stark_proofs[i]
+            .proof
+            .openings
+            .ctl_zs_first
+            .clone()
+            .with_context(|| format!("table {i} has no cross-table lookup openings"))?;
+    }
+
+    let AllProofChallenges {
+        stark_challenges,
+        ctl_challenges,
+    } = all_proof.get_challenges(cfg);
+
+    let num_lookup_columns = all_stark.num_lookups_helper_columns(cfg);
+    let ctl_vars = get_ctl_vars_from_proofs(
+        &all_proof.multi_proof,
+        &all_stark.cross_table_lookups,
+        &ctl_challenges,
+        &num_lookup_columns,
+        all_stark.max_constraint_degree(),
+    );
+
+    let proofs = &all_proof.multi_proof.stark_proofs;
+    let args = |table: Table| {
+        let i = table as usize;
+        (
+            &proofs[i].proof,
+            &stark_challenges[i],
+            Some(&ctl_vars[i][..]),
+        )
+    };
+
+    let (proof, challenges, vars) = args(Table::Arith);
+    verify_stark_proof_with_challenges(&all_stark.arith_stark, proof, challenges, vars, &[], cfg)?;
+
+    let (proof, challenges, vars) = args(Table::Bits);
+    verify_stark_proof_with_challenges(&all_stark.bit_stark, proof, challenges, vars, &[], cfg)?;
+
+    let (proof, challenges, vars) = args(Table::Bytes);
+    verify_stark_proof_with_challenges(&all_stark.byte_stark, proof, challenges, vars, &[], cfg)?;
+
+    let (proof, challenges, vars) = args(Table::Cpu);
+    let cpu_inputs = all_proof.public_values.cpu_inputs().to_vec();
+    let cpu_stark = &all_stark.cpu_stark;
+    verify_stark_proof_with_challenges(cpu_stark, proof, challenges, vars, &cpu_inputs, cfg)?;
+
+    let (proof, challenges, vars) = args(Table::Io);
+    verify_stark_proof_with_challenges(&all_stark.io_stark, proof, challenges, vars, &[], cfg)?;
+    let public_values = &all_proof.public_values;
+    ensure!(
+        public_values.input_hash == io.input_hash()
+            && public_values.output_hash == io.output_hash(),
+        "io hashes do not match the streams",
+    );
+    ensure!(
+        (public_values.input_start, public_values.input_end) == (io.input_start, io.input_end())
+            && (public_values.output_start, public_values.output_end)
+                == (io.output_start, io.output_end()),
+        "io offsets do not match the streams",
+    );
+    let degree_bits = proof.recover_degree_bits(cfg);
+    verify_tape_openings(io, &proof.openings, challenges.stark_zeta, degree_bits)?;
+
+    let (proof, challenges, vars) = args(Table::Mem);
+    verify_stark_proof_with_challenges(&all_stark.mem_stark, proof, challenges, vars, &[], cfg)?;
+
+    let (proof, challenges, vars) = args(Table::MemFinal);
+    let memfinal_stark = &all_stark.memfinal_stark;
+    verify_stark_proof_with_challenges(memfinal_stark, proof, challenges, vars, &[], cfg)?;
+    let final_mem = &all_proof.final_mem;
+    ensure!(
+        all_proof.public_values.final_hash == final_mem.hash(),
+        "final hash does not match the final memory",
+    );
+    let degree_bits = proof.recover_degree_bits(cfg);
+    verify_final_openings(
+        final_mem,
+        &proof.openings,
+        challenges.stark_zeta,
+        degree_bits,
+    )?;
+
+    let (proof, challenges, vars) = args(Table::MemInit);
+    let meminit_stark = &all_stark.meminit_stark;
+    verify_stark_proof_with_challenges(meminit_stark, proof, challenges, vars, &[], cfg)?;
+    ensure!(
+        all_proof.public_values.image_hash == image.hash(),
+        "image hash does not match the memory image",
+    );
+    let degree_bits = proof.recover_degree_bits(cfg);
+    verify_image_openings(image, &proof.openings, challenges.stark_zeta, degree_bits)?;
+
+    let (proof, challenges, vars) = args(Table::MulDiv);
+    let muldiv_stark = &all_stark.muldiv_stark;
+    verify_stark_proof_with_challenges(muldiv_stark, proof, challenges, vars, &[], cfg)?;
+
+    let (proof, challenges, vars) = args(Table::Program);
+    let program_stark = &all_stark.program_stark;
+    verify_stark_proof_with_challenges(program_stark, proof, challenges, vars, &[], cfg)?;
+    ensure!(
+        all_proof.public_values.program_hash == rom.hash(),
+        "program hash does not match the program",
+    );
+    let degree_bits = proof.recover_degree_bits(cfg);
+    verify_rom_openings(rom, &proof.openings, challenges.stark_zeta, degree_bits)?;
+
+    verify_cross_table_lookups::<F, D, NUM_TABLES>(
+        &all_stark.cross_table_lookups,
+        ctl_zs_first,
+        None,
+        cfg,
+    )
+}
+
+/// Verifies the proofs of the consecutive segments of a single execution,
+/// which starts with the memory whose pages have the Merkle root `root`, and
+/// reads and writes the streams of `io`. Returns the root of the memory in
+/// which execution halted.
+///
+/// Each segment must start with the pc, memory root and stream positions in
+/// which the previous one stopped, and only the last segment may halt. The
+/// program of each segment is read from the pages it touches.
+pub fn verify_segments<F, C, const D: usize>(
+    all_stark: &AllStark<F, D>,
+    root: HashOut<F>,
+    io: &IoTape,
+    segments: Vec<SegmentProof<F, C, D>>,
+    cfg: &StarkConfig,
+) -> Result<HashOut<F>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    ensure!(!segments.is_empty(), "there are no segments to verify");
+    let n_segments = segments.len();
+    let mut root = root;
+    let mut pc_end = None;
+    let (mut input_pos, mut output_pos) = (io.input_start, io.output_start);
+    for (i, segment) in segments.into_iter().enumerate() {
+        let SegmentProof { proof, pages } = segment;
+        let public_values = proof.public_values;
+        if let Some(pc_end) = pc_end {
+            ensure!(
+                public_values.pc_start == pc_end,
+                "segment {i} does not start where the previous segment stopped",
+            );
+        }
+        ensure!(
+            public_values.halted == (i + 1 == n_segments),
+            "only the last segment may halt, and it must halt",
+        );
+        ensure!(
+            public_values.root_start == root,
+            "segment {i} does not start with the memory root",
+        );
+        ensure!(
+            (public_values.input_start, public_values.output_start) == (input_pos, output_pos),
+            "segment {i} does not resume the streams where the previous segment stopped",
+        );
+
+        let image = pages
+            .page_in(root)
+            .with_context(|| format!("segment {i} does not start with the memory root"))?;
+        let rom = ProgramRom::from_image(&image.main_bytes());
+        let segment_io = io
+            .chunk(
+                public_values.input_start..public_values.input_end,
+                public_values.output_start..public_values.output_end,
+            )
+            .with_context(|| format!("segment {i} transfers bytes outside the streams"))?;
+        let final_mem = proof.final_mem.clone();
+        verify_execution(all_stark, proof, &rom, &image, &segment_io, cfg)?;
+        // every address accessed by the segment must have been paged in, as
+        // memory outside the image reads as 0
+        root = pages
+            .page_out(&final_mem)
+            .with_context(|| format!("segment {i} accesses memory which was not paged in"))?;
+        ensure!(
+            public_values.root_end == root,
+            "segment {i} does not stop with its memory root",
+        );
+        pc_end = Some(public_values.pc_end);
+        (input_pos, output_pos) = (public_values.input_end, public_values.output_end);
+    }
+    ensure!(
+        (input_pos, output_pos) == (io.input_end(), io.output_end()),
+        "segments do not transfer the whole streams",
+    );
+    Ok(root)
+}
+
+/// Checks that the ROM columns of the program table, opened at `zeta`, hold
+/// the instructions of `rom`.
+fn verify_rom_openings<F, const D: usize>(
+    rom: &ProgramRom,
+    openings: &StarkOpeningSet<F, D>,
+    zeta: F::Extension,
+    degree_bits: usize,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+{
+    let rom_polys = rom.rom_polys::<F>(degree_bits)?;
+    let rom_openings = &openings.local_values[..N_ROM_COLS];
+    ensure!(
+        openings_match(&rom_polys, rom_openings, zeta),
+        "program table does not match the program"
+    );
+    Ok(())
+}
+
+/// Checks that the columns of the memfinal table, opened at `zeta`, hold the
+/// values of `final_mem`.
+fn verify_final_openings<F, const D: usize>(
+    final_mem: &FinalMem,
+    openings: &StarkOpeningSet<F, D>,
+    zeta: F::Extension,
+    degree_bits: usize,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+{
+    let final_polys = final_mem.final_polys::<F>(degree_bits)?;
+    ensure!(
+        openings_match(&final_polys, &openings.local_values, zeta),
+        "memfinal table does not match the final memory"
+    );
+    Ok(())
+}
+
+/// Checks that the columns of the meminit table, opened at `zeta`, hold the
+/// bytes of `image`.
+fn verify_image_openings<F, const D: usize>(
+    image: &MemImage,
+    openings: &StarkOpeningSet<F, D>,
+    zeta: F::Extension,
+    degree_bits: usize,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+{
+    let image_polys = image.image_polys::<F>(degree_bits)?;
+    ensure!(
+        openings_match(&image_polys, &openings.local_values, zeta),
+        "meminit table does not match the memory image"
+    );
+    Ok(())
+}
+
+/// Checks that the tape columns of the io table, opened at `zeta`, hold the
+/// bytes of `io`.
+fn verify_tape_openings<F, const D: usize>(
+    io: &IoTape,
+    openings: &StarkOpeningSet<F, D>,
+    zeta: F::Extension,
+    degree_bits: usize,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+{
+    let tape_polys = io.tape_polys::<F>(degree_bits)?;
+    let tape_openings = &openings.local_values[..N_TAPE_COLS];
+    ensure!(
+        openings_match(&tape_polys, tape_openings, zeta),
+        "io table does not match the streams"
+    );
+    Ok(())
+}
+
+/// Returns true if each of `openings` is the evaluation of the corresponding
+/// polynomial at `zeta`.
+fn openings_match<F, const D: usize>(
+    polys: &[PolynomialCoeffs<F>],
+    openings: &[F::Extension],
+    zeta: F::Extension,
+) -> bool
+where
+    F: RichField + Extendable<D>,
+{
+    polys
+        .iter()
+        .zip(openings)
+        .all(|(poly, &opening)| poly.to_extension::<D>().eval(zeta) == opening)
+}
@@ -0,0 +1,610 @@
+//! A small RV32IM assembler for writing test programs.
+//!
+//! Each line holds at most one instruction, optionally preceded by any number
+//! of `label:` definitions. Comments start with `#`. Besides the RV32I and
+//! RV32M instructions understood by [`decode`](crate::vm::decode::decode),
+//! including `lui`/`auipc` and `ecall`/`ebreak`, the pseudo-instructions `li`,
+//! `mv`, `j`, `ret` and `nop` are supported. Branch and jump targets may be labels or numeric offsets.
+
+use core::fmt;
+
+use hashbrown::HashMap;
+
+use crate::cpu::control_flow::INSTRUCTION_BYTES;
+use crate::vm::decode::{
+    AUIPC, BRANCH, EBREAK_WORD, ECALL_WORD, FUNCT7_ALT, FUNCT7_MULDIV, JAL, JALR, LOAD, LUI, OP,
+    OP_IMM, STORE,
+};
+
+/// An error returned when assembling a program fails.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AsmError {
+    /// The line on which the error occurred, starting at 1.
+    pub line: usize,
+    /// A description of the error.
+    pub msg: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// The operand format and fixed fields of a mnemonic.
+#[derive(Clone, Copy, Debug)]
+enum Format {
+    /// `rd, rs1, rs2`
+    R { funct3: u32, funct7: u32 },
+    /// `rd, rs1, imm`
+    I { funct3: u32 },
+    /// `rd, rs1, shamt`
+    Shift { funct3: u32, funct7: u32 },
+    /// `rd, imm(rs1)`
+    Load { funct3: u32 },
+    /// `rs2, imm(rs1)`
+    Store { funct3: u32 },
+    /// `rs1, rs2, target`
+    Branch { funct3: u32 },
+    /// `rd, imm`
+    U { opcode: u32 },
+    /// `[rd,] target`
+    Jal,
+    /// `rd, imm(rs1)`, `rd, rs1, imm` or `rs1`
+    Jalr,
+}
+
+fn lookup(mnemonic: &str) -> Option<Format> {
+    use Format::*;
+    let fmt = match mnemonic {
+        "add" => R {
+            funct3: 0b000,
+            funct7: 0,
+        },
+        "sub" => R {
+            funct3: 0b000,
+            funct7: FUNCT7_ALT,
+        },
+        "sll" => R {
+            funct3: 0b001,
+            funct7: 0,
+        },
+        "slt" => R {
+            funct3: 0b010,
+            funct7: 0,
+        },
+        "sltu" => R {
+            funct3: 0b011,
+            funct7: 0,
+        },
+        "xor" => R {
+            funct3: 0b100,
+            funct7: 0,
+        },
+        "srl" => R {
+            funct3: 0b101,
+            funct7: 0,
+        },
+        "sra" => R {
+            funct3: 0b101,
+            funct7: FUNCT7_ALT,
+        },
+        "or" => R {
+            funct3: 0b110,
+            funct7: 0,
+        },
+        "and" => R {
+            funct3: 0b111,
+            funct7: 0,
+        },
+        "mul" => R {
+            funct3: 0b000,
+            funct7: FUNCT7_MULDIV,
+        },
+        "mulh" => R {
+            funct3: 0b001,
+            funct7: FUNCT7_MULDIV,
+        },
+        "mulhsu" => R {
+            funct3: 0b010,
+            funct7: FUNCT7_MULDIV,
+        },
+        "mulhu" => R {
+            funct3: 0b011,
+            funct7: FUNCT7_MULDIV,
+        },
+        "div" => R {
+            funct3: 0b100,
+            funct7: FUNCT7_MULDIV,
+        },
+        "divu" => R {
+            funct3: 0b101,
+            funct7: FUNCT7_MULDIV,
+        },
+        "rem" => R {
+            funct3: 0b110,
+            funct7: FUNCT7_MULDIV,
+        },
+        "remu" => R {
+            funct3: 0b111,
+            funct7: FUNCT7_MULDIV,
+        },
+        "addi" => I { funct3: 0b000 },
+        "slti" => I { funct3: 0b010 },
+        "sltiu" => I { funct3: 0b011 },
+        "xori" => I { funct3: 0b100 },
+        "ori" => I { funct3: 0b110 },
+        "andi" => I { funct3: 0b111 },
+        "slli" => Shift {
+            funct3: 0b001,
+            funct7: 0,
+        },
+        "srli" => Shift {
+            funct3: 0b101,
+            funct7: 0,
+        },
+        "srai" => Shift {
+            funct3: 0b101,
+            funct7: FUNCT7_ALT,
+        },
+        "lb" => Load { funct3: 0b000 },
+        "lh" => Load { funct3: 0b001 },
+        "lw" => Load { funct3: 0b010 },
+        "lbu" => Load { funct3: 0b100 },
+        "lhu" => Load { funct3: 0b101 },
+        "sb" => Store { funct3: 0b000 },
+        "sh" => Store { funct3: 0b001 },
+        "sw" => Store { funct3: 0b010 },
+        "beq" => Branch { funct3: 0b000 },
+        "bne" => Branch { funct3: 0b001 },
+        "blt" => Branch { funct3: 0b100 },
+        "bge" => Branch { funct3: 0b101 },
+        "bltu" => Branch { funct3: 0b110 },
+        "bgeu" => Branch { funct3: 0b111 },
+        "lui" => U { opcode: LUI },
+        "auipc" => U { opcode: AUIPC },
+        "jal" => Jal,
+        "jalr" => Jalr,
+        _ => return None,
+    };
+    Some(fmt)
+}
+
+/// Returns the number of a register given its `x` name or ABI name.
+fn reg(name: &str) -> Result<u32, String> {
+    const ABI_NAMES: [&str; 32] = [
+        "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+        "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+        "t5", "t6",
+    ];
+    if name == "fp" {
+        return Ok(8);
+    }
+    let num = name.strip_prefix('x').and_then(|n| n.parse::<u32>().ok());
+    num.filter(|&n| n < 32)
+        .or_else(|| {
+            ABI_NAMES
+                .iter()
+                .position(|&abi| abi == name)
+                .map(|n| n as u32)
+        })
+        .ok_or_else(|| format!("invalid register `{name}`"))
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal integer.
+fn int(s: &str) -> Result<i64, String> {
+    let (neg, abs) = match s.strip_prefix('-') {
+        Some(abs) => (true, abs),
+        None => (false, s),
+    };
+    let val = match abs.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => abs.parse(),
+    };
+    let val = val.map_err(|_| format!("invalid integer `{s}`"))?;
+    Ok(if neg { -val } else { val })
+}
+
+/// Parses an immediate which must lie in `min..=max`.
+fn imm(s: &str, min: i64, max: i64) -> Result<u32, String> {
+    let val = int(s)?;
+    if val < min || val > max {
+        return Err(format!("immediate {val} is out of range {min}..={max}"));
+    }
+    Ok(val as u32)
+}
+
+/// Parses a 12-bit signed immediate.
+fn imm12(s: &str) -> Result<u32, String> {
+    imm(s, -(1 << 11), (1 << 11) - 1)
+}
+
+/// Parses a memory operand `imm(rs1)`, where `imm` may be omitted.
+fn mem_operand(s: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid memory operand `{s}`");
+    let (offset, rest) = s.split_once('(').ok_or_else(invalid)?;
+    let base = rest.strip_suffix(')').ok_or_else(invalid)?;
+    let offset = match offset.trim() {
+        "" => 0,
+        offset => imm12(offset)?,
+    };
+    Ok((offset, reg(base.trim())?))
+}
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '.')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+const fn encode_r(opcode: u32, funct3: u32, funct7: u32, rd: u32, rs1: u32, rs2: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+const fn encode_i(opcode: u32, funct3: u32, rd: u32, rs1: u32, imm: u32) -> u32 {
+    ((imm & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+const fn encode_s(opcode: u32, funct3: u32, rs1: u32, rs2: u32, imm: u32) -> u32 {
+    (((imm >> 5) & 0x7f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3 << 12)
+        | ((imm & 0x1f) << 7)
+        | opcode
+}
+
+const fn encode_b(funct3: u32, rs1: u32, rs2: u32, imm: u32) -> u32 {
+    (((imm >> 12) & 1) << 31)
+        | (((imm >> 5) & 0x3f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3 << 12)
+        | (((imm >> 1) & 0xf) << 8)
+        | (((imm >> 11) & 1) << 7)
+        | BRANCH
+}
+
+const fn encode_u(opcode: u32, rd: u32, imm: u32) -> u32 {
+    ((imm & 0xfffff) << 12) | (rd << 7) | opcode
+}
+
+const fn encode_j(rd: u32, imm: u32) -> u32 {
+    (((imm >> 20) & 1) << 31)
+        | (((imm >> 1) & 0x3ff) << 21)
+        | (((imm >> 11) & 1) << 20)
+        | (((imm >> 12) & 0xff) << 12)
+        | (rd << 7)
+        | JAL
+}
+
+/// Splits `val` into the upper and lower immediates of `lui` and `addi`,
+/// such that `(hi << 12) + lo == val`.
+const fn split_imm(val: u32) -> (u32, u32) {
+    let lo = ((val << 20) as i32 >> 20) as u32;
+    let hi = val.wrapping_sub(lo) >> 12;
+    (hi, lo)
+}
+
+/// A single instruction or pseudo-instruction, before labels are resolved.
+#[derive(Clone, Debug)]
+struct Statement<'a> {
+    line: usize,
+    pc: u32,
+    mnemonic: &'a str,
+    operands: Vec<&'a str>,
+}
+
+impl Statement<'_> {
+    /// The number of instruction words this statement assembles to.
+    fn len(&self) -> Result<u32, String> {
+        if self.mnemonic != "li" {
+            return Ok(1);
+        }
+        let [_, val] = self.operands[..] else {
+            return Err("expected 2 operands".into());
+        };
+        let (hi, lo) = split_imm(li_imm(val)?);
+        Ok(if hi != 0 && lo != 0 { 2 } else { 1 })
+    }
+
+    /// Returns the operands, checking that there are exactly `N` of them.
+    fn operands<const N: usize>(&self) -> Result<[&str; N], String> {
+        self.operands[..]
+            .try_into()
+            .map_err(|_| format!("expected {N} operands, found {}", self.operands.len()))
+    }
+
+    /// Resolves a branch or jump target to an offset from the current pc.
+    fn offset(&self, target: &str, labels: &HashMap<&str, u32>, bits: u32) -> Result<u32, String> {
+        let offset = match labels.get(target) {
+            Some(&adr) => adr.wrapping_sub(self.pc) as i32 as i64,
+            None if is_label(target) => return Err(format!("undefined label `{target}`")),
+            None => int(target)?,
+        };
+        if offset % 2 != 0 {
+            return Err(format!("offset {offset} is not a multiple of 2"));
+        }
+        let max = 1i64 << (bits - 1);
+        if offset < -max || offset >= max {
+            return Err(format!("offset {offset} is out of range"));
+        }
+        Ok(offset as u32)
+    }
+
+    fn encode(&self, labels: &HashMap<&str, u32>) -> Result<Vec<u32>, String> {
+        let word = match self.mnemonic {
+            "nop" => {
+                self.operands::<0>()?;
+                encode_i(OP_IMM, 0, 0, 0, 0)
+            }
+            "mv" => {
+                let [rd, rs1] = self.operands()?;
+                encode_i(OP_IMM, 0, reg(rd)?, reg(rs1)?, 0)
+            }
+            "li" => {
+                let [rd, val] = self.operands()?;
+                let rd = reg(rd)?;
+                let (hi, lo) = split_imm(li_imm(val)?);
+                return Ok(match (hi, lo) {
+                    (0, lo) => vec![encode_i(OP_IMM, 0, rd, 0, lo)],
+                    (hi, 0) => vec![encode_u(LUI, rd, hi)],
+                    (hi, lo) => vec![encode_u(LUI, rd, hi), encode_i(OP_IMM, 0, rd, rd, lo)],
+                });
+            }
+            "j" => {
+                let [target] = self.operands()?;
+                encode_j(0, self.offset(target, labels, 21)?)
+            }
+            "ret" => {
+                self.operands::<0>()?;
+                encode_i(JALR, 0, 0, 1, 0)
+            }
+            "ecall" => {
+                self.operands::<0>()?;
+                ECALL_WORD
+            }
+            "ebreak" => {
+                self.operands::<0>()?;
+                EBREAK_WORD
+            }
+            mnemonic => {
+                let fmt =
+                    lookup(mnemonic).ok_or_else(|| format!("unknown mnemonic `{mnemonic}`"))?;
+                self.encode_format(fmt, labels)?
+            }
+        };
+        Ok(vec![word])
+    }
+
+    fn encode_format(&self, fmt: Format, labels: &HashMap<&str, u32>) -> Result<u32, String> {
+        let word = match fmt {
+            Format::R { funct3, funct7 } => {
+                let [rd, rs1, rs2] = self.operands()?;
+                encode_r(OP, funct3, funct7, reg(rd)?, reg(rs1)?, reg(rs2)?)
+            }
+            Format::I { funct3 } => {
+                let [rd, rs1, imm] = self.operands()?;
+                encode_i(OP_IMM, funct3, reg(rd)?, reg(rs1)?, imm12(imm)?)
+            }
+            Format::Shift { funct3, funct7 } => {
+                let [rd, rs1, shamt] = self.operands()?;
+                let shamt = imm(shamt, 0, 31)?;
+                encode_i(OP_IMM, funct3, reg(rd)?, reg(rs1)?, (funct7 << 5) | shamt)
+            }
+            Format::Load { funct3 } => {
+                let [rd, mem] = self.operands()?;
+                let (offset, rs1) = mem_operand(mem)?;
+                encode_i(LOAD, funct3, reg(rd)?, rs1, offset)
+            }
+            Format::Store { funct3 } => {
+                let [rs2, mem] = self.operands()?;
+                let (offset, rs1) = mem_operand(mem)?;
+                encode_s(STORE, funct3, rs1, reg(rs2)?, offset)
+            }
+            Format::Branch { funct3 } => {
+                let [rs1, rs2, target] = self.operands()?;
+                let offset = self.offset(target, labels, 13)?;
+                encode_b(funct3, reg(rs1)?, reg(rs2)?, offset)
+            }
+            Format::U { opcode } => {
+                let [rd, imm20] = self.operands()?;
+                encode_u(opcode, reg(rd)?, imm(imm20, 0, (1 << 20) - 1)?)
+            }
+            Format::Jal => {
+                let (rd, target) = match self.operands[..] {
+                    [target] => (1, target),
+                    [rd, target] => (reg(rd)?, target),
+                    _ => return Err("expected 1 or 2 operands".into()),
+                };
+                encode_j(rd, self.offset(target, labels, 21)?)
+            }
+            Format::Jalr => {
+                let (rd, offset, rs1) = match self.operands[..] {
+                    [rs1] => (1, 0, reg(rs1)?),
+                    [rd, mem] => {
+                        let (offset, rs1) = mem_operand(mem)?;
+                        (reg(rd)?, offset, rs1)
+                    }
+                    [rd, rs1, offset] => (reg(rd)?, imm12(offset)?, reg(rs1)?),
+                    _ => return Err("expected 1 to 3 operands".into()),
+                };
+                encode_i(JALR, 0, rd, rs1, offset)
+            }
+        };
+        Ok(word)
+    }
+}
+
+/// Parses the immediate of `li`, which may be any signed or unsigned 32-bit
+/// value.
+fn li_imm(s: &str) -> Result<u32, String> {
+    imm(s, i32::MIN.into(), u32::MAX.into())
+}
+
+/// Assembles `src` into instruction words, with the first instruction at
+/// address 0. Since branch and jump offsets are relative, the program may be
+/// loaded at any address.
+pub(crate) fn assemble(src: &str) -> Result<Vec<u32>, AsmError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut pc = 0;
+
+    for (i, line) in src.lines().enumerate() {
+        let err = |msg| AsmError { line: i + 1, msg };
+        let mut line = line.split_once('#').map_or(line, |(code, _)| code).trim();
+
+        while let Some((label, rest)) = line.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(err(format!("invalid label `{label}`")));
+            }
+            if labels.insert(label, pc).is_some() {
+                return Err(err(format!("duplicate label `{label}`")));
+            }
+            line = rest.trim();
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let operands = match operands.trim() {
+            "" => vec![],
+            operands => operands.split(',').map(str::trim).collect(),
+        };
+        let statement = Statement {
+            line: i + 1,
+            pc,
+            mnemonic,
+            operands,
+        };
+        pc += statement.len().map_err(err)? * INSTRUCTION_BYTES as u32;
+        statements.push(statement);
+    }
+
+    let mut words = Vec::new();
+    for statement in statements {
+        let encoded = statement.encode(&labels).map_err(|msg| AsmError {
+            line: statement.line,
+            msg,
+        })?;
+        words.extend(encoded);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::decode::decode;
+    use crate::vm::opcode::Opcode;
+
+    #[test]
+    fn test_assemble_instructions() {
+        let src = "
+            add x3, x1, x2
+            sub t0, a0, a1
+            sra x1, x2, x3
+            and x1, x2, x3
+            addi sp, sp, -16
+            sltiu x1, x2, 2047
+            xori x1, x2, -1
+            ori x1, x2, 0x7f
+            slli x1, x2, 31
+            srai x1, x2, 7
+            lb x1, -1(x2)
+            lw ra, 0(sp)
+            lhu x1, -2048(x2)
+            sb x1, -1(x2)
+            sw ra, 12(sp)
+            lui x5, 0xfffff
+            auipc x6, 1
+            jalr x1, 4(x2)
+            mul x3, x1, x2
+            mulhsu x5, x6, x7
+            remu x10, x11, x12
+            ecall
+            ebreak
+        ";
+        let expected = [
+            0x002081b3, 0x40b502b3, 0x403150b3, 0x003170b3, 0xff010113, 0x7ff13093, 0xfff14093,
+            0x07f16093, 0x01f11093, 0x40715093, 0xfff10083, 0x00012083, 0x80015083, 0xfe110fa3,
+            0x00112623, 0xfffff2b7, 0x00001317, 0x004100e7, 0x022081b3, 0x027322b3, 0x02c5f533,
+            0x00000073, 0x00100073,
+        ];
+        assert_eq!(assemble(src).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_assemble_labels() {
+        let src = "
+            start:
+                li a0, 10
+                li a1, -2048
+                li a2, 0x12345678
+                li a3, 0x1000
+                li a4, -1
+                mv a5, a0
+            loop: addi a0, a0, -1 # decrement
+                bne a0, zero, loop
+                beq a0, a1, end
+                blt a0, a1, start
+                bge a0, a1, end
+                bltu a0, a1, end
+                bgeu a0, a1, end
+                jal ra, end
+                jal end
+                j loop
+                nop
+            end: ret
+        ";
+        let expected = [
+            0x00a00513, 0x80000593, 0x12345637, 0x67860613, 0x000016b7, 0xfff00713, 0x00050793,
+            0xfff50513, 0xfe051ee3, 0x02b50263, 0xfcb54ce3, 0x00b55e63, 0x00b56c63, 0x00b57a63,
+            0x010000ef, 0x00c000ef, 0xfddff06f, 0x00000013, 0x00008067,
+        ];
+        assert_eq!(assemble(src).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_assemble_decode() {
+        let src = "
+            li t0, 5
+            blt t0, x0, 8
+            jalr x0, t0, -4
+        ";
+        let ixs: Vec<_> = assemble(src)
+            .unwrap()
+            .into_iter()
+            .map(|word| decode(word).unwrap())
+            .collect();
+        assert_eq!(ixs[0].opcode, Opcode::ADD);
+        assert_eq!((ixs[0].rd, ixs[0].imm), (5, 5));
+        assert_eq!(ixs[1].opcode, Opcode::BLT);
+        assert_eq!((ixs[1].rs1, ixs[1].imm), (5, 8));
+        assert_eq!(ixs[2].opcode, Opcode::JALR);
+        assert_eq!((ixs[2].rs1, ixs[2].imm), (5, -4i32 as u32));
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let err = |src| assemble(src).unwrap_err();
+        assert_eq!(err("nop\nfoo x1").line, 2);
+        assert_eq!(err("foo x1").msg, "unknown mnemonic `foo`");
+        assert_eq!(err("add x1, x2, x32").msg, "invalid register `x32`");
+        assert_eq!(err("add x1, x2").msg, "expected 3 operands, found 2");
+        assert_eq!(
+            err("addi x1, x2, 2048").msg,
+            "immediate 2048 is out of range -2048..=2047"
+        );
+        assert_eq!(err("j nowhere").msg, "undefined label `nowhere`");
+        assert_eq!(err("a: nop\na: nop").msg, "duplicate label `a`");
+        assert_eq!(err("beq x0, x0, 3").msg, "offset 3 is not a multiple of 2");
+        assert_eq!(err("lw x1, 4(x2").msg, "invalid memory operand `4(x2`");
+    }
+}
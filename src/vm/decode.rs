@@ -0,0 +1,423 @@
+//! Decoding of raw RV32I and RV32M instruction words.
+//!
+//! See chapters 2 and 7 of the [RISC-V unprivileged spec] for the
+//! instruction formats handled here.
+//!
+//! [RISC-V unprivileged spec]: https://github.com/riscv/riscv-isa-manual/releases
+
+use core::fmt;
+
+use crate::vm::opcode::Opcode;
+
+pub(crate) const OP: u32 = 0b011_0011;
+pub(crate) const OP_IMM: u32 = 0b001_0011;
+pub(crate) const LOAD: u32 = 0b000_0011;
+pub(crate) const STORE: u32 = 0b010_0011;
+pub(crate) const BRANCH: u32 = 0b110_0011;
+pub(crate) const JAL: u32 = 0b110_1111;
+pub(crate) const JALR: u32 = 0b110_0111;
+pub(crate) const LUI: u32 = 0b011_0111;
+pub(crate) const AUIPC: u32 = 0b001_0111;
+pub(crate) const MISC_MEM: u32 = 0b000_1111;
+pub(crate) const SYSTEM: u32 = 0b111_0011;
+
+/// The only encoding of ECALL.
+pub(crate) const ECALL_WORD: u32 = SYSTEM;
+/// The only encoding of EBREAK.
+pub(crate) const EBREAK_WORD: u32 = (1 << 20) | SYSTEM;
+
+/// `funct7` value selecting the alternate ALU operation (SUB, SRA, SRAI).
+pub(crate) const FUNCT7_ALT: u32 = 0b010_0000;
+/// `funct7` value selecting the RV32M extension.
+pub(crate) const FUNCT7_MULDIV: u32 = 0b000_0001;
+
+/// A decoded instruction. The fields correspond directly to the `opcode`,
+/// `rd`, `rs1`, `rs2`, `imm` and `f_imm` columns of
+/// [`CpuCols`](crate::cpu::columns::CpuCols). Registers that are not used by
+/// the instruction are set to 0.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Instruction {
+    pub opcode: Opcode,
+    pub rd: u8,
+    pub rs1: u8,
+    pub rs2: u8,
+    /// The sign extended immediate value, the shift amount for immediate
+    /// shifts, or the upper 20 bits for LUI and AUIPC.
+    pub imm: u32,
+    /// True if the second operand is `imm` rather than `rs2`.
+    pub uses_imm: bool,
+}
+
+/// An error returned when decoding an instruction word fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The major opcode does not belong to a 32-bit RV32I instruction.
+    InvalidOpcode(u32),
+    /// The `funct3` or `funct7` fields are invalid for the major opcode.
+    InvalidFunct(u32),
+    /// The instruction is valid RISC-V but is not supported.
+    Unsupported(u32),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidOpcode(word) => write!(f, "invalid opcode in instruction {word:#010x}"),
+            Self::InvalidFunct(word) => {
+                write!(f, "invalid funct field in instruction {word:#010x}")
+            }
+            Self::Unsupported(word) => write!(f, "unsupported instruction {word:#010x}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Returns the value of `word[lo..lo + len]`.
+const fn bits(word: u32, lo: u32, len: u32) -> u32 {
+    (word >> lo) & ((1 << len) - 1)
+}
+
+/// Register fields, common to all formats which use them.
+const fn rd(word: u32) -> u8 {
+    bits(word, 7, 5) as u8
+}
+
+const fn rs1(word: u32) -> u8 {
+    bits(word, 15, 5) as u8
+}
+
+const fn rs2(word: u32) -> u8 {
+    bits(word, 20, 5) as u8
+}
+
+const fn funct3(word: u32) -> u32 {
+    bits(word, 12, 3)
+}
+
+const fn funct7(word: u32) -> u32 {
+    bits(word, 25, 7)
+}
+
+/// The sign extended immediate of an I-type instruction.
+const fn imm_i(word: u32) -> u32 {
+    ((word as i32) >> 20) as u32
+}
+
+/// The sign extended immediate of an S-type instruction.
+const fn imm_s(word: u32) -> u32 {
+    ((((word as i32) >> 25) << 5) as u32) | bits(word, 7, 5)
+}
+
+/// The sign extended immediate of a B-type instruction.
+const fn imm_b(word: u32) -> u32 {
+    ((((word as i32) >> 31) << 12) as u32)
+        | (bits(word, 7, 1) << 11)
+        | (bits(word, 25, 6) << 5)
+        | (bits(word, 8, 4) << 1)
+}
+
+/// The unshifted immediate of a U-type instruction.
+const fn imm_u(word: u32) -> u32 {
+    bits(word, 12, 20)
+}
+
+/// The sign extended immediate of a J-type instruction.
+const fn imm_j(word: u32) -> u32 {
+    ((((word as i32) >> 31) << 20) as u32)
+        | (bits(word, 12, 8) << 12)
+        | (bits(word, 20, 1) << 11)
+        | (bits(word, 21, 10) << 1)
+}
+
+impl Instruction {
+    /// An instruction with format R.
+    fn r_type(opcode: Opcode, word: u32) -> Self {
+        Self {
+            opcode,
+            rd: rd(word),
+            rs1: rs1(word),
+            rs2: rs2(word),
+            imm: 0,
+            uses_imm: false,
+        }
+    }
+
+    /// An instruction with format I, or an immediate shift.
+    fn i_type(opcode: Opcode, word: u32, imm: u32) -> Self {
+        Self {
+            opcode,
+            rd: rd(word),
+            rs1: rs1(word),
+            rs2: 0,
+            imm,
+            uses_imm: true,
+        }
+    }
+
+    /// An instruction with format S or B.
+    fn s_type(opcode: Opcode, word: u32, imm: u32) -> Self {
+        Self {
+            opcode,
+            rd: 0,
+            rs1: rs1(word),
+            rs2: rs2(word),
+            imm,
+            uses_imm: true,
+        }
+    }
+
+    /// An instruction with format U.
+    fn u_type(opcode: Opcode, word: u32) -> Self {
+        Self {
+            opcode,
+            rd: rd(word),
+            rs1: 0,
+            rs2: 0,
+            imm: imm_u(word),
+            uses_imm: true,
+        }
+    }
+
+    /// An instruction with format J.
+    fn j_type(opcode: Opcode, word: u32) -> Self {
+        Self {
+            opcode,
+            rd: rd(word),
+            rs1: 0,
+            rs2: 0,
+            imm: imm_j(word),
+            uses_imm: true,
+        }
+    }
+}
+
+/// Decodes a single RV32I or RV32M instruction word.
+pub(crate) fn decode(word: u32) -> Result<Instruction, DecodeError> {
+    let invalid = DecodeError::InvalidFunct(word);
+    let f3 = funct3(word);
+    let f7 = funct7(word);
+
+    let ix = match bits(word, 0, 7) {
+        OP => {
+            let opcode = match (f3, f7) {
+                (0b000, 0) => Opcode::ADD,
+                (0b000, FUNCT7_ALT) => Opcode::SUB,
+                (0b001, 0) => Opcode::SLL,
+                (0b010, 0) => Opcode::SLT,
+                (0b011, 0) => Opcode::SLTU,
+                (0b100, 0) => Opcode::XOR,
+                (0b101, 0) => Opcode::SRL,
+                (0b101, FUNCT7_ALT) => Opcode::SRA,
+                (0b110, 0) => Opcode::OR,
+                (0b111, 0) => Opcode::AND,
+                (0b000, FUNCT7_MULDIV) => Opcode::MUL,
+                (0b001, FUNCT7_MULDIV) => Opcode::MULH,
+                (0b010, FUNCT7_MULDIV) => Opcode::MULHSU,
+                (0b011, FUNCT7_MULDIV) => Opcode::MULHU,
+                (0b100, FUNCT7_MULDIV) => Opcode::DIV,
+                (0b101, FUNCT7_MULDIV) => Opcode::DIVU,
+                (0b110, FUNCT7_MULDIV) => Opcode::REM,
+                (0b111, FUNCT7_MULDIV) => Opcode::REMU,
+                _ => return Err(invalid),
+            };
+            Instruction::r_type(opcode, word)
+        }
+        OP_IMM => {
+            let shamt = bits(word, 20, 5);
+            let (opcode, imm) = match (f3, f7) {
+                (0b000, _) => (Opcode::ADD, imm_i(word)),
+                (0b010, _) => (Opcode::SLT, imm_i(word)),
+                (0b011, _) => (Opcode::SLTU, imm_i(word)),
+                (0b100, _) => (Opcode::XOR, imm_i(word)),
+                (0b110, _) => (Opcode::OR, imm_i(word)),
+                (0b111, _) => (Opcode::AND, imm_i(word)),
+                (0b001, 0) => (Opcode::SLL, shamt),
+                (0b101, 0) => (Opcode::SRL, shamt),
+                (0b101, FUNCT7_ALT) => (Opcode::SRA, shamt),
+                _ => return Err(invalid),
+            };
+            Instruction::i_type(opcode, word, imm)
+        }
+        LOAD => {
+            let opcode = match f3 {
+                0b000 => Opcode::LB,
+                0b001 => Opcode::LH,
+                0b010 => Opcode::LW,
+                0b100 => Opcode::LBU,
+                0b101 => Opcode::LHU,
+                _ => return Err(invalid),
+            };
+            Instruction::i_type(opcode, word, imm_i(word))
+        }
+        STORE => {
+            let opcode = match f3 {
+                0b000 => Opcode::SB,
+                0b001 => Opcode::SH,
+                0b010 => Opcode::SW,
+                _ => return Err(invalid),
+            };
+            Instruction::s_type(opcode, word, imm_s(word))
+        }
+        BRANCH => {
+            let opcode = match f3 {
+                0b000 => Opcode::BEQ,
+                0b001 => Opcode::BNE,
+                0b100 => Opcode::BLT,
+                0b101 => Opcode::BGE,
+                0b110 => Opcode::BLTU,
+                0b111 => Opcode::BGEU,
+                _ => return Err(invalid),
+            };
+            Instruction::s_type(opcode, word, imm_b(word))
+        }
+        JAL => Instruction::j_type(Opcode::JAL, word),
+        JALR if f3 == 0 => Instruction::i_type(Opcode::JALR, word, imm_i(word)),
+        JALR => return Err(invalid),
+        LUI => Instruction::u_type(Opcode::LUI, word),
+        AUIPC => Instruction::u_type(Opcode::AUIPC, word),
+        // ECALL and EBREAK use no registers, so every operand field is zero
+        SYSTEM if word == ECALL_WORD => Instruction::r_type(Opcode::ECALL, word),
+        SYSTEM if word == EBREAK_WORD => Instruction::r_type(Opcode::EBREAK, word),
+        MISC_MEM | SYSTEM => return Err(DecodeError::Unsupported(word)),
+        _ => return Err(DecodeError::InvalidOpcode(word)),
+    };
+    Ok(ix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_r_type() {
+        // add x3, x1, x2
+        let ix = decode(0x002081b3).unwrap();
+        assert_eq!(ix, Instruction::r_type(Opcode::ADD, 0x002081b3));
+        assert_eq!((ix.rd, ix.rs1, ix.rs2, ix.uses_imm), (3, 1, 2, false));
+
+        // sub x3, x1, x2
+        assert_eq!(decode(0x402081b3).unwrap().opcode, Opcode::SUB);
+        // sra x5, x6, x7
+        assert_eq!(decode(0x407352b3).unwrap().opcode, Opcode::SRA);
+        // sltu x10, x11, x12
+        assert_eq!(decode(0x00c5b533).unwrap().opcode, Opcode::SLTU);
+        // mul x3, x1, x2
+        let ix = decode(0x022081b3).unwrap();
+        assert_eq!(ix.opcode, Opcode::MUL);
+        assert_eq!((ix.rd, ix.rs1, ix.rs2, ix.uses_imm), (3, 1, 2, false));
+        // mulhsu x5, x6, x7
+        assert_eq!(decode(0x027322b3).unwrap().opcode, Opcode::MULHSU);
+        // remu x10, x11, x12
+        assert_eq!(decode(0x02c5f533).unwrap().opcode, Opcode::REMU);
+    }
+
+    #[test]
+    fn test_decode_i_type() {
+        // addi x1, x0, -5
+        let ix = decode(0xffb00093).unwrap();
+        assert_eq!(ix.opcode, Opcode::ADD);
+        assert_eq!((ix.rd, ix.rs1, ix.rs2), (1, 0, 0));
+        assert_eq!(ix.imm, -5i32 as u32);
+        assert!(ix.uses_imm);
+
+        // srai x5, x6, 3
+        let ix = decode(0x40335293).unwrap();
+        assert_eq!(ix.opcode, Opcode::SRA);
+        assert_eq!(ix.imm, 3);
+
+        // lbu x5, -4(x2)
+        let ix = decode(0xffc14283).unwrap();
+        assert_eq!(ix.opcode, Opcode::LBU);
+        assert_eq!((ix.rd, ix.rs1, ix.imm), (5, 2, -4i32 as u32));
+
+        // jalr x1, 16(x5)
+        let ix = decode(0x010280e7).unwrap();
+        assert_eq!(ix.opcode, Opcode::JALR);
+        assert_eq!((ix.rd, ix.rs1, ix.imm), (1, 5, 16));
+    }
+
+    #[test]
+    fn test_decode_s_b_j_type() {
+        // sw x5, -8(x2)
+        let ix = decode(0xfe512c23).unwrap();
+        assert_eq!(ix.opcode, Opcode::SW);
+        assert_eq!((ix.rd, ix.rs1, ix.rs2, ix.imm), (0, 2, 5, -8i32 as u32));
+
+        // bgeu x1, x2, -4096
+        let ix = decode(0x8020f063).unwrap();
+        assert_eq!(ix.opcode, Opcode::BGEU);
+        assert_eq!((ix.rs1, ix.rs2, ix.imm), (1, 2, -4096i32 as u32));
+
+        // bne x1, x2, 2046
+        let ix = decode(0x7e209f63).unwrap();
+        assert_eq!(ix.opcode, Opcode::BNE);
+        assert_eq!(ix.imm, 2046);
+
+        // jal x1, -1048576
+        let ix = decode(0x800000ef).unwrap();
+        assert_eq!(ix.opcode, Opcode::JAL);
+        assert_eq!((ix.rd, ix.imm), (1, -1048576i32 as u32));
+
+        // jal x0, 1048574
+        let ix = decode(0x7ffff06f).unwrap();
+        assert_eq!((ix.rd, ix.imm), (0, 1048574));
+    }
+
+    #[test]
+    fn test_decode_u_type() {
+        // lui x5, 0xfffff
+        let ix = decode(0xfffff2b7).unwrap();
+        assert_eq!(ix.opcode, Opcode::LUI);
+        assert_eq!((ix.rd, ix.rs1, ix.imm, ix.uses_imm), (5, 0, 0xfffff, true));
+
+        // auipc x6, 1
+        let ix = decode(0x00001317).unwrap();
+        assert_eq!(ix.opcode, Opcode::AUIPC);
+        assert_eq!((ix.rd, ix.imm), (6, 1));
+    }
+
+    #[test]
+    fn test_decode_system() {
+        let ix = decode(0x00000073).unwrap();
+        assert_eq!(ix.opcode, Opcode::ECALL);
+        assert_eq!((ix.rd, ix.rs1, ix.rs2, ix.imm), (0, 0, 0, 0));
+
+        let ix = decode(0x00100073).unwrap();
+        assert_eq!(ix.opcode, Opcode::EBREAK);
+        assert_eq!((ix.rd, ix.rs1, ix.rs2, ix.imm), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        // compressed instructions are not supported
+        assert_eq!(decode(0x0001), Err(DecodeError::InvalidOpcode(0x0001)));
+        // all zeros is defined to be an illegal instruction
+        assert_eq!(decode(0), Err(DecodeError::InvalidOpcode(0)));
+        // add with an invalid funct7
+        assert_eq!(
+            decode(0x202081b3),
+            Err(DecodeError::InvalidFunct(0x202081b3))
+        );
+        // slli with an invalid funct7
+        assert_eq!(
+            decode(0x40309293),
+            Err(DecodeError::InvalidFunct(0x40309293))
+        );
+        // load with funct3 = 0b011 (ld is RV64 only)
+        assert_eq!(
+            decode(0x0000b003),
+            Err(DecodeError::InvalidFunct(0x0000b003))
+        );
+        // csrrw x1, mstatus, x2 (Zicsr is not supported)
+        assert_eq!(
+            decode(0x300110f3),
+            Err(DecodeError::Unsupported(0x300110f3))
+        );
+        // fence
+        assert_eq!(
+            decode(0x0ff0000f),
+            Err(DecodeError::Unsupported(0x0ff0000f))
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Loading of statically linked RV32 ELF executables.
+//!
+//! Only the ELF header and program headers are parsed. Every `PT_LOAD`
+//! segment is copied into the [`Segment::Main`](crate::mem::Segment::Main)
+//! address space at its virtual address. See the [ELF specification] for the
+//! layout of the structures read here.
+//!
+//! [ELF specification]: https://refspecs.linuxfoundation.org/elf/elf.pdf
+
+use core::fmt;
+
+use hashbrown::HashMap;
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const ELFCLASS32: u8 = 1;
+const ELFDATA2LSB: u8 = 1;
+const ET_EXEC: u16 = 2;
+const EM_RISCV: u16 = 243;
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+
+/// The size of the ELF32 file header.
+const EHDR_SIZE: usize = 52;
+/// The size of an ELF32 program header.
+const PHDR_SIZE: usize = 32;
+
+/// A program loaded from an ELF file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Program {
+    /// The address of the first instruction.
+    pub entry: u32,
+    /// The initial contents of main memory. Bytes of a segment beyond its
+    /// size in the file are zero-initialized, and are not included here
+    /// since main memory reads as zero by default.
+    pub image: HashMap<u32, u8>,
+}
+
+/// An error returned when loading an ELF file fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ElfError {
+    /// The file is truncated or otherwise malformed.
+    Malformed(&'static str),
+    /// The file is not a 32-bit little-endian ELF file.
+    UnsupportedFormat,
+    /// The file targets a machine other than RISC-V.
+    UnsupportedMachine(u16),
+    /// The file is not an executable, e.g. a relocatable object file or a
+    /// shared object.
+    UnsupportedType(u16),
+    /// The file is dynamically linked, and so requires relocations to be
+    /// applied at load time.
+    UnsupportedRelocations,
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(msg) => write!(f, "malformed ELF file: {msg}"),
+            Self::UnsupportedFormat => write!(f, "not a 32-bit little-endian ELF file"),
+            Self::UnsupportedMachine(machine) => {
+                write!(
+                    f,
+                    "unsupported ELF machine type {machine} (expected RISC-V)"
+                )
+            }
+            Self::UnsupportedType(ty) => {
+                write!(f, "unsupported ELF file type {ty} (expected an executable)")
+            }
+            Self::UnsupportedRelocations => {
+                write!(f, "dynamic relocations are not supported")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ElfError {}
+
+/// Returns `data[offset..offset + len]`.
+fn slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8], ElfError> {
+    offset
+        .checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(ElfError::Malformed("unexpected end of file"))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, ElfError> {
+    let bytes = slice(data, offset, 2)?;
+    Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ElfError> {
+    let bytes = slice(data, offset, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// A program header, restricted to the fields needed for loading.
+#[derive(Clone, Copy, Debug)]
+struct ProgramHeader {
+    p_type: u32,
+    offset: u32,
+    vaddr: u32,
+    filesz: u32,
+    memsz: u32,
+}
+
+impl ProgramHeader {
+    fn parse(data: &[u8], offset: usize) -> Result<Self, ElfError> {
+        Ok(Self {
+            p_type: read_u32(data, offset)?,
+            offset: read_u32(data, offset + 4)?,
+            vaddr: read_u32(data, offset + 8)?,
+            filesz: read_u32(data, offset + 16)?,
+            memsz: read_u32(data, offset + 20)?,
+        })
+    }
+}
+
+/// Parses an ELF32 little-endian RISC-V executable, returning its entry
+/// point and the contents of its `PT_LOAD` segments.
+pub(crate) fn load_elf(data: &[u8]) -> Result<Program, ElfError> {
+    let ident = slice(data, 0, EHDR_SIZE)?;
+    if ident[..4] != ELF_MAGIC {
+        return Err(ElfError::Malformed("bad magic number"));
+    }
+    if ident[4] != ELFCLASS32 || ident[5] != ELFDATA2LSB {
+        return Err(ElfError::UnsupportedFormat);
+    }
+
+    let e_type = read_u16(data, 16)?;
+    if e_type != ET_EXEC {
+        return Err(ElfError::UnsupportedType(e_type));
+    }
+    let e_machine = read_u16(data, 18)?;
+    if e_machine != EM_RISCV {
+        return Err(ElfError::UnsupportedMachine(e_machine));
+    }
+
+    let entry = read_u32(data, 24)?;
+    let phoff = read_u32(data, 28)? as usize;
+    let phentsize = read_u16(data, 42)? as usize;
+    let phnum = read_u16(data, 44)? as usize;
+    if phnum > 0 && phentsize < PHDR_SIZE {
+        return Err(ElfError::Malformed("program header entries are too small"));
+    }
+
+    let mut image = HashMap::new();
+    for i in 0..phnum {
+        let phdr = ProgramHeader::parse(data, phoff + i * phentsize)?;
+        match phdr.p_type {
+            PT_LOAD => (),
+            PT_DYNAMIC => return Err(ElfError::UnsupportedRelocations),
+            _ => continue,
+        }
+        if phdr.filesz > phdr.memsz {
+            return Err(ElfError::Malformed(
+                "segment file size exceeds its memory size",
+            ));
+        }
+        if phdr.vaddr.checked_add(phdr.memsz).is_none() {
+            return Err(ElfError::Malformed("segment exceeds the address space"));
+        }
+
+        let bytes = slice(data, phdr.offset as usize, phdr.filesz as usize)?;
+        let adrs = bytes
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| (phdr.vaddr + i as u32, b));
+        image.extend(adrs);
+    }
+
+    Ok(Program { entry, image })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes `mem[0x208] = mem[0x200] + mem[0x204]`, then halts. The text
+    /// segment is loaded at 0x100, and the data segment at 0x200 holds the
+    /// words 7 and 35 followed by a zero-initialized word.
+    const ADD_ELF: &[u8] = include_bytes!("fixtures/add.elf");
+    /// The text of `add.elf`, assembled but not linked.
+    const ADD_OBJ: &[u8] = include_bytes!("fixtures/add.o");
+
+    #[test]
+    fn test_load_elf() {
+        let program = load_elf(ADD_ELF).unwrap();
+        assert_eq!(program.entry, 0x100);
+        assert_eq!(program.image.len(), 8 * 4 + 2 * 4);
+
+        // addi x1, x0, 0x200
+        let word: [u8; 4] = core::array::from_fn(|i| program.image[&(0x100 + i as u32)]);
+        assert_eq!(u32::from_le_bytes(word), 0x20000093);
+
+        assert_eq!(program.image[&0x200], 7);
+        assert_eq!(program.image[&0x204], 35);
+        assert!(!program.image.contains_key(&0x208));
+    }
+
+    #[test]
+    fn test_load_elf_errors() {
+        assert_eq!(
+            load_elf(&[]),
+            Err(ElfError::Malformed("unexpected end of file"))
+        );
+        assert_eq!(
+            load_elf(&ADD_ELF[..60]),
+            Err(ElfError::Malformed("unexpected end of file"))
+        );
+
+        // relocatable object files are rejected
+        assert_eq!(load_elf(ADD_OBJ), Err(ElfError::UnsupportedType(1)));
+
+        let mut elf = ADD_ELF.to_vec();
+        elf[18] = 0x3e; // EM_X86_64
+        assert_eq!(load_elf(&elf), Err(ElfError::UnsupportedMachine(62)));
+
+        let mut elf = ADD_ELF.to_vec();
+        elf[4] = 2; // ELFCLASS64
+        assert_eq!(load_elf(&elf), Err(ElfError::UnsupportedFormat));
+
+        // turn the data segment into a dynamic segment
+        let mut elf = ADD_ELF.to_vec();
+        elf[EHDR_SIZE + PHDR_SIZE] = PT_DYNAMIC as u8;
+        assert_eq!(load_elf(&elf), Err(ElfError::UnsupportedRelocations));
+    }
+}
@@ -0,0 +1,16 @@
+# Computes mem[0x208] = mem[0x200] + mem[0x204], then halts with exit code 0.
+    .text
+    .globl _start
+_start:
+    addi x1, x0, 0x200
+    lw x2, 0(x1)
+    lw x3, 4(x1)
+    add x4, x2, x3
+    sw x4, 8(x1)
+    li a7, 93
+    li a0, 0
+    ecall
+
+    .data
+    .word 7
+    .word 35
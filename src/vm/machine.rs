@@ -0,0 +1,1313 @@
+//! A reference RV32IM interpreter.
+//!
+//! Each executed instruction is recorded as a [`CpuStep`], along with the
+//! operations it delegates to the other tables. The resulting
+//! [`ExecutionLog`] contains exactly the inputs expected by the `gen_trace`
+//! function of each table.
+//!
+//! Programs interact with the host through ECALL, which dispatches on the
+//! syscall number in `a7`; see [`Syscall`].
+
+use anyhow::{anyhow, ensure, Context, Result};
+use hashbrown::{HashMap, HashSet};
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::hash::hash_types::RichField;
+
+use crate::arith::trace::{ArithOp, Op as ArithKind};
+use crate::bits::trace::{BitOp, Op as BitKind};
+use crate::bytes::trace::ByteOp;
+use crate::bytes::{Alignment, BYTES_HALF, BYTES_WORD};
+use crate::cpu::columns::{N_MEM_CHANNELS, RD_CHANNEL, RS1_CHANNEL, RS2_CHANNEL};
+use crate::cpu::control_flow::INSTRUCTION_BYTES;
+use crate::cpu::shift::SHAMT_MASK;
+use crate::cpu::syscall::{Syscall, FD_STDIN, FD_STDOUT, REG_A0, REG_A1, REG_A2, REG_A7};
+use crate::cpu::upper::UPPER_IMM_SHIFT;
+use crate::io::trace::{IoKind, IoOp, IoTape};
+use crate::mem::page::{page_of, PageProof, PagedMemory, REG_PAGE};
+use crate::mem::trace::{MemAddress, MemKind, MemOp};
+use crate::mem::Segment;
+use crate::memfinal::trace::FinalMem;
+use crate::meminit::trace::MemImage;
+use crate::muldiv::trace::{MulDivOp, Op as MulDivKind};
+use crate::program::trace::ProgramRom;
+use crate::proof::PublicValues;
+use crate::stark::NUM_TABLES;
+use crate::vm::decode::{decode, Instruction};
+use crate::vm::elf::load_elf;
+use crate::vm::opcode::Opcode;
+
+/// The number of general purpose registers.
+pub(crate) const N_REGS: usize = 32;
+
+/// A single executed instruction, containing everything needed to fill one
+/// row of the CPU table.
+#[derive(Clone, Debug)]
+pub(crate) struct CpuStep {
+    /// The value of the clock when the instruction was executed.
+    pub clock: usize,
+    /// The program counter of the instruction.
+    pub pc: u32,
+    /// The program counter of the next instruction.
+    pub next_pc: u32,
+    /// The decoded instruction.
+    pub ix: Instruction,
+    /// The register accesses made on each memory channel.
+    pub membus: [Option<MemOp>; N_MEM_CHANNELS],
+    /// The main memory address `rs1 + imm` accessed by loads and stores.
+    pub adr_virt: u32,
+    /// True if this is a branch instruction and the branch was taken.
+    pub take_branch: bool,
+    /// The position in the input stream of the next byte to read.
+    pub input_pos: usize,
+    /// The position in the output stream of the next byte to write.
+    pub output_pos: usize,
+}
+
+/// The operations generated by executing a program, grouped by table.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ExecutionLog {
+    /// The initial contents of main memory.
+    pub image: HashMap<u32, u8>,
+    /// The initial contents of the registers.
+    pub regs: [u32; N_REGS],
+    /// The initial contents of the registers and main memory, cut into
+    /// pages. Unlike `image` and `regs`, these are not restricted to the
+    /// pages touched by the execution.
+    memory: PagedMemory,
+    /// The program counter of the first instruction.
+    pub pc_start: u32,
+    pub cpu: Vec<CpuStep>,
+    pub arith: Vec<ArithOp>,
+    pub bits: Vec<BitOp>,
+    pub bytes: Vec<ByteOp>,
+    pub io: Vec<IoOp>,
+    pub mem: Vec<MemOp>,
+    pub muldiv: Vec<MulDivOp>,
+    /// The bytes read from the input stream and written to the output
+    /// stream.
+    pub tape: IoTape,
+    /// The exit code passed to the HALT syscall, if it was called.
+    pub exit_code: Option<u32>,
+}
+
+impl ExecutionLog {
+    /// Returns the public values of the execution.
+    pub(crate) fn public_values<F: RichField>(&self) -> PublicValues<F> {
+        PublicValues {
+            program_hash: ProgramRom::from_image(&self.image).hash(),
+            image_hash: self.mem_image().hash(),
+            input_hash: self.tape.input_hash(),
+            output_hash: self.tape.output_hash(),
+            final_hash: self.final_mem().hash(),
+            root_start: self.memory.root(),
+            root_end: self.memory.with_final(&self.final_mem()).root(),
+            pc_start: self.pc_start,
+            pc_end: self.cpu.last().map_or(self.pc_start, |step| step.next_pc),
+            input_start: self.tape.input_start,
+            input_end: self.tape.input_end(),
+            output_start: self.tape.output_start,
+            output_end: self.tape.output_end(),
+            clock_end: self.cpu.len(),
+            halted: self.exit_code.is_some(),
+            exit_code: self.exit_code.unwrap_or_default(),
+        }
+    }
+
+    /// Returns the initial contents of the registers and main memory.
+    pub(crate) fn mem_image(&self) -> MemImage {
+        MemImage::new(&self.image, &self.regs)
+    }
+
+    /// Returns the initial contents of the registers and main memory, cut
+    /// into pages.
+    pub(crate) fn memory(&self) -> &PagedMemory {
+        &self.memory
+    }
+
+    /// Restricts the initial contents of memory to the pages touched by the
+    /// execution, so that untouched memory is left out of the trace. Returns
+    /// the proof that these pages belong to the memory in which execution
+    /// started.
+    ///
+    /// A page is touched if it holds an executed instruction or an address
+    /// of the memory table, which includes the dummy reads filling range
+    /// check gaps. The registers are always paged in.
+    pub(crate) fn restrict_to_touched_pages<F: RichField>(&mut self) -> PageProof<F> {
+        let image = self.image.clone();
+        let ix_bytes = INSTRUCTION_BYTES as u32;
+        let code = self
+            .cpu
+            .iter()
+            .flat_map(|step| [step.pc, step.pc.wrapping_add(ix_bytes - 1)])
+            .map(|pc| page_of(Segment::Main, pc).0);
+        let data = self
+            .mem
+            .iter()
+            .map(|op| page_of(op.adr.seg, op.adr.virt as u32).0);
+        let mut pages: HashSet<_> = code.chain(data).chain([REG_PAGE]).collect();
+        loop {
+            self.image = image
+                .iter()
+                .filter(|&(&adr, _)| pages.contains(&page_of(Segment::Main, adr).0))
+                .map(|(&adr, &val)| (adr, val))
+                .collect();
+            // paging in more memory may move the dummy reads to other pages
+            let n_pages = pages.len();
+            let final_mem = self.final_mem();
+            let accessed = final_mem.cells().iter();
+            pages.extend(accessed.map(|&(seg, virt, _)| page_of(seg, virt).0));
+            if pages.len() == n_pages {
+                return self.memory.prove(pages);
+            }
+        }
+    }
+
+    /// Returns the contents of memory at the end of execution.
+    pub(crate) fn final_mem(&self) -> FinalMem {
+        FinalMem::from_ops(self.mem_ops())
+    }
+
+    /// Returns every access of the memory table, starting with the writes of
+    /// the initial image.
+    fn mem_ops(&self) -> Vec<MemOp> {
+        let image = self.mem_image();
+        image.mem_ops().chain(self.mem.iter().copied()).collect()
+    }
+
+    /// Generates the trace of every table, ordered by
+    /// [`Table`](crate::stark::Table). Each trace has at least `min_rows` rows.
+    pub(crate) fn gen_traces<F: RichField>(
+        self,
+        min_rows: usize,
+    ) -> [Vec<PolynomialValues<F>>; NUM_TABLES] {
+        let rom = ProgramRom::from_image(&self.image);
+        let pcs = self.cpu.iter().map(|step| step.pc);
+        let program = crate::program::trace::gen_trace(&rom, pcs, min_rows);
+        let image = self.mem_image();
+        let meminit = crate::meminit::trace::gen_trace(&image, min_rows);
+        let memfinal = crate::memfinal::trace::gen_trace(&self.final_mem(), min_rows);
+        let mem = crate::mem::trace::gen_trace(self.mem_ops(), min_rows);
+        [
+            crate::arith::trace::gen_trace(self.arith, min_rows),
+            crate::bits::trace::gen_trace(self.bits, min_rows),
+            crate::bytes::trace::gen_trace(self.bytes, min_rows),
+            crate::cpu::trace::gen_trace(self.cpu, min_rows),
+            crate::io::trace::gen_trace(self.io, min_rows),
+            mem,
+            memfinal,
+            meminit,
+            crate::muldiv::trace::gen_trace(self.muldiv, min_rows),
+            program,
+        ]
+    }
+}
+
+/// An RV32IM machine with 32 registers and byte-addressed main memory.
+///
+/// The machine halts when the program calls HALT. Fetching an instruction
+/// from an address which holds no data, e.g. when execution falls off the
+/// end of the program, is an error. Only executions ending with HALT can be
+/// proven, possibly as a sequence of segments; see
+/// [`run_segments`](Machine::run_segments).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Machine {
+    pc: u32,
+    clock: usize,
+    regs: [u32; N_REGS],
+    mem: HashMap<u32, u8>,
+    /// The input stream provided by the host.
+    input: Vec<u8>,
+    /// The alignment policy for loads and stores.
+    alignment: Alignment,
+    log: ExecutionLog,
+}
+
+impl Machine {
+    /// Creates a machine with all registers zeroed, starting execution at
+    /// `pc`. Main memory is initialized with the given `(address, byte)`
+    /// pairs.
+    pub(crate) fn new(pc: u32, image: impl IntoIterator<Item = (u32, u8)>) -> Self {
+        let mem: HashMap<_, _> = image.into_iter().collect();
+        let log = ExecutionLog {
+            image: mem.clone(),
+            memory: PagedMemory::new(&mem, &[0; N_REGS]),
+            pc_start: pc,
+            ..Default::default()
+        };
+        Self {
+            pc,
+            mem,
+            log,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a machine whose main memory holds `words` starting at
+    /// address `pc`.
+    pub(crate) fn from_words(pc: u32, words: &[u32]) -> Self {
+        let image = words
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .enumerate()
+            .map(|(i, b)| (pc.wrapping_add(i as u32), b));
+        Self::new(pc, image)
+    }
+
+    /// Creates a machine from an ELF executable, starting execution at its
+    /// entry point.
+    pub(crate) fn from_elf(data: &[u8]) -> Result<Self> {
+        let program = load_elf(data)?;
+        Ok(Self::new(program.entry, program.image))
+    }
+
+    /// Sets the input stream consumed by the READ syscall.
+    pub(crate) fn with_input(mut self, input: impl IntoIterator<Item = u8>) -> Self {
+        self.input = input.into_iter().collect();
+        self
+    }
+
+    /// Sets the alignment policy for loads and stores. There is no trap
+    /// handler, so a misaligned access under [`Alignment::Strict`] stops the
+    /// machine with an error.
+    pub(crate) fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub(crate) fn pc(&self) -> u32 {
+        self.pc
+    }
+
+    pub(crate) fn clock(&self) -> usize {
+        self.clock
+    }
+
+    /// Returns the value of register `reg`.
+    pub(crate) fn reg(&self, reg: u8) -> u32 {
+        self.regs[reg as usize]
+    }
+
+    /// Returns the byte stored at `adr` in main memory.
+    pub(crate) fn mem(&self, adr: u32) -> u8 {
+        self.mem.get(&adr).copied().unwrap_or_default()
+    }
+
+    /// Returns true if the machine has halted.
+    pub(crate) fn is_halted(&self) -> bool {
+        self.log.exit_code.is_some()
+    }
+
+    /// Executes instructions until the machine halts or `max_steps`
+    /// instructions have been executed. Returns the execution log.
+    pub(crate) fn run(mut self, max_steps: usize) -> Result<ExecutionLog> {
+        for _ in 0..max_steps {
+            if self.is_halted() {
+                return Ok(self.log);
+            }
+            self.step()?;
+        }
+        if self.is_halted() {
+            Ok(self.log)
+        } else {
+            Err(anyhow!("machine did not halt within {max_steps} steps"))
+        }
+    }
+
+    /// Executes instructions until the machine halts or `max_steps`
+    /// instructions have been executed, cutting execution into segments of
+    /// `segment_cycles` instructions. Returns the execution log of each
+    /// segment.
+    ///
+    /// Each segment starts from the registers, memory, pc and stream
+    /// positions in which the previous one stopped, with its clock reset, so
+    /// that it can be proven on its own. Only the last segment halts.
+    pub(crate) fn run_segments(
+        mut self,
+        segment_cycles: usize,
+        max_steps: usize,
+    ) -> Result<Vec<ExecutionLog>> {
+        ensure!(
+            segment_cycles > 0,
+            "segments must execute at least one instruction"
+        );
+        let mut logs = vec![];
+        for _ in 0..max_steps {
+            if self.is_halted() {
+                break;
+            }
+            if self.clock == segment_cycles {
+                logs.push(self.start_segment());
+            }
+            self.step()?;
+        }
+        ensure!(
+            self.is_halted(),
+            "machine did not halt within {max_steps} steps"
+        );
+        logs.push(self.log);
+        Ok(logs)
+    }
+
+    /// Starts a new segment from the current state of the machine, returning
+    /// the execution log of the previous segment.
+    fn start_segment(&mut self) -> ExecutionLog {
+        // the streams of the new segment start after the bytes already
+        // transferred
+        let tape = IoTape {
+            input_start: self.log.tape.input_end(),
+            output_start: self.log.tape.output_end(),
+            ..Default::default()
+        };
+        self.clock = 0;
+        let log = ExecutionLog {
+            image: self.mem.clone(),
+            regs: self.regs,
+            memory: PagedMemory::new(&self.mem, &self.regs),
+            pc_start: self.pc,
+            tape,
+            ..Default::default()
+        };
+        core::mem::replace(&mut self.log, log)
+    }
+
+    /// Fetches, decodes, and executes a single instruction.
+    pub(crate) fn step(&mut self) -> Result<()> {
+        let word = self.fetch()?;
+        let ix = decode(word).with_context(|| format!("failed to decode at pc {:#x}", self.pc))?;
+
+        let mut step = CpuStep {
+            clock: self.clock,
+            pc: self.pc,
+            next_pc: self.pc.wrapping_add(INSTRUCTION_BYTES as u32),
+            ix,
+            membus: Default::default(),
+            adr_virt: 0,
+            take_branch: false,
+            input_pos: self.log.tape.input_end(),
+            output_pos: self.log.tape.output_end(),
+        };
+
+        match ix.opcode {
+            Opcode::ADD | Opcode::SUB | Opcode::SLT | Opcode::SLTU => {
+                let (in0, in1) = self.read_operands(&mut step);
+                let kind = match ix.opcode {
+                    Opcode::ADD => ArithKind::ADD,
+                    Opcode::SUB => ArithKind::SUB,
+                    Opcode::SLT => ArithKind::LTS,
+                    _ => ArithKind::LTU,
+                };
+                let out = match ix.opcode {
+                    Opcode::ADD => in0.wrapping_add(in1),
+                    Opcode::SUB => in0.wrapping_sub(in1),
+                    Opcode::SLT => ((in0 as i32) < (in1 as i32)) as u32,
+                    _ => (in0 < in1) as u32,
+                };
+                self.log.arith.push(ArithOp::new(kind, in0, in1));
+                self.write_reg(&mut step, out);
+            }
+            Opcode::AND | Opcode::OR | Opcode::XOR | Opcode::SLL | Opcode::SRL | Opcode::SRA => {
+                let (in0, in1) = self.read_operands(&mut step);
+                let shamt = in1 & SHAMT_MASK;
+                let (kind, operand, out) = match ix.opcode {
+                    Opcode::AND => (BitKind::AND, in1, in0 & in1),
+                    Opcode::OR => (BitKind::OR, in1, in0 | in1),
+                    Opcode::XOR => (BitKind::XOR, in1, in0 ^ in1),
+                    Opcode::SLL => (BitKind::SLL, shamt, in0 << shamt),
+                    Opcode::SRL => (BitKind::SRL, shamt, in0 >> shamt),
+                    _ => (BitKind::SRA, shamt, ((in0 as i32) >> shamt) as u32),
+                };
+                self.log.bits.push(BitOp::new(kind, in0, operand));
+                // the bits table masks the shift amount read from rs2
+                let is_shift = matches!(ix.opcode, Opcode::SLL | Opcode::SRL | Opcode::SRA);
+                if is_shift && !ix.uses_imm {
+                    let mask = BitOp::new(BitKind::AND, in1, SHAMT_MASK);
+                    self.log.bits.push(mask);
+                }
+                self.write_reg(&mut step, out);
+            }
+            Opcode::MUL
+            | Opcode::MULH
+            | Opcode::MULHSU
+            | Opcode::MULHU
+            | Opcode::DIV
+            | Opcode::DIVU
+            | Opcode::REM
+            | Opcode::REMU => {
+                let (in0, in1) = self.read_operands(&mut step);
+                let (kind, out) = match ix.opcode {
+                    Opcode::MUL => (MulDivKind::MUL, in0.wrapping_mul(in1)),
+                    Opcode::MULH => {
+                        let prod = i64::from(in0 as i32) * i64::from(in1 as i32);
+                        (MulDivKind::MULH, (prod >> 32) as u32)
+                    }
+                    Opcode::MULHSU => {
+                        let prod = i64::from(in0 as i32) * i64::from(in1);
+                        (MulDivKind::MULHSU, (prod >> 32) as u32)
+                    }
+                    Opcode::MULHU => {
+                        let prod = u64::from(in0) * u64::from(in1);
+                        (MulDivKind::MULHU, (prod >> 32) as u32)
+                    }
+                    // division by zero gives all bits set, and a remainder
+                    // equal to the dividend
+                    _ if in1 == 0 => match ix.opcode {
+                        Opcode::DIV => (MulDivKind::DIV, u32::MAX),
+                        Opcode::DIVU => (MulDivKind::DIVU, u32::MAX),
+                        Opcode::REM => (MulDivKind::REM, in0),
+                        _ => (MulDivKind::REMU, in0),
+                    },
+                    // signed overflow wraps around, leaving a zero remainder
+                    Opcode::DIV => (
+                        MulDivKind::DIV,
+                        (in0 as i32).wrapping_div(in1 as i32) as u32,
+                    ),
+                    Opcode::DIVU => (MulDivKind::DIVU, in0 / in1),
+                    Opcode::REM => (
+                        MulDivKind::REM,
+                        (in0 as i32).wrapping_rem(in1 as i32) as u32,
+                    ),
+                    _ => (MulDivKind::REMU, in0 % in1),
+                };
+                self.log.muldiv.push(MulDivOp::new(kind, in0, in1));
+                self.write_reg(&mut step, out);
+            }
+            Opcode::LB | Opcode::LBU | Opcode::LH | Opcode::LHU | Opcode::LW => {
+                let base = self.read_reg(&mut step, RS1_CHANNEL, ix.rs1);
+                step.adr_virt = base.wrapping_add(ix.imm);
+                let (len, signed) = match ix.opcode {
+                    Opcode::LB => (1, true),
+                    Opcode::LBU => (1, false),
+                    Opcode::LH => (BYTES_HALF, true),
+                    Opcode::LHU => (BYTES_HALF, false),
+                    _ => (BYTES_WORD, false),
+                };
+                self.check_alignment(step.adr_virt, len)?;
+                let val = self.load(step.adr_virt, len, signed)?;
+                self.write_reg(&mut step, val);
+            }
+            Opcode::SB | Opcode::SH | Opcode::SW => {
+                let base = self.read_reg(&mut step, RS1_CHANNEL, ix.rs1);
+                let val = self.read_reg(&mut step, RS2_CHANNEL, ix.rs2);
+                step.adr_virt = base.wrapping_add(ix.imm);
+                let len = match ix.opcode {
+                    Opcode::SB => 1,
+                    Opcode::SH => BYTES_HALF,
+                    _ => BYTES_WORD,
+                };
+                self.check_alignment(step.adr_virt, len)?;
+                self.store(step.adr_virt, len, val)?;
+            }
+            Opcode::LUI => {
+                self.write_reg(&mut step, ix.imm << UPPER_IMM_SHIFT);
+            }
+            Opcode::AUIPC => {
+                let upper_imm = ix.imm << UPPER_IMM_SHIFT;
+                let op = ArithOp::new(ArithKind::ADD, self.pc, upper_imm);
+                self.log.arith.push(op);
+                self.write_reg(&mut step, self.pc.wrapping_add(upper_imm));
+            }
+            Opcode::ECALL => self.ecall(&mut step)?,
+            // there is no debugger to transfer control to
+            Opcode::EBREAK => {}
+            Opcode::JAL => {
+                step.next_pc = self.pc.wrapping_add(ix.imm);
+                self.write_reg(&mut step, self.pc.wrapping_add(INSTRUCTION_BYTES as u32));
+            }
+            Opcode::JALR => {
+                let base = self.read_reg(&mut step, RS1_CHANNEL, ix.rs1);
+                let target = base.wrapping_add(ix.imm);
+                // the bits table extracts the lsb of the target
+                self.log.bits.push(BitOp::new(BitKind::AND, target, 1));
+                step.next_pc = target & !1;
+                self.write_reg(&mut step, self.pc.wrapping_add(INSTRUCTION_BYTES as u32));
+            }
+            Opcode::BEQ | Opcode::BNE | Opcode::BLT | Opcode::BGE | Opcode::BLTU | Opcode::BGEU => {
+                let in0 = self.read_reg(&mut step, RS1_CHANNEL, ix.rs1);
+                let in1 = self.read_reg(&mut step, RS2_CHANNEL, ix.rs2);
+                let cmp = match ix.opcode {
+                    Opcode::BLT => Some(ArithKind::LTS),
+                    Opcode::BGE => Some(ArithKind::GES),
+                    Opcode::BLTU => Some(ArithKind::LTU),
+                    Opcode::BGEU => Some(ArithKind::GEU),
+                    _ => None,
+                };
+                if let Some(kind) = cmp {
+                    self.log.arith.push(ArithOp::new(kind, in0, in1));
+                }
+                step.take_branch = match ix.opcode {
+                    Opcode::BEQ => in0 == in1,
+                    Opcode::BNE => in0 != in1,
+                    Opcode::BLT => (in0 as i32) < (in1 as i32),
+                    Opcode::BGE => (in0 as i32) >= (in1 as i32),
+                    Opcode::BLTU => in0 < in1,
+                    _ => in0 >= in1,
+                };
+                if step.take_branch {
+                    step.next_pc = self.pc.wrapping_add(ix.imm);
+                }
+            }
+        }
+
+        self.pc = step.next_pc;
+        self.clock += 1;
+        self.log.cpu.push(step);
+        Ok(())
+    }
+
+    /// Executes the syscall selected by register `a7`. Reading and writing
+    /// zero bytes is allowed, and has no effect.
+    fn ecall(&mut self, step: &mut CpuStep) -> Result<()> {
+        let num = self.read_reg(step, RS1_CHANNEL, REG_A7);
+        let a0 = self.read_reg(step, RS2_CHANNEL, REG_A0);
+        let len = self.read_reg(step, RD_CHANNEL, REG_A2);
+        let syscall = Syscall::from_u32(num).ok_or_else(|| anyhow!("unknown syscall {num}"))?;
+        let kind = match syscall {
+            Syscall::Halt => {
+                self.log.exit_code = Some(a0);
+                return Ok(());
+            }
+            Syscall::Read => {
+                ensure!(a0 == FD_STDIN, "cannot read from file descriptor {a0}");
+                IoKind::Read
+            }
+            Syscall::Write => {
+                ensure!(a0 == FD_STDOUT, "cannot write to file descriptor {a0}");
+                IoKind::Write
+            }
+        };
+        if len == 0 {
+            return Ok(());
+        }
+
+        // the buffer address is read by the io table, at the same time as the
+        // transferred bytes
+        let time = self.timestamp(0);
+        let buf = self.reg(REG_A1);
+        self.log.mem.push(MemOp {
+            on: true,
+            time,
+            kind: MemKind::Read,
+            adr: MemAddress::new(Segment::Reg, REG_A1.into()),
+            val: buf,
+        });
+        ensure!(
+            buf.checked_add(len - 1).is_some(),
+            "buffer of {len} bytes at {buf:#x} exceeds the address space",
+        );
+
+        let (pos, bytes) = match kind {
+            IoKind::Read => {
+                let pos = self.log.tape.input_end();
+                let bytes = self
+                    .input
+                    .get(pos..pos + len as usize)
+                    .with_context(|| format!("input stream has less than {len} bytes left"))?
+                    .to_vec();
+                for (adr, &byte) in (buf..).zip(&bytes) {
+                    self.mem.insert(adr, byte);
+                    self.log.mem.push(MemOp {
+                        on: true,
+                        time,
+                        kind: MemKind::Write,
+                        adr: MemAddress::new(Segment::Main, adr as usize),
+                        val: byte.into(),
+                    });
+                }
+                self.log.tape.input.extend(&bytes);
+                (pos, bytes)
+            }
+            IoKind::Write => {
+                let pos = self.log.tape.output_end();
+                let bytes: Vec<u8> = (buf..=buf + (len - 1)).map(|adr| self.mem(adr)).collect();
+                for (adr, &byte) in (buf..).zip(&bytes) {
+                    self.log.mem.push(MemOp {
+                        on: true,
+                        time,
+                        kind: MemKind::Read,
+                        adr: MemAddress::new(Segment::Main, adr as usize),
+                        val: byte.into(),
+                    });
+                }
+                self.log.tape.output.extend(&bytes);
+                (pos, bytes)
+            }
+        };
+        self.log.io.push(IoOp {
+            kind,
+            clock: self.clock,
+            buf,
+            pos,
+            bytes,
+        });
+        Ok(())
+    }
+
+    /// Reads the little-endian instruction word at the program counter.
+    fn fetch(&self) -> Result<u32> {
+        ensure!(
+            self.mem.contains_key(&self.pc),
+            "no instruction at pc {:#x}",
+            self.pc,
+        );
+        let bytes = core::array::from_fn(|i| self.mem(self.pc.wrapping_add(i as u32)));
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// The timestamp of an access on memory channel `channel`. Clock 0 is
+    /// offset past [`INIT_TIME`](crate::mem::INIT_TIME), so that the initial
+    /// image is written before any access.
+    fn timestamp(&self, channel: usize) -> usize {
+        (self.clock + 1) * N_MEM_CHANNELS + channel
+    }
+
+    /// Reads `rs1`, along with either `rs2` or the immediate value.
+    fn read_operands(&mut self, step: &mut CpuStep) -> (u32, u32) {
+        let in0 = self.read_reg(step, RS1_CHANNEL, step.ix.rs1);
+        let in1 = if step.ix.uses_imm {
+            step.ix.imm
+        } else {
+            self.read_reg(step, RS2_CHANNEL, step.ix.rs2)
+        };
+        (in0, in1)
+    }
+
+    /// Reads register `reg` on memory channel `channel`.
+    fn read_reg(&mut self, step: &mut CpuStep, channel: usize, reg: u8) -> u32 {
+        let val = self.reg(reg);
+        let op = MemOp {
+            on: true,
+            time: self.timestamp(channel),
+            kind: MemKind::Read,
+            adr: MemAddress::new(Segment::Reg, reg.into()),
+            val,
+        };
+        step.membus[channel] = Some(op);
+        self.log.mem.push(op);
+        val
+    }
+
+    /// Writes `val` to register `rd` on the `rd` memory channel. Writes to
+    /// register `x0` are recorded but have no effect.
+    fn write_reg(&mut self, step: &mut CpuStep, val: u32) {
+        let rd = step.ix.rd;
+        let op = MemOp {
+            on: true,
+            time: self.timestamp(RD_CHANNEL),
+            kind: MemKind::Write,
+            adr: MemAddress::new(Segment::Reg, rd.into()),
+            val,
+        };
+        step.membus[RD_CHANNEL] = Some(op);
+        self.log.mem.push(op);
+        if rd != 0 {
+            self.regs[rd as usize] = val;
+        }
+    }
+
+    /// Traps if an access of `len` bytes at `adr` is misaligned under
+    /// [`Alignment::Strict`]. Aligned accesses are proven by computing
+    /// `adr & (len - 1)` in the bits table.
+    fn check_alignment(&mut self, adr: u32, len: usize) -> Result<()> {
+        if self.alignment == Alignment::Strict && len > 1 {
+            let mask = len as u32 - 1;
+            ensure!(
+                adr & mask == 0,
+                "misaligned access of {len} bytes at {adr:#x}"
+            );
+            self.log.bits.push(BitOp::new(BitKind::AND, adr, mask));
+        }
+        Ok(())
+    }
+
+    /// Reads `len` bytes from main memory starting at `adr`, then extends
+    /// the little-endian value to 32 bits.
+    fn load(&mut self, adr: u32, len: usize, signed: bool) -> Result<u32> {
+        // the bytes table computes the address of each byte in the field,
+        // where it doesn't wrap around the end of the address space
+        ensure!(
+            adr.checked_add(len as u32 - 1).is_some(),
+            "load of {len} bytes at {adr:#x} exceeds the address space",
+        );
+        let time = self.timestamp(0);
+        let bytes: Vec<u8> = (0..len as u32).map(|i| self.mem(adr + i)).collect();
+        for (i, &byte) in bytes.iter().enumerate() {
+            self.log.mem.push(MemOp {
+                on: true,
+                time,
+                kind: MemKind::Read,
+                adr: MemAddress::new(Segment::Main, adr as usize + i),
+                val: byte.into(),
+            });
+        }
+
+        let sign_bit = bytes[len - 1] >> 7;
+        let ext = if signed && sign_bit != 0 { u8::MAX } else { 0 };
+        let mut word = [ext; BYTES_WORD];
+        word[..len].copy_from_slice(&bytes);
+
+        self.log.bytes.push(ByteOp {
+            rw: false,
+            signed,
+            adr_virt: adr,
+            time: time as u32,
+            bytes: bytes.into_iter().rev().collect(),
+            src: 0,
+        });
+        Ok(u32::from_le_bytes(word))
+    }
+
+    /// Writes the `len` least significant bytes of `val` to main memory
+    /// starting at `adr`.
+    fn store(&mut self, adr: u32, len: usize, val: u32) -> Result<()> {
+        ensure!(
+            adr.checked_add(len as u32 - 1).is_some(),
+            "store of {len} bytes at {adr:#x} exceeds the address space",
+        );
+        let time = self.timestamp(0);
+        let bytes = &val.to_le_bytes()[..len];
+        for (i, &byte) in bytes.iter().enumerate() {
+            let adr = adr + i as u32;
+            self.mem.insert(adr, byte);
+            self.log.mem.push(MemOp {
+                on: true,
+                time,
+                kind: MemKind::Write,
+                adr: MemAddress::new(Segment::Main, adr as usize),
+                val: byte.into(),
+            });
+        }
+
+        self.log.bytes.push(ByteOp {
+            rw: true,
+            signed: false,
+            adr_virt: adr,
+            time: time as u32,
+            bytes: bytes.iter().rev().copied().collect(),
+            src: val,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Halts with the exit code in a0.
+    const HALT: [u32; 2] = [
+        0x05d00893, // addi a7, x0, 93
+        0x00000073, // ecall
+    ];
+
+    /// Returns `program` followed by [`HALT`].
+    fn with_halt(program: &[u32]) -> Vec<u32> {
+        [program, &HALT].concat()
+    }
+
+    #[test]
+    fn test_arith() {
+        let program = [
+            0x00500093, // addi x1, x0, 5
+            0xff900113, // addi x2, x0, -7
+            0x002081b3, // add x3, x1, x2
+            0x40208233, // sub x4, x1, x2
+            0x0020a2b3, // slt x5, x1, x2
+            0x0020b333, // sltu x6, x1, x2
+            0x00108093, // addi x1, x1, 1
+        ];
+        let mut m = Machine::from_words(0, &program);
+        for _ in program {
+            m.step().unwrap();
+        }
+        assert_eq!(m.reg(3), -2i32 as u32);
+        assert_eq!(m.reg(4), 12);
+        assert_eq!(m.reg(5), 0);
+        assert_eq!(m.reg(6), 1);
+        assert_eq!(m.reg(1), 6);
+
+        let log = m.log;
+        assert_eq!(log.cpu.len(), program.len());
+        assert_eq!(log.arith.len(), program.len());
+        assert!(log.bits.is_empty() && log.bytes.is_empty());
+
+        // addi x1, x1, 1 reads x1 before writing it
+        let last = log.cpu.last().unwrap();
+        let rs1 = last.membus[RS1_CHANNEL].unwrap();
+        let rd = last.membus[RD_CHANNEL].unwrap();
+        assert!(rs1.time < rd.time);
+        assert_eq!((rs1.val, rd.val), (5, 6));
+        assert!(last.membus[RS2_CHANNEL].is_none());
+    }
+
+    #[test]
+    fn test_bits() {
+        let program = [
+            0xff000093, // addi x1, x0, -16
+            0x00300113, // addi x2, x0, 3
+            0x0020f1b3, // and x3, x1, x2
+            0x0020e233, // or x4, x1, x2
+            0x0ff0c293, // xori x5, x1, 255
+            0x00209333, // sll x6, x1, x2
+            0x4020d3b3, // sra x7, x1, x2
+            0x0010d413, // srli x8, x1, 1
+        ];
+        let log = Machine::from_words(0, &with_halt(&program))
+            .run(100)
+            .unwrap();
+        // sll and sra also mask the shift amount in x2
+        assert_eq!(log.bits.len(), 8);
+
+        let mut m = Machine::from_words(0, &program);
+        for _ in program {
+            m.step().unwrap();
+        }
+        assert_eq!(m.reg(3), 0);
+        assert_eq!(m.reg(4), -13i32 as u32);
+        assert_eq!(m.reg(5), 0xffffff0f);
+        assert_eq!(m.reg(6), -128i32 as u32);
+        assert_eq!(m.reg(7), -2i32 as u32);
+        assert_eq!(m.reg(8), 0x7ffffff8);
+    }
+
+    #[test]
+    fn test_muldiv() {
+        let program = [
+            0xff900093, // addi x1, x0, -7
+            0x00200113, // addi x2, x0, 2
+            0x022081b3, // mul x3, x1, x2
+            0x02209233, // mulh x4, x1, x2
+            0x0220b2b3, // mulhu x5, x1, x2
+            0x0220c333, // div x6, x1, x2
+            0x0220e3b3, // rem x7, x1, x2
+            0x0220d433, // divu x8, x1, x2
+            0x020144b3, // div x9, x2, x0
+            0x02017533, // remu x10, x2, x0
+        ];
+        let log = Machine::from_words(0, &with_halt(&program))
+            .run(100)
+            .unwrap();
+        assert_eq!(log.muldiv.len(), 8);
+
+        let mut m = Machine::from_words(0, &program);
+        for _ in program {
+            m.step().unwrap();
+        }
+        assert_eq!(m.reg(3), -14i32 as u32);
+        assert_eq!(m.reg(4), u32::MAX);
+        assert_eq!(m.reg(5), 1);
+        assert_eq!(m.reg(6), -3i32 as u32);
+        assert_eq!(m.reg(7), -1i32 as u32);
+        assert_eq!(m.reg(8), 0x7ffffffc);
+        assert_eq!(m.reg(9), u32::MAX);
+        assert_eq!(m.reg(10), 2);
+    }
+
+    #[test]
+    fn test_upper_imm() {
+        let program = [
+            0x123452b7, // lui x5, 0x12345
+            0x00001317, // auipc x6, 1
+            0xfffff397, // auipc x7, 0xfffff
+        ];
+        let mut m = Machine::from_words(0, &program);
+        for _ in program {
+            m.step().unwrap();
+        }
+        assert_eq!(m.reg(5), 0x12345000);
+        assert_eq!(m.reg(6), 0x1004);
+        assert_eq!(m.reg(7), 0xfffff008);
+        assert_eq!(m.log.arith.len(), 2);
+    }
+
+    #[test]
+    fn test_load_store() {
+        let program = [
+            0x40000093, // addi x1, x0, 1024
+            0xf8100113, // addi x2, x0, -127
+            0x0020a023, // sw x2, 0(x1)
+            0x00108223, // sb x1, 4(x1)
+            0x00008183, // lb x3, 0(x1)
+            0x0000c203, // lbu x4, 0(x1)
+            0x0020d283, // lhu x5, 2(x1)
+            0x0040a303, // lw x6, 4(x1)
+        ];
+        let mut m = Machine::from_words(0, &program);
+        for _ in program {
+            m.step().unwrap();
+        }
+        assert_eq!(m.mem(1024), 0x81);
+        assert_eq!(m.mem(1027), 0xff);
+        assert_eq!(m.mem(1028), 0x00);
+        assert_eq!(m.reg(3), -127i32 as u32);
+        assert_eq!(m.reg(4), 0x81);
+        assert_eq!(m.reg(5), 0xffff);
+        assert_eq!(m.reg(6), 0);
+
+        let log = m.log;
+        assert_eq!(log.bytes.len(), 6);
+        let n_main = log.mem.iter().filter(|op| op.adr.seg == Segment::Main);
+        assert_eq!(n_main.count(), 4 + 1 + 1 + 1 + 2 + 4);
+
+        // lb x3, 0(x1) reads one byte at address 1024
+        let step = &log.cpu[4];
+        assert_eq!(step.adr_virt, 1024);
+        assert_eq!(step.membus[RD_CHANNEL].unwrap().val, -127i32 as u32);
+    }
+
+    #[test]
+    fn test_final_mem() {
+        let program = [
+            0x40000093, // addi x1, x0, 1024
+            0xf8100113, // addi x2, x0, -127
+            0x0020a023, // sw x2, 0(x1)
+            0x00008023, // sb x0, 0(x1)
+        ];
+        let log = Machine::from_words(0, &with_halt(&program))
+            .run(100)
+            .unwrap();
+        let final_mem = log.final_mem();
+
+        assert_eq!(final_mem.get(Segment::Main, 1024), Some(0));
+        assert_eq!(final_mem.get(Segment::Main, 1025), Some(0xff));
+        assert_eq!(final_mem.get(Segment::Reg, 1), Some(1024));
+        assert_eq!(final_mem.get(Segment::Reg, 2), Some(-127i32 as u32));
+        // the image is part of the final memory, but register x0 is not
+        assert_eq!(final_mem.get(Segment::Main, 0), Some(0x93));
+        assert_eq!(final_mem.get(Segment::Reg, 0), None);
+        assert_eq!(final_mem.get(Segment::Reg, 3), None);
+    }
+
+    #[test]
+    fn test_run_segments() {
+        use plonky2::field::goldilocks_field::GoldilocksField;
+
+        use crate::vm::asm::assemble;
+        type F = GoldilocksField;
+
+        let program = assemble(
+            "
+                li t0, 1024
+                li t1, 5
+            loop:
+                sw t1, 0(t0)
+                addi t0, t0, 4
+                addi t1, t1, -1
+                bne t1, zero, loop
+                li a7, 93
+                li a0, 7
+                ecall
+            ",
+        )
+        .unwrap();
+        let log = Machine::from_words(0, &program).run(1000).unwrap();
+        let logs = Machine::from_words(0, &program)
+            .run_segments(4, 1000)
+            .unwrap();
+        let (last, segments) = logs.split_last().unwrap();
+
+        // every segment but the last runs for exactly 4 instructions
+        let n_steps: usize = logs.iter().map(|log| log.cpu.len()).sum();
+        assert_eq!(n_steps, log.cpu.len());
+        assert!(segments.iter().all(|log| log.cpu.len() == 4));
+        assert!(segments.iter().all(|log| log.exit_code.is_none()));
+        assert_eq!(last.exit_code, Some(7));
+
+        // each segment starts from the pc and clock in which the previous one
+        // stopped
+        for [prev, next] in logs.array_windows::<2>() {
+            assert_eq!(next.pc_start, prev.cpu.last().unwrap().next_pc);
+            assert_eq!(next.cpu[0].clock, 0);
+        }
+
+        // and pages out the memory in which the next one starts
+        let mut roots: Vec<_> = logs.iter().map(|log| log.memory().root::<F>()).collect();
+        let mut log = log;
+        let pages = log.restrict_to_touched_pages::<F>();
+        roots.push(pages.page_out(&log.final_mem()).unwrap());
+        for (mut log, [start, end]) in logs.into_iter().zip(roots.array_windows::<2>()) {
+            let pages = log.restrict_to_touched_pages::<F>();
+            pages.page_in(*start).unwrap();
+            assert_eq!(pages.page_out(&log.final_mem()).unwrap(), *end);
+        }
+    }
+
+    #[test]
+    fn test_touched_pages() {
+        use plonky2::field::goldilocks_field::GoldilocksField;
+
+        use crate::mem::page::PAGE_BYTES;
+        type F = GoldilocksField;
+
+        let program = [
+            0x40000093, // addi x1, x0, 1024
+            0x0000c103, // lbu x2, 0(x1)
+        ];
+        let far = 16 * PAGE_BYTES as u32;
+        let image = with_halt(&program)
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .zip(0..)
+            .map(|(b, adr)| (adr, b))
+            .chain([(1024, 7), (far, 9)]);
+        let mut log = Machine::new(0, image).run(100).unwrap();
+        let root = log.memory().root::<F>();
+        let n_image = log.mem_image().len();
+
+        // the untouched page is left out of the image
+        let pages = log.restrict_to_touched_pages::<F>();
+        assert_eq!(log.mem_image().len(), n_image - 1);
+        assert!(!log.image.contains_key(&far));
+        assert_eq!(log.image.get(&1024), Some(&7));
+        assert_eq!(pages.page_in(root).unwrap(), log.mem_image());
+    }
+
+    #[test]
+    fn test_alignment() {
+        use crate::vm::asm::assemble;
+
+        let program = assemble(
+            "
+                li x1, 1024
+                li x2, 0x11223344
+                sw x2, 3(x1)
+                lhu x3, 5(x1)
+                lh x4, 2(x1)
+                lw x5, 0(x1)
+            ",
+        )
+        .unwrap();
+        let program = with_halt(&program);
+
+        // misaligned accesses are split into consecutive bytes, spanning words
+        let log = Machine::from_words(0, &program).run(100).unwrap();
+        let loaded: Vec<_> = log.cpu[4..7]
+            .iter()
+            .map(|step| step.membus[RD_CHANNEL].unwrap().val)
+            .collect();
+        assert_eq!(loaded, [0x1122, 0x4400, 0x44000000]);
+        assert_eq!(log.bytes[0].adr_virt, 1027);
+        assert!(log.bits.is_empty());
+
+        // they trap under the strict policy
+        let strict = Machine::from_words(0, &program).with_alignment(Alignment::Strict);
+        let err = strict.run(100).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("misaligned access of 4 bytes at 0x403"));
+
+        // and aligned accesses are checked by the bits table
+        let aligned = assemble("li x1, 1024\nsw x1, 4(x1)\nlh x2, 6(x1)\nlb x3, 7(x1)").unwrap();
+        let log = Machine::from_words(0, &with_halt(&aligned))
+            .with_alignment(Alignment::Strict)
+            .run(100)
+            .unwrap();
+        assert_eq!(log.bits.len(), 2);
+    }
+
+    #[test]
+    fn test_strict_alignment_trap() {
+        use crate::vm::asm::assemble;
+
+        for (src, msg) in [
+            ("lw x2, 2(x1)", "misaligned access of 4 bytes at 0x402"),
+            ("sw x1, 1(x1)", "misaligned access of 4 bytes at 0x401"),
+        ] {
+            let program = assemble(&format!("li x1, 1024\n{src}")).unwrap();
+            Machine::from_words(0, &with_halt(&program))
+                .run(100)
+                .unwrap();
+
+            // the trap stops the machine before the access
+            let mut m = Machine::from_words(0, &program).with_alignment(Alignment::Strict);
+            m.step().unwrap();
+            let err = m.step().unwrap_err();
+            assert!(err.to_string().contains(msg));
+            assert!(m.log.mem.iter().all(|op| op.adr.seg != Segment::Main));
+        }
+    }
+
+    #[test]
+    fn test_wrapping_access() {
+        use crate::vm::asm::assemble;
+
+        // the last byte of the address space can be accessed
+        let program = assemble("lb x1, -1(x0)\nsb x1, -1(x0)").unwrap();
+        Machine::from_words(0, &with_halt(&program))
+            .run(100)
+            .unwrap();
+
+        // but accesses can't wrap around its end, even when permissive
+        for (src, msg) in [
+            ("lw x1, -2(x0)", "load of 4 bytes at 0xfffffffe"),
+            ("sh x1, -1(x0)", "store of 2 bytes at 0xffffffff"),
+        ] {
+            let program = assemble(src).unwrap();
+            let mut m = Machine::from_words(0, &program).with_alignment(Alignment::Permissive);
+            let err = m.step().unwrap_err();
+            assert!(err.to_string().contains(msg));
+            assert!(m.log.mem.iter().all(|op| op.adr.seg != Segment::Main));
+        }
+    }
+
+    #[test]
+    fn test_control_flow() {
+        let program = [
+            0x00300093, // addi x1, x0, 3
+            0xfff08093, // loop: addi x1, x1, -1
+            0xfe009ee3, // bne x1, x0, loop
+            0x00c0016f, // jal x2, end
+            0x00100193, // addi x3, x0, 1
+            0x00000013, // nop
+            0x0000006f, // end: jal x0, 0 (never reached)
+        ];
+        let mut m = Machine::from_words(0, &program);
+        for _ in 0..8 {
+            m.step().unwrap();
+        }
+        assert_eq!(m.reg(1), 0);
+        assert_eq!(m.reg(2), 16);
+        assert_eq!(m.reg(3), 0);
+        assert_eq!(m.pc(), 24);
+
+        let taken: Vec<_> = m.log.cpu.iter().map(|s| s.take_branch).collect();
+        assert_eq!(
+            taken,
+            [false, false, true, false, true, false, false, false]
+        );
+
+        // jal x0, 0 loops forever
+        assert!(m.clone().run(10).is_err());
+
+        // and execution can't fall off the end of the program
+        let err = Machine::from_words(0, &program[..3]).run(10).unwrap_err();
+        assert!(err.to_string().contains("no instruction at pc 0xc"));
+    }
+
+    #[test]
+    fn test_gen_traces() {
+        use plonky2::field::goldilocks_field::GoldilocksField;
+
+        use crate::stark::Table;
+        type F = GoldilocksField;
+
+        let program = [
+            0x40000093, // addi x1, x0, 1024
+            0x0020a023, // sw x2, 0(x1)
+            0x0000c203, // lbu x4, 0(x1)
+            0x0020f1b3, // and x3, x1, x2
+        ];
+        let log = Machine::from_words(0, &with_halt(&program))
+            .run(100)
+            .unwrap();
+        let n_mem_ops = log.mem.len();
+        let n_image = log.mem_image().len();
+
+        let traces = log.gen_traces::<F>(4);
+        assert_eq!(traces[Table::Arith as usize][0].len(), 256);
+        assert_eq!(traces[Table::Bits as usize][0].len(), 4);
+        assert_eq!(traces[Table::Bytes as usize][0].len(), 256);
+        // the cpu trace ends with at least one halted row
+        assert_eq!(traces[Table::Cpu as usize][0].len(), 8);
+        assert_eq!(traces[Table::Io as usize][0].len(), 4);
+        // the memory trace also writes each non-zero byte of the image
+        assert!(traces[Table::Mem as usize][0].len() >= n_mem_ops + n_image);
+        // the final memory holds at least 4 registers, 4 stored bytes and
+        // the image
+        assert!(traces[Table::MemFinal as usize][0].len() >= 4 + 4 + n_image);
+        assert_eq!(traces[Table::MemInit as usize][0].len(), 16);
+        assert_eq!(traces[Table::MulDiv as usize][0].len(), 256);
+        assert_eq!(traces[Table::Program as usize][0].len(), 8);
+    }
+
+    #[test]
+    fn test_elf() {
+        use plonky2::field::goldilocks_field::GoldilocksField;
+
+        let elf = include_bytes!("fixtures/add.elf");
+        let log = Machine::from_elf(elf).unwrap().run(100).unwrap();
+        assert_eq!(log.cpu.len(), 8);
+        assert_eq!(log.cpu[0].pc, 0x100);
+        assert_eq!(log.exit_code, Some(0));
+
+        let store = log.bytes.last().unwrap();
+        assert!(store.rw);
+        assert_eq!((store.adr_virt, store.src), (0x208, 42));
+
+        let public_values = log.public_values::<GoldilocksField>();
+        assert_eq!(public_values.pc_start, 0x100);
+        assert_eq!(public_values.pc_end, 0x120);
+        assert_eq!(public_values.clock_end, 8);
+    }
+
+    #[test]
+    fn test_jalr() {
+        let program = [
+            0x00d00093, // addi x1, x0, 13
+            0x003080e7, // jalr x1, 3(x1)
+        ];
+        let mut m = Machine::from_words(0, &program);
+        m.step().unwrap();
+        m.step().unwrap();
+        assert_eq!(m.pc(), 16);
+        assert_eq!(m.reg(1), 8);
+        // which holds no instruction
+        assert!(m.step().is_err());
+    }
+
+    #[test]
+    fn test_syscalls() {
+        use crate::vm::asm::assemble;
+
+        let program = assemble(
+            "
+                li a7, 63
+                li a0, 0
+                li a1, 0x100
+                li a2, 3
+                ecall
+                li a7, 64
+                li a0, 1
+                li a1, 0x101
+                li a2, 2
+                ecall
+                li a2, 0
+                ecall
+                ebreak
+                li a7, 93
+                li a0, 7
+                ecall
+                li a0, 8
+            ",
+        )
+        .unwrap();
+        let m = Machine::from_words(0, &program).with_input([1, 2, 3, 4]);
+        let log = m.clone().run(100).unwrap();
+        assert_eq!(log.exit_code, Some(7));
+        assert_eq!(log.cpu.len(), program.len() - 1);
+        assert_eq!(log.tape.input, [1, 2, 3]);
+        assert_eq!(log.tape.output, [2, 3]);
+
+        // the empty write transfers nothing
+        assert_eq!(log.io.len(), 2);
+        assert_eq!((log.io[1].kind, log.io[1].buf), (IoKind::Write, 0x101));
+        let write = log
+            .cpu
+            .iter()
+            .rev()
+            .find(|step| step.ix.opcode == Opcode::ECALL);
+        assert_eq!(write.unwrap().output_pos, 2);
+
+        // each transferred byte is a single main memory access
+        let n_main = log.mem.iter().filter(|op| op.adr.seg == Segment::Main);
+        assert_eq!(n_main.count(), 5);
+
+        // reading past the end of the input fails
+        assert!(m.clone().with_input([1, 2]).run(100).is_err());
+
+        // so do unknown syscalls and file descriptors
+        let bad_fd = assemble("li a7, 64\nli a2, 1\necall").unwrap();
+        assert!(Machine::from_words(0, &bad_fd).run(10).is_err());
+        let bad_num = assemble("li a7, 1\necall").unwrap();
+        assert!(Machine::from_words(0, &bad_num).run(10).is_err());
+    }
+}
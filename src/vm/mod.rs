@@ -1 +1,7 @@
+pub mod asm;
+pub mod decode;
+pub mod elf;
+pub mod machine;
 pub mod opcode;
+
+pub(crate) use machine::Machine;
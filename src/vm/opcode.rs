@@ -16,6 +16,16 @@ pub(crate) enum Opcode {
     SRL,
     SRA,
 
+    // multiplication and division
+    MUL,
+    MULH,
+    MULHSU,
+    MULHU,
+    DIV,
+    DIVU,
+    REM,
+    REMU,
+
     // memory load ops
     LW,
     LB,
@@ -28,6 +38,14 @@ pub(crate) enum Opcode {
     SB,
     SH,
 
+    // upper immediates
+    LUI,
+    AUIPC,
+
+    // environment calls
+    ECALL,
+    EBREAK,
+
     // jumps
     JAL,
     JALR,